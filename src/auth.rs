@@ -0,0 +1,77 @@
+use poem::http::StatusCode;
+use poem::{Endpoint, IntoResponse, Middleware, Request, Response, Result};
+
+use crate::schemas::common::UnauthorizedResponse;
+
+pub const API_KEY_HEADER: &str = "X-API-Key";
+
+/// Paths reachable without a key, relative to the API prefix, so load
+/// balancer probes keep working
+const PUBLIC_PATHS: [&str; 3] = ["/health", "/health/live", "/health/ready"];
+
+/// Rejects requests whose `X-API-Key` header doesn't match `api_key` with a
+/// 401. Does nothing when no key is configured.
+pub struct ApiKeyAuth {
+    api_key: Option<String>,
+}
+
+impl ApiKeyAuth {
+    pub fn new(api_key: Option<String>) -> Self {
+        Self { api_key }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for ApiKeyAuth {
+    type Output = ApiKeyAuthEndpoint<E>;
+
+    fn transform(&self, inner: E) -> Self::Output {
+        ApiKeyAuthEndpoint {
+            inner,
+            api_key: self.api_key.clone(),
+        }
+    }
+}
+
+pub struct ApiKeyAuthEndpoint<E> {
+    inner: E,
+    api_key: Option<String>,
+}
+
+impl<E: Endpoint> Endpoint for ApiKeyAuthEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        if let Some(expected) = &self.api_key
+            && !PUBLIC_PATHS.contains(&req.uri().path())
+        {
+            let provided = req
+                .headers()
+                .get(API_KEY_HEADER)
+                .map(|value| value.as_bytes());
+            if !provided.is_some_and(|key| keys_match(key, expected.as_bytes())) {
+                return Ok(unauthorized());
+            }
+        }
+
+        self.inner.call(req).await.map(IntoResponse::into_response)
+    }
+}
+
+/// Compares in time independent of where the keys first differ, so the key
+/// can't be guessed byte by byte from response timings.
+pub(crate) fn keys_match(provided: &[u8], expected: &[u8]) -> bool {
+    provided.len() == expected.len()
+        && provided
+            .iter()
+            .zip(expected)
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn unauthorized() -> Response {
+    let body = serde_json::json!({ "message": UnauthorizedResponse::default().message });
+    Response::builder()
+        .status(StatusCode::UNAUTHORIZED)
+        .content_type("application/json; charset=utf-8")
+        .body(body.to_string())
+}
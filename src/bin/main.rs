@@ -1,11 +1,16 @@
-use poem::listener::TcpListener;
-use rendering_engine::core::renderer::RenderingEngine;
+use rendering_engine::core::renderer::{EngineSettings, RenderingEngine};
+use rendering_engine::core::{registry, selftest, storage::ObjectStorage, template};
+use rendering_engine::listener::LimitedAcceptor;
 use rendering_engine::settings::get_config;
 use rendering_engine::{AppState, init_openapi_route};
 use tracing::Level;
 
+use std::path::Path;
 use std::sync::Arc;
-use tracing_subscriber;
+use std::time::Duration;
+
+/// How long shutdown waits for in-flight requests, and then renders, to finish
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
 
 #[tokio::main]
 async fn main() {
@@ -21,22 +26,137 @@ async fn main() {
     tracing::info!("Initializing Rendering Service...");
 
     let config = get_config();
-    tracing::info!("run with config: {:?}", config);
+    tracing::info!(config = ?config, "resolved configuration");
+
+    if let Some(path) = &config.library_registry_path {
+        let changes = registry::reload(Path::new(path)).expect("Failed to load library registry");
+        tracing::info!(
+            "Loaded library registry from {}: added {:?}, overridden {:?}",
+            path,
+            changes.added,
+            changes.updated
+        );
+    }
+
+    if config.allowed_cdn_hosts.is_none() {
+        tracing::warn!(
+            "allowed_cdn_hosts is not set: requests may load any script published to {}",
+            template::DEFAULT_CDN_HOSTS.join(", ")
+        );
+    }
 
-    let engine = Arc::new(RenderingEngine::new().expect("Failed to initialize rendering engine"));
+    let settings = EngineSettings::from(&config);
+    settings
+        .check_chrome_path()
+        .expect("Failed to find Chrome/Chromium");
+    let engine = Arc::new(
+        RenderingEngine::with_settings(settings).expect("Failed to initialize rendering engine"),
+    );
 
     // Init App State
-    let app_state = Arc::new(AppState { engine: engine });
+    let storage = ObjectStorage::from_config(&config).expect("Failed to configure object storage");
+    if storage.is_some() {
+        tracing::info!("Object storage sink enabled for output_key renders");
+    }
+    let app_state = Arc::new(AppState { engine, storage });
 
     tracing::info!("Rendering engine initialized successfully");
 
+    if std::env::args().any(|arg| arg == "--selftest") {
+        std::process::exit(run_selftest(&app_state.engine).await);
+    }
+
+    if config.warmup {
+        tracing::info!("Warming up rendering engine, /health reports 503 until done");
+        app_state.engine.spawn_warm_up();
+    }
+
     let app = init_openapi_route(app_state.clone(), &config);
     tracing::info!("run server on {}:{}", config.host, config.port);
-    poem::Server::new(TcpListener::bind(format!(
-        "{}:{}",
-        config.host, config.port
-    )))
-    .run(app)
-    .await
-    .unwrap()
+    let acceptor = LimitedAcceptor::bind(&config).expect("Failed to bind listener");
+    let result = poem::Server::new_with_acceptor(acceptor)
+        .run_with_graceful_shutdown(app, shutdown_signal(), Some(SHUTDOWN_TIMEOUT))
+        .await;
+
+    let engine = &app_state.engine;
+    if !engine.drain(SHUTDOWN_TIMEOUT).await {
+        tracing::warn!(
+            "Renders still running after {:?}, closing browsers anyway",
+            SHUTDOWN_TIMEOUT
+        );
+    }
+    engine.shutdown();
+    tracing::info!("Shutdown complete");
+
+    result.unwrap()
+}
+
+/// Resolves on SIGTERM or Ctrl-C, after which the server stops accepting
+/// connections and lets in-flight requests finish.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to listen for Ctrl-C");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    tracing::info!("Shutdown signal received, draining in-flight renders");
+}
+
+/// Render one chart per library and print pass/fail, returning the exit code.
+async fn run_selftest(engine: &RenderingEngine) -> i32 {
+    let output_dir =
+        std::env::temp_dir().join(format!("rendering-engine-selftest-{}", std::process::id()));
+    if let Err(e) = std::fs::create_dir_all(&output_dir) {
+        eprintln!(
+            "Failed to create self-test output dir {}: {}",
+            output_dir.display(),
+            e
+        );
+        return 1;
+    }
+
+    println!(
+        "Running self-test, writing outputs to {}",
+        output_dir.display()
+    );
+    let results = selftest::run(engine, &output_dir).await;
+
+    for result in &results {
+        match &result.outcome {
+            Ok(path) => println!(
+                "PASS  {:<16} {:>8.0?}  {}",
+                result.library,
+                result.duration,
+                path.display()
+            ),
+            Err(e) => println!(
+                "FAIL  {:<16} {:>8.0?}  {}",
+                result.library, result.duration, e
+            ),
+        }
+    }
+
+    let failed = results.iter().filter(|r| !r.passed()).count();
+    println!(
+        "{}/{} libraries passed",
+        results.len() - failed,
+        results.len()
+    );
+
+    if failed == 0 { 0 } else { 1 }
 }
@@ -0,0 +1,71 @@
+use poem::error::ReadBodyError;
+use poem::http::{StatusCode, header};
+use poem::{Body, Endpoint, IntoResponse, Middleware, Request, Response, Result};
+
+use crate::schemas::common::PayloadTooLargeResponse;
+
+/// Rejects request bodies over `max_bytes` with a 413 before they are
+/// parsed. Unlike `poem::middleware::SizeLimit`, requests without a
+/// `Content-Length` (probes, chunked uploads) aren't refused outright but
+/// read up to the limit.
+pub struct BodyLimit {
+    max_bytes: usize,
+}
+
+impl BodyLimit {
+    pub fn new(max_bytes: usize) -> Self {
+        Self { max_bytes }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for BodyLimit {
+    type Output = BodyLimitEndpoint<E>;
+
+    fn transform(&self, inner: E) -> Self::Output {
+        BodyLimitEndpoint {
+            inner,
+            max_bytes: self.max_bytes,
+        }
+    }
+}
+
+pub struct BodyLimitEndpoint<E> {
+    inner: E,
+    max_bytes: usize,
+}
+
+impl<E: Endpoint> Endpoint for BodyLimitEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let content_length = req
+            .headers()
+            .get(header::CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<usize>().ok());
+
+        match content_length {
+            Some(length) if length > self.max_bytes => return Ok(self.payload_too_large()),
+            Some(_) => {}
+            None => match req.take_body().into_bytes_limit(self.max_bytes).await {
+                Ok(bytes) => req.set_body(Body::from_bytes(bytes)),
+                Err(ReadBodyError::PayloadTooLarge) => return Ok(self.payload_too_large()),
+                Err(e) => return Err(e.into()),
+            },
+        }
+
+        self.inner.call(req).await.map(IntoResponse::into_response)
+    }
+}
+
+impl<E> BodyLimitEndpoint<E> {
+    fn payload_too_large(&self) -> Response {
+        let body = serde_json::json!({
+            "message": PayloadTooLargeResponse::new(self.max_bytes).message
+        });
+        Response::builder()
+            .status(StatusCode::PAYLOAD_TOO_LARGE)
+            .content_type("application/json; charset=utf-8")
+            .body(body.to_string())
+    }
+}
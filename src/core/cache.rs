@@ -0,0 +1,140 @@
+use parking_lot::Mutex;
+use poem_openapi::types::ToJSON;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::schemas::render::RenderRequest;
+
+type CacheKey = [u8; 32];
+
+/// Options that only shape the response around the rendered bytes, left out
+/// of the key so e.g. `return_base64` requests share the plain render's entry
+const RESPONSE_OPTIONS: [&str; 5] = [
+    "return_base64",
+    "return_html",
+    "capture_console",
+    "error_as_image",
+    "output_key",
+];
+
+struct Entry {
+    bytes: Vec<u8>,
+    inserted: Instant,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Entries {
+    map: HashMap<CacheKey, Entry>,
+    /// Bumped on every access; an entry's `last_used` orders eviction
+    clock: u64,
+}
+
+/// In-memory LRU cache of rendered output, keyed on a SHA-256 of the whole
+/// request so identical renders skip the browser pool entirely.
+pub struct RenderCache {
+    entries: Mutex<Entries>,
+    max_entries: usize,
+    ttl: Duration,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl RenderCache {
+    pub fn new(max_entries: usize, ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(Entries::default()),
+            max_entries,
+            ttl,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Hash of the request's canonical JSON. Object keys serialize in sorted
+    /// order, so field order in the original body doesn't matter.
+    pub fn key(request: &RenderRequest) -> CacheKey {
+        let mut json = request.to_json().unwrap_or_default();
+        if let Some(options) = json.get_mut("options").and_then(|o| o.as_object_mut()) {
+            for option in RESPONSE_OPTIONS {
+                options.remove(option);
+            }
+        }
+        Sha256::digest(json.to_string().as_bytes()).into()
+    }
+
+    pub fn get(&self, key: &CacheKey) -> Option<Vec<u8>> {
+        let mut entries = self.entries.lock();
+        entries.clock += 1;
+        let clock = entries.clock;
+
+        let bytes = match entries.map.get_mut(key) {
+            Some(entry) if entry.inserted.elapsed() < self.ttl => {
+                entry.last_used = clock;
+                Some(entry.bytes.clone())
+            }
+            Some(_) => {
+                entries.map.remove(key);
+                None
+            }
+            None => None,
+        };
+
+        let counter = if bytes.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        bytes
+    }
+
+    pub fn insert(&self, key: CacheKey, bytes: Vec<u8>) {
+        let mut entries = self.entries.lock();
+        entries.clock += 1;
+        let clock = entries.clock;
+
+        if !entries.map.contains_key(&key) && entries.map.len() >= self.max_entries {
+            let ttl = self.ttl;
+            entries
+                .map
+                .retain(|_, entry| entry.inserted.elapsed() < ttl);
+            if entries.map.len() >= self.max_entries
+                && let Some(oldest) = entries
+                    .map
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.last_used)
+                    .map(|(key, _)| *key)
+            {
+                entries.map.remove(&oldest);
+            }
+        }
+
+        entries.map.insert(
+            key,
+            Entry {
+                bytes,
+                inserted: Instant::now(),
+                last_used: clock,
+            },
+        );
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -0,0 +1,176 @@
+use anyhow::{Result, anyhow};
+use serde_json::Value as JsonValue;
+
+use crate::core::validation::json_kind;
+
+/// The JSON Schema keywords `validate` understands. Templates using any
+/// other keyword are rejected, so a schema can't appear to check more than
+/// it does.
+const KEYWORDS: [&str; 5] = ["type", "required", "properties", "items", "minItems"];
+
+const TYPES: [&str; 7] = [
+    "object", "array", "string", "number", "integer", "boolean", "null",
+];
+
+/// Check that `schema` only uses the supported subset of JSON Schema:
+/// `type` (a name or a list of names), `required`, `properties`, `items`
+/// and `minItems`.
+pub fn check(schema: &JsonValue) -> Result<()> {
+    let schema = schema
+        .as_object()
+        .ok_or_else(|| anyhow!("data_schema must be an object"))?;
+
+    for (keyword, value) in schema {
+        match keyword.as_str() {
+            "type" => {
+                let names = match value {
+                    JsonValue::Array(names) => names.iter().collect(),
+                    name => vec![name],
+                };
+                for name in names {
+                    if !name.as_str().is_some_and(|name| TYPES.contains(&name)) {
+                        return Err(anyhow!("data_schema has unknown type {}", name));
+                    }
+                }
+            }
+            "required" => {
+                if !value
+                    .as_array()
+                    .is_some_and(|names| names.iter().all(JsonValue::is_string))
+                {
+                    return Err(anyhow!("data_schema required must be a list of names"));
+                }
+            }
+            "properties" => {
+                let properties = value
+                    .as_object()
+                    .ok_or_else(|| anyhow!("data_schema properties must be an object"))?;
+                for property in properties.values() {
+                    check(property)?;
+                }
+            }
+            "items" => check(value)?,
+            "minItems" => {
+                if !value.is_u64() {
+                    return Err(anyhow!("data_schema minItems must be a whole number"));
+                }
+            }
+            _ => {
+                return Err(anyhow!(
+                    "data_schema keyword '{}' is not supported (supported: {})",
+                    keyword,
+                    KEYWORDS.join(", ")
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Where `data` breaks `schema`, as the path below `data` and a message.
+/// `schema` is expected to have passed `check`.
+pub fn validate(data: &JsonValue, schema: &JsonValue) -> Vec<(Vec<String>, String)> {
+    let mut errors = Vec::new();
+    validate_at(data, schema, &mut Vec::new(), &mut errors);
+    errors
+}
+
+fn validate_at(
+    value: &JsonValue,
+    schema: &JsonValue,
+    path: &mut Vec<String>,
+    errors: &mut Vec<(Vec<String>, String)>,
+) {
+    if let Some(types) = schema.get("type") {
+        let types: Vec<&str> = match types {
+            JsonValue::Array(names) => names.iter().filter_map(JsonValue::as_str).collect(),
+            name => name.as_str().into_iter().collect(),
+        };
+        if !types.iter().any(|name| is_type(value, name)) {
+            let expected: Vec<&str> = types.iter().map(|name| type_phrase(name)).collect();
+            errors.push((
+                path.clone(),
+                format!(
+                    "must be {}, got {}",
+                    expected.join(" or "),
+                    json_kind(value)
+                ),
+            ));
+            // Nested checks would only repeat the mismatch
+            return;
+        }
+    }
+
+    if let Some(object) = value.as_object() {
+        for name in schema
+            .get("required")
+            .and_then(JsonValue::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(JsonValue::as_str)
+        {
+            if !object.contains_key(name) {
+                let mut location = path.clone();
+                location.push(name.to_string());
+                errors.push((location, format!("{} is required", name)));
+            }
+        }
+
+        for (name, property) in schema
+            .get("properties")
+            .and_then(JsonValue::as_object)
+            .into_iter()
+            .flatten()
+        {
+            if let Some(field) = object.get(name) {
+                path.push(name.clone());
+                validate_at(field, property, path, errors);
+                path.pop();
+            }
+        }
+    }
+
+    if let Some(items) = value.as_array() {
+        if let Some(min) = schema.get("minItems").and_then(JsonValue::as_u64)
+            && (items.len() as u64) < min
+        {
+            errors.push((
+                path.clone(),
+                format!("must have at least {} items, got {}", min, items.len()),
+            ));
+        }
+
+        if let Some(item_schema) = schema.get("items") {
+            for (index, item) in items.iter().enumerate() {
+                path.push(index.to_string());
+                validate_at(item, item_schema, path, errors);
+                path.pop();
+            }
+        }
+    }
+}
+
+fn is_type(value: &JsonValue, name: &str) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => false,
+    }
+}
+
+fn type_phrase(name: &str) -> &str {
+    match name {
+        "object" => "an object",
+        "array" => "an array",
+        "string" => "a string",
+        "number" => "a number",
+        "integer" => "an integer",
+        "boolean" => "a boolean",
+        other => other,
+    }
+}
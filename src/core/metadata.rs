@@ -0,0 +1,254 @@
+use anyhow::{Result, anyhow};
+use lopdf::{Document, Object, Stream, dictionary};
+use std::io::Cursor;
+
+const PNG_SIGNATURE: &[u8] = &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+const INCHES_PER_METER: f64 = 39.3701;
+
+/// Stamp physical resolution metadata on an encoded image without touching
+/// its pixels. PNG gets a `pHYs` chunk, JPEG gets its JFIF density updated.
+/// Other formats are returned unchanged.
+pub fn stamp_ppi(bytes: Vec<u8>, format: &str, ppi: u32) -> Result<Vec<u8>> {
+    match format {
+        "png" => stamp_png(bytes, ppi),
+        "jpeg" | "jpg" => stamp_jpeg(bytes, ppi),
+        _ => Ok(bytes),
+    }
+}
+
+fn stamp_png(bytes: Vec<u8>, ppi: u32) -> Result<Vec<u8>> {
+    if !bytes.starts_with(PNG_SIGNATURE) {
+        return Err(anyhow!("Cannot stamp PPI: output is not a PNG"));
+    }
+
+    let pixels_per_meter = (ppi as f64 * INCHES_PER_METER).round() as u32;
+    let mut data = Vec::with_capacity(9);
+    data.extend_from_slice(&pixels_per_meter.to_be_bytes());
+    data.extend_from_slice(&pixels_per_meter.to_be_bytes());
+    data.push(1); // unit: meter
+
+    let mut output = Vec::with_capacity(bytes.len() + 21);
+    output.extend_from_slice(PNG_SIGNATURE);
+
+    let mut offset = PNG_SIGNATURE.len();
+    while offset + 8 <= bytes.len() {
+        let length = u32::from_be_bytes(bytes[offset..offset + 4].try_into()?) as usize;
+        let chunk_end = offset + 12 + length;
+        if chunk_end > bytes.len() {
+            return Err(anyhow!("Cannot stamp PPI: truncated PNG chunk"));
+        }
+
+        let chunk_type = &bytes[offset + 4..offset + 8];
+        // Drop any existing pHYs so ours is the only one
+        if chunk_type != b"pHYs" {
+            output.extend_from_slice(&bytes[offset..chunk_end]);
+        }
+        if chunk_type == b"IHDR" {
+            write_png_chunk(&mut output, b"pHYs", &data);
+        }
+
+        offset = chunk_end;
+    }
+
+    Ok(output)
+}
+
+fn write_png_chunk(output: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(chunk_type);
+    hasher.update(data);
+
+    output.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    output.extend_from_slice(chunk_type);
+    output.extend_from_slice(data);
+    output.extend_from_slice(&hasher.finalize().to_be_bytes());
+}
+
+/// Re-encode a PNG at 16 bits per channel, widening each 8-bit sample `v` to
+/// `v * 257` so that 0 and 255 map to the ends of the 16-bit range. This
+/// only changes the container depth; the source precision stays 8-bit.
+pub fn png_to_16bit(bytes: &[u8]) -> Result<Vec<u8>> {
+    let mut decoder = png::Decoder::new(Cursor::new(bytes));
+    decoder.set_transformations(png::Transformations::EXPAND);
+    let mut reader = decoder.read_info()?;
+
+    let mut buffer = vec![
+        0;
+        reader
+            .output_buffer_size()
+            .ok_or_else(|| anyhow!("PNG is too large to re-encode"))?
+    ];
+    let frame = reader.next_frame(&mut buffer)?;
+    if frame.bit_depth == png::BitDepth::Sixteen {
+        return Ok(bytes.to_vec());
+    }
+
+    let samples: Vec<u8> = buffer[..frame.buffer_size()]
+        .iter()
+        .flat_map(|&v| (v as u16 * 257).to_be_bytes())
+        .collect();
+
+    let mut output = Vec::with_capacity(samples.len() / 2);
+    let mut encoder = png::Encoder::new(&mut output, frame.width, frame.height);
+    encoder.set_color(frame.color_type);
+    encoder.set_depth(png::BitDepth::Sixteen);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&samples)?;
+    writer.finish()?;
+
+    Ok(output)
+}
+
+fn stamp_jpeg(mut bytes: Vec<u8>, ppi: u32) -> Result<Vec<u8>> {
+    if !bytes.starts_with(&[0xff, 0xd8]) {
+        return Err(anyhow!("Cannot stamp PPI: output is not a JPEG"));
+    }
+
+    let density = ppi.min(u16::MAX as u32) as u16;
+    let has_jfif = bytes.len() >= 18 && bytes[2..4] == [0xff, 0xe0] && &bytes[6..11] == b"JFIF\0";
+
+    if has_jfif {
+        bytes[13] = 1; // unit: dots per inch
+        bytes[14..16].copy_from_slice(&density.to_be_bytes());
+        bytes[16..18].copy_from_slice(&density.to_be_bytes());
+        return Ok(bytes);
+    }
+
+    let mut app0 = vec![0xff, 0xe0, 0x00, 0x10];
+    app0.extend_from_slice(b"JFIF\0");
+    app0.extend_from_slice(&[0x01, 0x01, 0x01]); // version 1.1, unit: dots per inch
+    app0.extend_from_slice(&density.to_be_bytes());
+    app0.extend_from_slice(&density.to_be_bytes());
+    app0.extend_from_slice(&[0x00, 0x00]); // no thumbnail
+
+    bytes.splice(2..2, app0);
+    Ok(bytes)
+}
+
+/// Pixel dimensions of an encoded PNG, JPEG or WebP image, read from its
+/// header without decoding. `None` for other formats or unexpected bytes.
+pub fn image_dimensions(bytes: &[u8], format: &str) -> Option<(u32, u32)> {
+    match format {
+        "png" => png_dimensions(bytes),
+        "jpeg" | "jpg" => jpeg_dimensions(bytes),
+        "webp" => webp_dimensions(bytes),
+        _ => None,
+    }
+}
+
+fn png_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    // IHDR is always the first chunk
+    if !bytes.starts_with(PNG_SIGNATURE) || bytes.get(12..16)? != b"IHDR" {
+        return None;
+    }
+    let width = u32::from_be_bytes(bytes.get(16..20)?.try_into().ok()?);
+    let height = u32::from_be_bytes(bytes.get(20..24)?.try_into().ok()?);
+    Some((width, height))
+}
+
+fn jpeg_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    if !bytes.starts_with(&[0xff, 0xd8]) {
+        return None;
+    }
+
+    let mut offset = 2;
+    loop {
+        let marker = *bytes.get(offset + 1)?;
+        if bytes[offset] != 0xff {
+            return None;
+        }
+        let length = u16::from_be_bytes(bytes.get(offset + 2..offset + 4)?.try_into().ok()?);
+        // Start-of-frame markers, except DHT, JPG and DAC which share the range
+        if (0xc0..=0xcf).contains(&marker) && ![0xc4, 0xc8, 0xcc].contains(&marker) {
+            let frame = bytes.get(offset + 5..offset + 9)?;
+            let height = u16::from_be_bytes([frame[0], frame[1]]);
+            let width = u16::from_be_bytes([frame[2], frame[3]]);
+            return Some((width as u32, height as u32));
+        }
+        offset += 2 + length as usize;
+    }
+}
+
+fn webp_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    if bytes.get(0..4)? != b"RIFF" || bytes.get(8..12)? != b"WEBP" {
+        return None;
+    }
+
+    let data = bytes.get(20..)?;
+    let le = |range: std::ops::Range<usize>| {
+        let mut value = [0u8; 4];
+        value[..range.len()].copy_from_slice(data.get(range)?);
+        Some(u32::from_le_bytes(value))
+    };
+    match bytes.get(12..16)? {
+        b"VP8 " if data.get(3..6)? == [0x9d, 0x01, 0x2a] => {
+            Some((le(6..8)? & 0x3fff, le(8..10)? & 0x3fff))
+        }
+        b"VP8L" if data.first()? == &0x2f => {
+            let bits = le(1..5)?;
+            Some(((bits & 0x3fff) + 1, ((bits >> 14) & 0x3fff) + 1))
+        }
+        b"VP8X" => Some((le(4..7)? + 1, le(7..10)? + 1)),
+        _ => None,
+    }
+}
+
+/// Attach `contents` to a PDF as an embedded file (shown in the attachments
+/// panel of viewers such as Acrobat, Preview or Firefox's PDF viewer).
+pub fn embed_pdf_attachment(
+    pdf: Vec<u8>,
+    file_name: &str,
+    mime_type: &str,
+    contents: Vec<u8>,
+) -> Result<Vec<u8>> {
+    let mut document = Document::load_mem(&pdf)?;
+
+    let size = contents.len() as i64;
+    let embedded_file = document.add_object(Stream::new(
+        dictionary! {
+            "Type" => "EmbeddedFile",
+            "Subtype" => Object::Name(mime_type.as_bytes().to_vec()),
+            "Params" => dictionary! { "Size" => size },
+        },
+        contents,
+    ));
+
+    let file_spec = document.add_object(dictionary! {
+        "Type" => "Filespec",
+        "F" => Object::string_literal(file_name),
+        "UF" => Object::string_literal(file_name),
+        "AFRelationship" => "Data",
+        "EF" => dictionary! { "F" => embedded_file },
+    });
+
+    let embedded_files = dictionary! {
+        "Names" => vec![Object::string_literal(file_name), file_spec.into()],
+    };
+
+    // Keep any existing name trees (e.g. Chrome's named destinations)
+    let names_ref = document
+        .catalog()?
+        .get(b"Names")
+        .and_then(Object::as_reference)
+        .ok();
+    match names_ref {
+        Some(id) => document
+            .get_dictionary_mut(id)?
+            .set("EmbeddedFiles", embedded_files),
+        None => {
+            let catalog = document.catalog_mut()?;
+            match catalog.get_mut(b"Names").and_then(Object::as_dict_mut) {
+                Ok(names) => names.set("EmbeddedFiles", embedded_files),
+                Err(_) => catalog.set("Names", dictionary! { "EmbeddedFiles" => embedded_files }),
+            }
+        }
+    }
+
+    document
+        .catalog_mut()?
+        .set("AF", vec![Object::Reference(file_spec)]);
+
+    let mut output = Vec::with_capacity(pdf.len());
+    document.save_to(&mut output)?;
+    Ok(output)
+}
@@ -0,0 +1,204 @@
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::core::renderer::{HealthStatus, PoolStatus};
+
+const PREFIX: &str = "rendering_engine_";
+
+/// Upper bounds of the render duration histogram, in seconds
+const DURATION_BUCKETS: [f64; 10] = [0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0];
+
+/// Per-pool gauges reported from `HealthStatus::pools`: name, help, value
+type PoolGauge = (&'static str, &'static str, fn(&PoolStatus) -> usize);
+
+const POOL_GAUGES: [PoolGauge; 3] = [
+    ("browser_pool_size", "Browser instances per pool", |pool| {
+        pool.size
+    }),
+    (
+        "browser_pool_idle",
+        "Idle browser instances per pool",
+        |pool| pool.available,
+    ),
+    (
+        "browser_pool_capacity",
+        "Maximum browser instances per pool",
+        |pool| pool.capacity,
+    ),
+];
+
+/// Counters and a duration histogram for `/metrics`, updated once per
+/// render. Labels are kept in sorted maps so the output is stable between
+/// scrapes.
+#[derive(Default)]
+pub struct RenderMetrics {
+    renders: AtomicU64,
+    by_library: Mutex<BTreeMap<String, u64>>,
+    by_format: Mutex<BTreeMap<String, u64>>,
+    failures: Mutex<BTreeMap<&'static str, u64>>,
+    /// Non-cumulative count per bucket; the last slot is `+Inf`
+    duration_buckets: [AtomicU64; DURATION_BUCKETS.len() + 1],
+    duration_sum_micros: AtomicU64,
+}
+
+impl RenderMetrics {
+    /// Count a finished render, successful or not.
+    pub fn record_render(&self, library: &str, format: &str) {
+        self.renders.fetch_add(1, Ordering::Relaxed);
+        *self
+            .by_library
+            .lock()
+            .entry(library.to_string())
+            .or_default() += 1;
+        *self.by_format.lock().entry(format.to_string()).or_default() += 1;
+    }
+
+    pub fn record_failure(&self, kind: &'static str) {
+        *self.failures.lock().entry(kind).or_default() += 1;
+    }
+
+    /// Add a successful render's wall time to the histogram.
+    pub fn record_duration(&self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        let bucket = DURATION_BUCKETS
+            .iter()
+            .position(|bound| seconds <= *bound)
+            .unwrap_or(DURATION_BUCKETS.len());
+        self.duration_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.duration_sum_micros
+            .fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// Prometheus text exposition format (0.0.4), with pool and permit
+    /// gauges taken from `status`.
+    pub fn encode(&self, status: &HealthStatus) -> String {
+        let mut out = String::new();
+
+        let total = self.renders.load(Ordering::Relaxed);
+        family(
+            &mut out,
+            "renders_total",
+            "counter",
+            "Renders finished since startup",
+        )
+        .sample("", total);
+
+        let mut renders = family(
+            &mut out,
+            "renders_by_library_total",
+            "counter",
+            "Renders finished per library",
+        );
+        for (library, count) in self.by_library.lock().iter() {
+            renders.sample(&label("library", library), *count);
+        }
+
+        let mut renders = family(
+            &mut out,
+            "renders_by_format_total",
+            "counter",
+            "Renders finished per output format",
+        );
+        for (format, count) in self.by_format.lock().iter() {
+            renders.sample(&label("format", format), *count);
+        }
+
+        let mut failures = family(
+            &mut out,
+            "render_failures_total",
+            "counter",
+            "Failed renders per failure type",
+        );
+        for (kind, count) in self.failures.lock().iter() {
+            failures.sample(&label("type", kind), *count);
+        }
+
+        let mut durations = family(
+            &mut out,
+            "render_duration_seconds",
+            "histogram",
+            "Wall time of successful renders, excluding the wait for a render slot",
+        );
+        let mut cumulative = 0;
+        for (index, count) in self.duration_buckets.iter().enumerate() {
+            cumulative += count.load(Ordering::Relaxed);
+            let bound = DURATION_BUCKETS
+                .get(index)
+                .map_or_else(|| "+Inf".to_string(), f64::to_string);
+            durations.suffixed("_bucket", &label("le", &bound), cumulative);
+        }
+        let sum_micros = self.duration_sum_micros.load(Ordering::Relaxed);
+        durations.suffixed("_sum", "", sum_micros as f64 / 1_000_000.0);
+        durations.suffixed("_count", "", cumulative);
+
+        family(
+            &mut out,
+            "slow_renders_total",
+            "counter",
+            "Renders over slow_render_threshold",
+        )
+        .sample("", status.slow_renders);
+
+        for (name, help, value) in POOL_GAUGES {
+            let mut gauge = family(&mut out, name, "gauge", help);
+            for pool in &status.pools {
+                gauge.sample(&label("raster_engine", pool.raster_engine), value(pool));
+            }
+        }
+
+        family(
+            &mut out,
+            "render_permits_available",
+            "gauge",
+            "Render slots not in use",
+        )
+        .sample("", status.available_permits);
+        family(
+            &mut out,
+            "render_permits_max",
+            "gauge",
+            "Render slots in total",
+        )
+        .sample("", status.max_concurrent);
+
+        out
+    }
+}
+
+/// Writes the `HELP`/`TYPE` header of one metric and then its samples.
+struct Family<'a> {
+    out: &'a mut String,
+    name: &'a str,
+}
+
+fn family<'a>(out: &'a mut String, name: &'a str, kind: &str, help: &str) -> Family<'a> {
+    let _ = writeln!(out, "# HELP {}{} {}", PREFIX, name, help);
+    let _ = writeln!(out, "# TYPE {}{} {}", PREFIX, name, kind);
+    Family { out, name }
+}
+
+impl Family<'_> {
+    fn sample(&mut self, labels: &str, value: impl fmt::Display) {
+        self.suffixed("", labels, value);
+    }
+
+    /// A sample of a derived series, such as a histogram's `_bucket`
+    fn suffixed(&mut self, suffix: &str, labels: &str, value: impl fmt::Display) {
+        let _ = writeln!(
+            self.out,
+            "{}{}{}{} {}",
+            PREFIX, self.name, suffix, labels, value
+        );
+    }
+}
+
+fn label(name: &str, value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("{{{}=\"{}\"}}", name, escaped)
+}
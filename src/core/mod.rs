@@ -1,3 +1,13 @@
+pub mod cache;
+pub mod data_schema;
+pub mod error_card;
+pub mod metadata;
+pub mod metrics;
+pub mod page_server;
 pub mod registry;
 pub mod renderer;
+pub mod selftest;
+pub mod sprite;
+pub mod storage;
 pub mod template;
+pub mod validation;
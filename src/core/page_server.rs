@@ -0,0 +1,108 @@
+use anyhow::Result;
+use once_cell::sync::OnceCell;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::time::Duration;
+
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+type Pages = Arc<Mutex<HashMap<String, Arc<str>>>>;
+
+/// Loopback HTTP server for render pages too large for a `data:` URL.
+/// Started on first use and shared by every engine in the process.
+static PAGE_SERVER: OnceCell<PageServer> = OnceCell::new();
+
+struct PageServer {
+    addr: SocketAddr,
+    pages: Pages,
+}
+
+/// A page reachable at `url` until dropped.
+pub struct ServedPage {
+    pub url: String,
+    token: String,
+    pages: Pages,
+}
+
+impl Drop for ServedPage {
+    fn drop(&mut self) {
+        self.pages.lock().remove(&self.token);
+    }
+}
+
+/// Serve `html` from the loopback server under an unguessable path, so
+/// other local processes can't read another render's page.
+pub fn serve(html: String) -> Result<ServedPage> {
+    let server = PAGE_SERVER.get_or_try_init(start)?;
+
+    let token = format!(
+        "{:016x}{:016x}",
+        RandomState::new().hash_one(&html),
+        RandomState::new().hash_one(&html)
+    );
+    server.pages.lock().insert(token.clone(), html.into());
+
+    Ok(ServedPage {
+        url: format!("http://{}/{}", server.addr, token),
+        token,
+        pages: server.pages.clone(),
+    })
+}
+
+fn start() -> Result<PageServer> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    let addr = listener.local_addr()?;
+    let pages: Pages = Arc::default();
+
+    let served = pages.clone();
+    std::thread::Builder::new()
+        .name("page-server".to_string())
+        .spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let pages = served.clone();
+                std::thread::spawn(move || {
+                    if let Err(e) = respond(stream, &pages) {
+                        tracing::debug!("Page server request failed: {}", e);
+                    }
+                });
+            }
+        })?;
+    tracing::info!("Serving large render pages from http://{}", addr);
+
+    Ok(PageServer { addr, pages })
+}
+
+fn respond(mut stream: TcpStream, pages: &Pages) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // The headers don't matter, but must be read before responding
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+        header.clear();
+    }
+
+    let page = match request_line.split_whitespace().collect::<Vec<_>>()[..] {
+        ["GET", path, ..] => pages.lock().get(path.trim_start_matches('/')).cloned(),
+        _ => None,
+    };
+    let (status, body) = match &page {
+        Some(html) => ("200 OK", html.as_bytes()),
+        None => ("404 Not Found", &b""[..]),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\n\
+         Cache-Control: no-store\r\nConnection: close\r\n\r\n",
+        status,
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()
+}
@@ -1,13 +1,270 @@
+use anyhow::{Result, anyhow};
 use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::Deserialize;
+use serde_json::{Value as JsonValue, json};
 use std::collections::HashMap;
+use std::path::Path;
 
+use crate::core::data_schema;
+use crate::core::template::{DEFAULT_CDN_HOSTS, validate_cdn_url};
+
+#[derive(Clone, Deserialize, PartialEq)]
 pub struct LibraryTemplate {
+    /// Library script loaded before the init script. Only the built-in
+    /// `raw-html` leaves it empty, loading no script at all
     pub cdn_url: String,
+    /// Version used when a request leaves `library.version` out
+    #[serde(default)]
+    pub default_version: Option<String>,
     pub wait_selector: String,
     pub init_script: String,
+    /// Whether `data` must be a JSON object (e.g. an ECharts option)
+    #[serde(default)]
+    pub expects_object: bool,
+    /// Named themes selectable with the `theme` option. The init script
+    /// receives the chosen one as `{theme}`: `{ name, config }` or `null`
+    #[serde(default)]
+    pub themes: HashMap<String, JsonValue>,
+    /// Element serialized for `format: "svg"`. Libraries drawing to a canvas
+    /// leave it unset and can't produce SVG. The init script gets the
+    /// renderer to use as `{renderer}`: `'svg'` or `'canvas'`, and whether
+    /// to animate as `{animation}`: `true` or `false`
+    #[serde(default)]
+    pub svg_selector: Option<String>,
+    /// Whether the init script runs code taken from `data`. Such libraries
+    /// are refused unless `allow_request_code` is set in the server config
+    #[serde(default)]
+    pub runs_request_code: bool,
+    /// How the page signals it is ready, see `ReadyStrategy`. Unset, the
+    /// init script sets `window.renderReady` itself
+    #[serde(default)]
+    pub ready_strategy: Option<ReadyStrategy>,
+    /// Markup added to `<head>`, e.g. a theme or plugin stylesheet
+    #[serde(default)]
+    pub head_html: Option<String>,
+    /// Scripts loaded in order after `cdn_url` and before the init script,
+    /// e.g. plugins. `{version}` expands as in `cdn_url`
+    #[serde(default)]
+    pub extra_scripts: Vec<String>,
+    /// Plugins a request can pick with `library.plugins`. Their scripts load
+    /// after `extra_scripts` and the init script receives their globals, in
+    /// request order, as `{plugins}`
+    #[serde(default)]
+    pub plugins: HashMap<String, LibraryPlugin>,
+    /// Minimal JSON Schema `data` is checked against before rendering, see
+    /// `data_schema::check` for the supported keywords. Not applied to
+    /// requests with their own `library.cdn_url`
+    #[serde(default)]
+    pub data_schema: Option<JsonValue>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct LibraryPlugin {
+    pub script_url: String,
+    /// Global the plugin script defines, e.g. `ChartDataLabels`
+    pub global: String,
+}
+
+/// How `generate_html` turns a finished init script into `renderReady`.
+/// A thrown error or a rejection becomes `window.renderError` in each case.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadyStrategy {
+    /// Ready as soon as the init script returns
+    Sync,
+    /// Ready when the promise the init script returns resolves
+    PromiseThen,
+    /// Ready when the init script calls `renderDone()`, e.g. from a library
+    /// event; `renderDone(error)` fails the render instead
+    CallbackEvent,
+}
+
+/// Built-in templates merged with any loaded from `library_registry_path`.
+/// Use `get_template`/`library_names` rather than holding the lock.
+pub static LIBRARY_REGISTRY: Lazy<RwLock<HashMap<String, LibraryTemplate>>> =
+    Lazy::new(|| RwLock::new(builtin_templates()));
+
+/// Alternative names clients use for built-in libraries. Matched after
+/// normalization, so `ECharts` and `e_charts` resolve too.
+const ALIASES: &[(&str, &str)] = &[
+    ("echarts", "apache-echarts"),
+    ("konva", "konvajs"),
+    ("konva-json", "konvajs-json"),
+    ("leafletjs", "leaflet"),
+    ("plotlyjs", "plotly"),
+];
+
+#[derive(Default)]
+pub struct RegistryChanges {
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// Template for `library` or any name that resolves to it, see
+/// `canonical_name`.
+pub fn get_template(library: &str) -> Option<LibraryTemplate> {
+    let name = canonical_name(library)?;
+    LIBRARY_REGISTRY.read().get(&name).cloned()
+}
+
+/// Registered name for `library`. Exact names win; otherwise names and
+/// aliases are compared ignoring case and separators, so `Apache_ECharts`,
+/// `apacheecharts` and `echarts` all resolve to `apache-echarts`.
+pub fn canonical_name(library: &str) -> Option<String> {
+    let registry = LIBRARY_REGISTRY.read();
+    if registry.contains_key(library) {
+        return Some(library.to_string());
+    }
+
+    let key = normalize(library);
+    registry
+        .keys()
+        .find(|name| normalize(name) == key)
+        .cloned()
+        .or_else(|| {
+            ALIASES
+                .iter()
+                .find(|(alias, name)| normalize(alias) == key && registry.contains_key(*name))
+                .map(|(_, name)| name.to_string())
+        })
+}
+
+/// Aliases that resolve to `library` besides its normalized spellings.
+pub fn aliases(library: &str) -> Vec<String> {
+    ALIASES
+        .iter()
+        .filter(|(_, name)| *name == library)
+        .map(|(alias, _)| alias.to_string())
+        .collect()
+}
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Registered library names, sorted.
+pub fn library_names() -> Vec<String> {
+    let mut names: Vec<String> = LIBRARY_REGISTRY.read().keys().cloned().collect();
+    names.sort();
+    names
+}
+
+/// Replace the registry with the built-in templates plus those in `path`
+/// (TOML if the extension is `.toml`, JSON otherwise), keyed by library
+/// name. File entries override built-ins of the same name, keeping the
+/// built-in themes they don't redefine. Nothing is swapped in unless every
+/// entry in the file is valid.
+pub fn reload(path: &Path) -> Result<RegistryChanges> {
+    let mut templates = builtin_templates();
+    for (name, mut template) in load_file(path)? {
+        // Built-in themes and plugins stay available unless the file
+        // redefines them
+        if let Some(builtin) = templates.get(&name) {
+            for (theme, config) in &builtin.themes {
+                template
+                    .themes
+                    .entry(theme.clone())
+                    .or_insert_with(|| config.clone());
+            }
+            for (plugin, config) in &builtin.plugins {
+                template
+                    .plugins
+                    .entry(plugin.clone())
+                    .or_insert_with(|| config.clone());
+            }
+        }
+        templates.insert(name, template);
+    }
+
+    let mut registry = LIBRARY_REGISTRY.write();
+    let mut changes = RegistryChanges::default();
+    for (name, template) in &templates {
+        match registry.get(name) {
+            None => changes.added.push(name.clone()),
+            Some(current) if current != template => changes.updated.push(name.clone()),
+            Some(_) => {}
+        }
+    }
+    changes.removed = registry
+        .keys()
+        .filter(|name| !templates.contains_key(*name))
+        .cloned()
+        .collect();
+
+    changes.added.sort();
+    changes.updated.sort();
+    changes.removed.sort();
+
+    *registry = templates;
+    Ok(changes)
+}
+
+fn load_file(path: &Path) -> Result<HashMap<String, LibraryTemplate>> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+
+    let templates: HashMap<String, LibraryTemplate> =
+        if path.extension().is_some_and(|ext| ext == "toml") {
+            toml::from_str(&contents)?
+        } else {
+            serde_json::from_str(&contents)?
+        };
+
+    let errors: Vec<String> = templates
+        .iter()
+        .filter_map(|(name, template)| {
+            validate_template(template)
+                .err()
+                .map(|e| format!("'{}': {}", name, e))
+        })
+        .collect();
+    if !errors.is_empty() {
+        return Err(anyhow!("Invalid library templates: {}", errors.join("; ")));
+    }
+
+    Ok(templates)
+}
+
+fn validate_template(template: &LibraryTemplate) -> Result<()> {
+    validate_cdn_url(
+        &template.cdn_url.replace("{version}", "0.0.0"),
+        &DEFAULT_CDN_HOSTS,
+    )?;
+    for script in &template.extra_scripts {
+        validate_cdn_url(&script.replace("{version}", "0.0.0"), &DEFAULT_CDN_HOSTS)?;
+    }
+    for plugin in template.plugins.values() {
+        validate_cdn_url(&plugin.script_url, &DEFAULT_CDN_HOSTS)?;
+    }
+
+    if template.wait_selector.trim().is_empty() {
+        return Err(anyhow!("wait_selector must not be empty"));
+    }
+    if let Some(schema) = &template.data_schema {
+        data_schema::check(schema)?;
+    }
+    // The renderer waits for this flag, without it every render times out
+    match template.ready_strategy {
+        None if !template.init_script.contains("renderReady") => {
+            return Err(anyhow!("init_script must set window.renderReady = true"));
+        }
+        Some(ReadyStrategy::CallbackEvent) if !template.init_script.contains("renderDone") => {
+            return Err(anyhow!(
+                "init_script must call renderDone() with ready_strategy callback_event"
+            ));
+        }
+        _ => {}
+    }
+
+    Ok(())
 }
 
-pub static LIBRARY_REGISTRY: Lazy<HashMap<String, LibraryTemplate>> = Lazy::new(|| {
+fn builtin_templates() -> HashMap<String, LibraryTemplate> {
     let mut registry = HashMap::new();
 
     // ECharts
@@ -16,29 +273,133 @@ pub static LIBRARY_REGISTRY: Lazy<HashMap<String, LibraryTemplate>> = Lazy::new(
         LibraryTemplate {
             cdn_url: "https://cdn.jsdelivr.net/npm/echarts@{version}/dist/echarts.min.js"
                 .to_string(),
+            default_version: Some("5.4.0".to_string()),
             wait_selector: "#render-container".to_string(),
             init_script: r#"
-                const chart = echarts.init(document.getElementById('render-container'));
-                chart.setOption({data});
-                window.renderReady = true;
+                const theme = {theme};
+                if (theme) {
+                    echarts.registerTheme(theme.name, theme.config);
+                }
+
+                const chart = echarts.init(
+                    document.getElementById('render-container'),
+                    theme && theme.name,
+                    { renderer: {renderer} }
+                );
+                const option = {data};
+                if (!{animation}) {
+                    option.animation = false;
+                }
+                chart.setOption(option);
             "#
             .to_string(),
+            expects_object: true,
+            svg_selector: Some("#render-container svg".to_string()),
+            runs_request_code: false,
+            ready_strategy: Some(ReadyStrategy::Sync),
+            head_html: None,
+            extra_scripts: Vec::new(),
+            plugins: HashMap::new(),
+            themes: echarts_themes(),
+            // An option without series renders a blank chart
+            data_schema: Some(json!({
+                "type": "object",
+                "required": ["series"],
+                "properties": {"series": {"type": ["array", "object"]}}
+            })),
         },
     );
 
-    // Chart.js
+    // ECharts with a caller-supplied GeoJSON map, e.g. for choropleths.
+    // data: { "geojson": {...}, "mapName": "regions", "option": {...} }
+    // where the option's map series reference `mapName` (default "geo").
+    registry.insert(
+        "echarts-map".to_string(),
+        LibraryTemplate {
+            cdn_url: "https://cdn.jsdelivr.net/npm/echarts@{version}/dist/echarts.min.js"
+                .to_string(),
+            default_version: Some("5.4.0".to_string()),
+            wait_selector: "#render-container".to_string(),
+            init_script: r#"
+                const config = {data};
+                if (!config.geojson) {
+                    throw new Error('echarts-map requires data.geojson');
+                }
+
+                echarts.registerMap(config.mapName || 'geo', config.geojson);
+
+                const theme = {theme};
+                if (theme) {
+                    echarts.registerTheme(theme.name, theme.config);
+                }
+
+                const chart = echarts.init(
+                    document.getElementById('render-container'),
+                    theme && theme.name,
+                    { renderer: {renderer} }
+                );
+                chart.on('finished', () => renderDone());
+                const option = config.option || {};
+                if (!{animation}) {
+                    option.animation = false;
+                }
+                chart.setOption(option);
+            "#
+            .to_string(),
+            expects_object: true,
+            svg_selector: Some("#render-container svg".to_string()),
+            runs_request_code: false,
+            ready_strategy: Some(ReadyStrategy::CallbackEvent),
+            head_html: None,
+            extra_scripts: Vec::new(),
+            plugins: HashMap::new(),
+            themes: echarts_themes(),
+            data_schema: Some(json!({
+                "type": "object",
+                "required": ["geojson"],
+                "properties": {
+                    "geojson": {"type": "object"},
+                    "mapName": {"type": "string"},
+                    "option": {"type": "object"}
+                }
+            })),
+        },
+    );
+
+    // Chart.js; plugins are registered globally before the chart is created
     registry.insert(
         "chartjs".to_string(),
         LibraryTemplate {
             cdn_url: "https://cdn.jsdelivr.net/npm/chart.js@{version}/dist/chart.umd.js"
                 .to_string(),
+            default_version: Some("4.4.0".to_string()),
             wait_selector: "#chart-canvas".to_string(),
             init_script: r#"
+                Chart.register(...{plugins});
                 const ctx = document.getElementById('chart-canvas').getContext('2d');
-                new Chart(ctx, {data});
-                window.renderReady = true;
+                const config = {data};
+                if (!{animation}) {
+                    config.options = { ...config.options, animation: false };
+                }
+                new Chart(ctx, config);
             "#
             .to_string(),
+            expects_object: true,
+            svg_selector: None,
+            runs_request_code: false,
+            ready_strategy: Some(ReadyStrategy::Sync),
+            head_html: None,
+            extra_scripts: Vec::new(),
+            plugins: chartjs_plugins(),
+            themes: HashMap::new(),
+            data_schema: Some(json!({
+                "type": "object",
+                "required": ["type", "data"],
+                "properties": {
+                    "type": {"type": "string"},
+                    "data": {"type": "object"}
+                }
+            })),
         },
     );
 
@@ -47,6 +408,7 @@ pub static LIBRARY_REGISTRY: Lazy<HashMap<String, LibraryTemplate>> = Lazy::new(
         "konvajs".to_string(),
         LibraryTemplate {
             cdn_url: "https://unpkg.com/konva@{version}/konva.min.js".to_string(),
+            default_version: Some("9.2.0".to_string()),
             wait_selector: "#render-container".to_string(),
             init_script: r#"
                 const stage = new Konva.Stage({
@@ -66,9 +428,17 @@ pub static LIBRARY_REGISTRY: Lazy<HashMap<String, LibraryTemplate>> = Lazy::new(
                 }
 
                 layer.draw();
-                window.renderReady = true;
             "#
             .to_string(),
+            expects_object: true,
+            svg_selector: None,
+            runs_request_code: false,
+            ready_strategy: Some(ReadyStrategy::Sync),
+            head_html: None,
+            extra_scripts: Vec::new(),
+            plugins: HashMap::new(),
+            themes: HashMap::new(),
+            data_schema: None,
         },
     );
 
@@ -76,6 +446,7 @@ pub static LIBRARY_REGISTRY: Lazy<HashMap<String, LibraryTemplate>> = Lazy::new(
         "konvajs-json".to_string(),
         LibraryTemplate {
             cdn_url: "https://unpkg.com/konva@{version}/konva.min.js".to_string(),
+            default_version: Some("9.2.0".to_string()),
             wait_selector: "#render-container".to_string(),
             init_script: r#"
                 const container = document.getElementById('render-container');
@@ -87,12 +458,469 @@ pub static LIBRARY_REGISTRY: Lazy<HashMap<String, LibraryTemplate>> = Lazy::new(
                 // Ensure proper dimensions
                 stage.width({width});
                 stage.height({height});
+            "#
+            .to_string(),
+            expects_object: true,
+            svg_selector: None,
+            runs_request_code: false,
+            ready_strategy: Some(ReadyStrategy::Sync),
+            head_html: None,
+            extra_scripts: Vec::new(),
+            plugins: HashMap::new(),
+            themes: HashMap::new(),
+            data_schema: None,
+        },
+    );
+
+    // billboard.js (D3-based); data is the bb.generate() options minus
+    // `bindto`. The packaged build bundles D3.
+    registry.insert(
+        "billboardjs".to_string(),
+        LibraryTemplate {
+            cdn_url: "https://cdn.jsdelivr.net/npm/billboard.js@{version}/dist/billboard.pkgd.min.js"
+                .to_string(),
+            default_version: Some("3.14.0".to_string()),
+            wait_selector: "#render-container".to_string(),
+            init_script: r#"
+                const config = {data};
+
+                const script = document.querySelector('script[src*="billboard"]');
+                const stylesheet = document.createElement('link');
+                stylesheet.rel = 'stylesheet';
+                stylesheet.href = script.src.replace(/billboard\.pkgd(\.min)?\.js.*$/, 'billboard.min.css');
+                stylesheet.onerror = () => {
+                    renderDone(new Error('Failed to load ' + stylesheet.href));
+                };
+                stylesheet.onload = () => {
+                    try {
+                        bb.generate({
+                            ...config,
+                            bindto: '#render-container',
+                            size: { width: {width}, height: {height} },
+                            transition: { duration: 0 },
+                            onrendered() {
+                                renderDone();
+                            }
+                        });
+                    } catch (error) {
+                        renderDone(error);
+                    }
+                };
+                document.head.appendChild(stylesheet);
+            "#
+            .to_string(),
+            expects_object: true,
+            svg_selector: Some("#render-container svg".to_string()),
+            runs_request_code: false,
+            ready_strategy: Some(ReadyStrategy::CallbackEvent),
+            head_html: None,
+            extra_scripts: Vec::new(),
+            plugins: HashMap::new(),
+            themes: HashMap::new(),
+            data_schema: None,
+        },
+    );
+
+    // Highcharts; data is the Highcharts.chart() options. Free for
+    // non-commercial use and evaluation only, see the README.
+    registry.insert(
+        "highcharts".to_string(),
+        LibraryTemplate {
+            cdn_url: "https://code.highcharts.com/{version}/highcharts.js".to_string(),
+            default_version: Some("11.4.8".to_string()),
+            wait_selector: "#render-container".to_string(),
+            init_script: r#"
+                const config = {data};
+                const chart = config.chart || {};
+                config.chart = {
+                    ...chart,
+                    animation: false,
+                    events: {
+                        ...chart.events,
+                        load() {
+                            renderDone();
+                        }
+                    }
+                };
+                config.plotOptions = {
+                    ...config.plotOptions,
+                    series: { ...(config.plotOptions || {}).series, animation: false }
+                };
+
+                Highcharts.chart('render-container', config);
+            "#
+            .to_string(),
+            expects_object: true,
+            svg_selector: Some("#render-container svg.highcharts-root".to_string()),
+            runs_request_code: false,
+            ready_strategy: Some(ReadyStrategy::CallbackEvent),
+            head_html: None,
+            extra_scripts: Vec::new(),
+            plugins: HashMap::new(),
+            themes: HashMap::new(),
+            data_schema: None,
+        },
+    );
 
-                window.renderReady = true;
+    // ApexCharts; data is the ApexCharts options. Drawing is async and
+    // render() resolves once the chart is in the DOM, so the script returns
+    // its promise. `chart.width`/`chart.height` default to the page size.
+    registry.insert(
+        "apexcharts".to_string(),
+        LibraryTemplate {
+            cdn_url: "https://cdn.jsdelivr.net/npm/apexcharts@{version}".to_string(),
+            default_version: Some("3.54.1".to_string()),
+            wait_selector: "#render-container".to_string(),
+            init_script: r#"
+                const config = {data};
+                config.chart = { width: {width}, height: {height}, ...config.chart };
+                if (!{animation}) {
+                    config.chart.animations = { ...config.chart.animations, enabled: false };
+                }
+
+                const chart = new ApexCharts(document.getElementById('render-container'), config);
+                return chart.render();
+            "#
+            .to_string(),
+            expects_object: true,
+            svg_selector: Some("#render-container svg.apexcharts-svg".to_string()),
+            runs_request_code: false,
+            ready_strategy: Some(ReadyStrategy::PromiseThen),
+            head_html: None,
+            extra_scripts: Vec::new(),
+            plugins: HashMap::new(),
+            themes: HashMap::new(),
+            data_schema: None,
+        },
+    );
+
+    // Plotly; data: { "traces": [...], "layout": {...} }. newPlot resolves
+    // once the plot is drawn, so the script returns its promise.
+    registry.insert(
+        "plotly".to_string(),
+        LibraryTemplate {
+            cdn_url: "https://cdn.jsdelivr.net/npm/plotly.js-dist-min@{version}/plotly.min.js"
+                .to_string(),
+            default_version: Some("2.35.2".to_string()),
+            wait_selector: "#render-container".to_string(),
+            init_script: r#"
+                const config = {data};
+                const layout = { width: {width}, height: {height}, ...config.layout };
+
+                return Plotly.newPlot('render-container', config.traces || [], layout, {
+                    staticPlot: true,
+                    displayModeBar: false
+                });
+            "#
+            .to_string(),
+            expects_object: true,
+            themes: HashMap::new(),
+            svg_selector: None,
+            runs_request_code: false,
+            ready_strategy: Some(ReadyStrategy::PromiseThen),
+            head_html: None,
+            extra_scripts: Vec::new(),
+            plugins: HashMap::new(),
+            data_schema: None,
+        },
+    );
+
+    // D3 has no standard entrypoint, so the caller supplies the drawing code.
+    // data: { "renderCode": "...", ...anything else }
+    // renderCode is the body of a function (container, d3, data, width, height)
+    // where container is #render-container and data the whole request data. It
+    // may return a Promise; ready is set once that resolves.
+    registry.insert(
+        "d3".to_string(),
+        LibraryTemplate {
+            cdn_url: "https://cdn.jsdelivr.net/npm/d3@{version}/dist/d3.min.js".to_string(),
+            default_version: Some("7.9.0".to_string()),
+            wait_selector: "#render-container".to_string(),
+            init_script: r#"
+                const config = {data};
+                if (typeof config.renderCode !== 'string') {
+                    throw new Error('d3 requires data.renderCode');
+                }
+                const render = new Function(
+                    'container', 'd3', 'data', 'width', 'height', config.renderCode
+                );
+                const container = document.getElementById('render-container');
+                return render(container, d3, config, {width}, {height});
             "#
             .to_string(),
+            expects_object: true,
+            themes: HashMap::new(),
+            svg_selector: Some("#render-container svg".to_string()),
+            runs_request_code: true,
+            ready_strategy: Some(ReadyStrategy::PromiseThen),
+            head_html: None,
+            extra_scripts: Vec::new(),
+            plugins: HashMap::new(),
+            data_schema: None,
+        },
+    );
+
+    // Mermaid diagrams; data: { "definition": "graph TD; A-->B" }. Rendering
+    // is async and syntax errors reject, so both report through the promise.
+    registry.insert(
+        "mermaid".to_string(),
+        LibraryTemplate {
+            cdn_url: "https://cdn.jsdelivr.net/npm/mermaid@{version}/dist/mermaid.min.js"
+                .to_string(),
+            default_version: Some("11.4.1".to_string()),
+            wait_selector: "#render-container".to_string(),
+            init_script: r#"
+                const config = {data};
+                if (typeof config.definition !== 'string') {
+                    throw new Error('mermaid requires data.definition');
+                }
+                mermaid.initialize({ startOnLoad: false });
+                return mermaid.render('graph', config.definition).then(({ svg }) => {
+                    document.getElementById('render-container').innerHTML = svg;
+                });
+            "#
+            .to_string(),
+            expects_object: true,
+            themes: HashMap::new(),
+            svg_selector: Some("#render-container svg".to_string()),
+            runs_request_code: false,
+            ready_strategy: Some(ReadyStrategy::PromiseThen),
+            head_html: None,
+            extra_scripts: Vec::new(),
+            plugins: HashMap::new(),
+            data_schema: None,
+        },
+    );
+
+    // Leaflet static maps.
+    // data: { "center": [lat, lng], "zoom": 13, "markers": [{ "lat", "lng", "label", "color" }],
+    //         "tileUrl": "https://.../{z}/{x}/{y}.png", "attribution": "..." }
+    // Markers are vector circles so no marker images need to load. Capture
+    // waits for the tile layer's `load` event, i.e. every tile in view.
+    registry.insert(
+        "leaflet".to_string(),
+        LibraryTemplate {
+            cdn_url: "https://unpkg.com/leaflet@{version}/dist/leaflet.js".to_string(),
+            default_version: Some("1.9.4".to_string()),
+            wait_selector: "#render-container".to_string(),
+            init_script: r#"
+                const config = {data};
+
+                // Tiles are positioned by leaflet.css, which sits next to the script
+                const script = document.querySelector('script[src*="leaflet"]');
+                const stylesheet = document.createElement('link');
+                stylesheet.rel = 'stylesheet';
+                stylesheet.href = script.src.replace(/\.js(\?.*)?$/, '.css');
+                stylesheet.onerror = () => {
+                    renderDone(new Error('Failed to load ' + stylesheet.href));
+                };
+                stylesheet.onload = () => {
+                    try {
+                        const map = L.map('render-container', {
+                            zoomControl: false,
+                            fadeAnimation: false,
+                            zoomAnimation: false,
+                            markerZoomAnimation: false
+                        });
+
+                        const tiles = L.tileLayer(
+                            config.tileUrl || 'https://tile.openstreetmap.org/{z}/{x}/{y}.png',
+                            {
+                                attribution: config.attribution ?? '&copy; OpenStreetMap contributors',
+                                maxZoom: 19
+                            }
+                        );
+                        window.tilesLoaded = 0;
+                        window.tilesFailed = 0;
+                        tiles.on('tileload', () => { window.tilesLoaded += 1; });
+                        tiles.on('tileerror', () => { window.tilesFailed += 1; });
+                        // Fires once every tile in view has loaded or failed
+                        tiles.on('load', () => {
+                            if (window.tilesFailed > 0) {
+                                renderDone(new Error(window.tilesFailed + ' map tiles failed to load'));
+                            } else {
+                                renderDone();
+                            }
+                        });
+                        tiles.addTo(map);
+
+                        (config.markers || []).forEach(marker => {
+                            const circle = L.circleMarker([marker.lat, marker.lng], {
+                                radius: 8,
+                                color: marker.color || '#3388ff',
+                                fillOpacity: 0.8
+                            }).addTo(map);
+                            if (marker.label) {
+                                circle.bindTooltip(marker.label, { permanent: true });
+                            }
+                        });
+
+                        map.setView(config.center, config.zoom ?? 13);
+                    } catch (error) {
+                        renderDone(error);
+                    }
+                };
+                document.head.appendChild(stylesheet);
+            "#
+            .to_string(),
+            expects_object: true,
+            svg_selector: None,
+            runs_request_code: false,
+            ready_strategy: Some(ReadyStrategy::CallbackEvent),
+            head_html: None,
+            extra_scripts: Vec::new(),
+            plugins: HashMap::new(),
+            themes: HashMap::new(),
+            data_schema: None,
+        },
+    );
+
+    // Vega-Lite (or plain Vega) specs; data is the spec itself. vega-embed
+    // compiles and draws it asynchronously, and rejects on an invalid spec,
+    // so both report through its promise. `{version}` is the Vega-Lite
+    // version; the Vega runtime and vega-embed are pinned to match it.
+    registry.insert(
+        "vega-lite".to_string(),
+        LibraryTemplate {
+            cdn_url: "https://cdn.jsdelivr.net/npm/vega@5.30.0/build/vega.min.js".to_string(),
+            default_version: Some("5.21.0".to_string()),
+            wait_selector: "#render-container".to_string(),
+            init_script: r#"
+                return vegaEmbed('#render-container', {data}, { actions: false });
+            "#
+            .to_string(),
+            expects_object: true,
+            themes: HashMap::new(),
+            svg_selector: None,
+            runs_request_code: false,
+            ready_strategy: Some(ReadyStrategy::PromiseThen),
+            head_html: None,
+            extra_scripts: vec![
+                "https://cdn.jsdelivr.net/npm/vega-lite@{version}/build/vega-lite.min.js"
+                    .to_string(),
+                "https://cdn.jsdelivr.net/npm/vega-embed@6.26.0/build/vega-embed.min.js"
+                    .to_string(),
+            ],
+            plugins: HashMap::new(),
+            data_schema: None,
+        },
+    );
+
+    // Caller-supplied markup, no library; data: { "html": "<svg>...</svg>",
+    // "readyExpression": "document.fonts.status === 'loaded'" }. Scripts in
+    // the markup run, in order, so it counts as request code.
+    registry.insert(
+        "raw-html".to_string(),
+        LibraryTemplate {
+            cdn_url: String::new(),
+            default_version: None,
+            wait_selector: "#render-container".to_string(),
+            init_script: r#"
+                const config = {data};
+                if (typeof config.html !== 'string') {
+                    window.renderError = 'raw-html requires data.html';
+                } else {
+                    const container = document.getElementById('render-container');
+                    container.innerHTML = config.html;
+                    // innerHTML leaves scripts inert; recreate them to run
+                    container.querySelectorAll('script').forEach((inert) => {
+                        const script = document.createElement('script');
+                        for (const { name, value } of inert.attributes) {
+                            script.setAttribute(name, value);
+                        }
+                        script.async = false;
+                        script.textContent = inert.textContent;
+                        inert.replaceWith(script);
+                    });
+
+                    if (typeof config.readyExpression !== 'string') {
+                        window.renderReady = true;
+                    } else {
+                        const isReady = new Function(`return (${config.readyExpression});`);
+                        const poll = () => {
+                            try {
+                                if (isReady()) {
+                                    window.renderReady = true;
+                                    return;
+                                }
+                            } catch (error) {
+                                window.renderError = error.message;
+                                return;
+                            }
+                            setTimeout(poll, 50);
+                        };
+                        poll();
+                    }
+                }
+            "#
+            .to_string(),
+            expects_object: true,
+            themes: HashMap::new(),
+            svg_selector: Some("#render-container svg".to_string()),
+            runs_request_code: true,
+            ready_strategy: None,
+            head_html: None,
+            extra_scripts: Vec::new(),
+            plugins: HashMap::new(),
+            data_schema: None,
         },
     );
 
     registry
-});
+}
+
+/// Chart.js plugins selectable with `library.plugins`.
+fn chartjs_plugins() -> HashMap<String, LibraryPlugin> {
+    HashMap::from([
+        (
+            "datalabels".to_string(),
+            LibraryPlugin {
+                script_url: "https://cdn.jsdelivr.net/npm/chartjs-plugin-datalabels@2.2.0/dist/chartjs-plugin-datalabels.min.js"
+                    .to_string(),
+                global: "ChartDataLabels".to_string(),
+            },
+        ),
+        (
+            "annotation".to_string(),
+            LibraryPlugin {
+                script_url: "https://cdn.jsdelivr.net/npm/chartjs-plugin-annotation@3.0.1/dist/chartjs-plugin-annotation.min.js"
+                    .to_string(),
+                global: "chartjs-plugin-annotation".to_string(),
+            },
+        ),
+    ])
+}
+
+/// Built-in ECharts themes, applied as `echarts.init(el, name)`.
+fn echarts_themes() -> HashMap<String, JsonValue> {
+    HashMap::from([
+        (
+            "light".to_string(),
+            json!({
+                "backgroundColor": "#ffffff",
+                "color": ["#5470c6", "#91cc75", "#fac858", "#ee6666", "#73c0de", "#3ba272"],
+                "textStyle": { "color": "#333333" },
+                "title": { "textStyle": { "color": "#333333" } },
+                "legend": { "textStyle": { "color": "#333333" } }
+            }),
+        ),
+        (
+            "dark".to_string(),
+            json!({
+                "backgroundColor": "#100c2a",
+                "color": ["#4992ff", "#7cffb2", "#fddd60", "#ff6e76", "#58d9f9", "#05c091"],
+                "textStyle": { "color": "#eeeeee" },
+                "title": { "textStyle": { "color": "#eeeeee" } },
+                "legend": { "textStyle": { "color": "#eeeeee" } },
+                "categoryAxis": {
+                    "axisLine": { "lineStyle": { "color": "#b9b8ce" } },
+                    "splitLine": { "lineStyle": { "color": "#484753" } }
+                },
+                "valueAxis": {
+                    "axisLine": { "lineStyle": { "color": "#b9b8ce" } },
+                    "splitLine": { "lineStyle": { "color": "#484753" } }
+                }
+            }),
+        ),
+    ])
+}
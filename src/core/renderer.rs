@@ -2,22 +2,371 @@ use anyhow::{Result, anyhow};
 use base64::{Engine as _, engine::general_purpose};
 use crossbeam::queue::ArrayQueue;
 use headless_chrome::Tab;
-use headless_chrome::{Browser, LaunchOptions, protocol::cdp::Page};
-use parking_lot::RwLock;
+use headless_chrome::browser::tab::point::Point;
+use headless_chrome::types::PrintToPdfOptions;
+use headless_chrome::{
+    Browser, LaunchOptions,
+    browser::tab::RequestPausedDecision,
+    protocol::cdp::{
+        DOM, Emulation, Fetch, Fetch::events::RequestPausedEvent, IO, Page, types::Event,
+    },
+};
+use parking_lot::{Mutex, RwLock};
+use std::collections::{HashMap, VecDeque};
 use std::ffi::OsStr;
-use std::sync::Arc;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Weak, mpsc};
 use std::thread::sleep;
 use std::time::{Duration, Instant};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
 use tokio::sync::Semaphore;
-
-use crate::core::registry::LIBRARY_REGISTRY;
+use tokio::sync::mpsc::error::SendTimeoutError;
+
+use crate::core::cache::RenderCache;
+use crate::core::metadata;
+use crate::core::metrics::RenderMetrics;
+use crate::core::page_server;
+use crate::core::registry;
+use crate::core::selftest;
+use crate::core::sprite;
 use crate::core::template;
-use crate::schemas::render::{Base64Response, RenderRequest};
+use crate::schemas::render::{
+    Base64Response, CapturePairResponse, ClipRegion, ConsoleMessage, PdfOptions, RenderOptions,
+    RenderRequest, ScreenOrientation, SpriteResponse,
+};
+use crate::settings::Config;
 
 const MIN_POOL_SIZE: usize = 1;
 const MAX_POOL_SIZE: usize = 10;
 const MAX_CONCURRENT_RENDERS: usize = 20;
 const SCALE_UP_THRESHOLD: f32 = 0.8; // Scale up when 80% capacity used
+const SCALE_DOWN_THRESHOLD: f32 = 0.5; // Reap idle browsers while under 50% used
+const POOL_IDLE_TIMEOUT_SECS: u64 = 300;
+const CACHE_TTL_SECS: u64 = 300;
+const CRASH_RETRIES: usize = 1;
+const INTERACTION_DELAY_MS: u64 = 300;
+const MAX_CAPTURE_DIMENSION: u32 = 8192; // Upper bound for content-fitted viewports
+const SCALE_COOLDOWN_SECS: u64 = 60;
+const MAX_SCALE_EVENTS: usize = 20; // Scale events kept per pool for /health
+const NETWORK_IDLE_MS: u64 = 500;
+const LAUNCH_FAILURE_BACKOFF: Duration = Duration::from_secs(5);
+const DEFAULT_ACCEPT_LANGUAGE: &str = "en-US";
+const DEFAULT_TIMEOUT_MS: u64 = 30000;
+const MAX_CONSOLE_MESSAGES: usize = 100; // Per render with capture_console
+const MAX_CONSOLE_TEXT_CHARS: usize = 1000; // Per console message
+const MAX_ERROR_CONSOLE_MESSAGES: usize = 20; // Most recent, attached to a failed render
+const MIN_CAPTURE_TIMEOUT: Duration = Duration::from_secs(1);
+const CANARY_TIMEOUT_MS: u64 = 10000; // Deep health check render
+// Chrome refuses URLs over 2 MiB; larger pages go through page_server
+const MAX_DATA_URL_BYTES: usize = 1024 * 1024;
+const PDF_STREAM_CHUNK: u32 = 256 * 1024; // Bytes per IO.read of a streamed PDF
+const PDF_STREAM_BUFFERED_CHUNKS: usize = 4; // Read ahead of a slow writer
+// forced-colors media emulation, the newest CDP feature renders rely on,
+// arrived in Chrome 89
+const MIN_CHROME_VERSION: u32 = 89;
+
+/// Tunables for the browser pools and render concurrency.
+#[derive(Debug, Clone)]
+pub struct EngineSettings {
+    pub min_pool_size: usize,
+    pub max_pool_size: usize,
+    pub max_concurrent: usize,
+    /// After the pool scales in one direction, scaling the opposite way is
+    /// inhibited for this long to avoid create/destroy thrashing
+    pub scale_cooldown: Duration,
+    /// Browsers above `min_pool_size` are closed once the pool has stayed
+    /// under `SCALE_DOWN_THRESHOLD` usage for this long
+    pub pool_idle_timeout: Duration,
+    /// Chrome/Chromium binary to launch. Auto-detected when unset
+    pub chrome_path: Option<PathBuf>,
+    /// Appended to the built-in Chrome flags, e.g. `--single-process`
+    pub extra_chrome_args: Vec<String>,
+    /// Renders slower than this are logged at warn level with phase timings
+    pub slow_render_threshold: Option<Duration>,
+    /// Hosts that receive `inject_fetch_headers`, subdomains included
+    pub fetch_header_hosts: Vec<String>,
+    /// Hosts a request's `library.cdn_url` may load from, matched exactly
+    pub allowed_cdn_hosts: Vec<String>,
+    /// Rendered outputs kept in the response cache; 0 disables caching
+    pub cache_max_entries: usize,
+    /// How long a cached output is served before it is rendered again
+    pub cache_ttl: Duration,
+    /// Times a render is retried on a fresh browser after its browser
+    /// crashed mid-render
+    pub crash_retries: usize,
+}
+
+impl Default for EngineSettings {
+    fn default() -> Self {
+        Self {
+            min_pool_size: MIN_POOL_SIZE,
+            max_pool_size: MAX_POOL_SIZE,
+            max_concurrent: MAX_CONCURRENT_RENDERS,
+            scale_cooldown: Duration::from_secs(SCALE_COOLDOWN_SECS),
+            pool_idle_timeout: Duration::from_secs(POOL_IDLE_TIMEOUT_SECS),
+            chrome_path: None,
+            extra_chrome_args: Vec::new(),
+            slow_render_threshold: None,
+            fetch_header_hosts: Vec::new(),
+            allowed_cdn_hosts: template::DEFAULT_CDN_HOSTS.map(String::from).to_vec(),
+            cache_max_entries: 0,
+            cache_ttl: Duration::from_secs(CACHE_TTL_SECS),
+            crash_retries: CRASH_RETRIES,
+        }
+    }
+}
+
+impl EngineSettings {
+    /// Reject pool bounds the engine can't run with, so a bad config fails
+    /// at startup rather than on the first render.
+    pub fn validate(&self) -> Result<()> {
+        if self.max_pool_size == 0 {
+            return Err(anyhow!("max_pool_size must be at least 1"));
+        }
+        if self.min_pool_size > self.max_pool_size {
+            return Err(anyhow!(
+                "min_pool_size ({}) must not exceed max_pool_size ({})",
+                self.min_pool_size,
+                self.max_pool_size
+            ));
+        }
+        if self.max_concurrent == 0 {
+            return Err(anyhow!("max_concurrent must be at least 1"));
+        }
+        Ok(())
+    }
+
+    /// Reject a `chrome_path` that doesn't exist, which would otherwise only
+    /// surface as failed launches. Kept out of `validate` so an engine can
+    /// still be built, and report 503s, while no browser is installed.
+    pub fn check_chrome_path(&self) -> Result<()> {
+        match &self.chrome_path {
+            Some(path) if !path.exists() => {
+                Err(anyhow!("chrome_path '{}' does not exist", path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl From<&Config> for EngineSettings {
+    fn from(config: &Config) -> Self {
+        let defaults = Self::default();
+        Self {
+            min_pool_size: config.min_pool_size.unwrap_or(defaults.min_pool_size),
+            max_pool_size: config.max_pool_size.unwrap_or(defaults.max_pool_size),
+            max_concurrent: config.max_concurrent.unwrap_or(defaults.max_concurrent),
+            scale_cooldown: config
+                .scale_cooldown_secs
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.scale_cooldown),
+            pool_idle_timeout: config
+                .pool_idle_timeout_secs
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.pool_idle_timeout),
+            chrome_path: config.chrome_path.as_ref().map(PathBuf::from),
+            extra_chrome_args: config
+                .extra_chrome_args
+                .as_deref()
+                .unwrap_or_default()
+                .split_whitespace()
+                .map(String::from)
+                .collect(),
+            slow_render_threshold: config.slow_render_threshold_ms.map(Duration::from_millis),
+            fetch_header_hosts: config
+                .fetch_header_hosts
+                .as_deref()
+                .unwrap_or_default()
+                .split(',')
+                .map(|host| host.trim().to_ascii_lowercase())
+                .filter(|host| !host.is_empty())
+                .collect(),
+            allowed_cdn_hosts: config
+                .allowed_cdn_hosts
+                .as_deref()
+                .map(|hosts| {
+                    hosts
+                        .split(',')
+                        .map(|host| host.trim().to_ascii_lowercase())
+                        .filter(|host| !host.is_empty())
+                        .collect::<Vec<_>>()
+                })
+                .filter(|hosts| !hosts.is_empty())
+                .unwrap_or(defaults.allowed_cdn_hosts),
+            cache_max_entries: config
+                .cache_max_entries
+                .unwrap_or(defaults.cache_max_entries),
+            cache_ttl: config
+                .cache_ttl_secs
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.cache_ttl),
+            crash_retries: config.crash_retries.unwrap_or(defaults.crash_retries),
+        }
+    }
+}
+
+/// No working browser could be obtained, e.g. because Chrome fails to
+/// launch. Returned instead of retrying so the render permit is released
+/// promptly; the API maps it to 503.
+#[derive(Debug)]
+pub struct BrowserUnavailable(pub String);
+
+impl fmt::Display for BrowserUnavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "No browser available: {}", self.0)
+    }
+}
+
+impl std::error::Error for BrowserUnavailable {}
+
+/// A screenshot or PDF call did not finish within the render's remaining
+/// `timeout_ms`. The browser instance it ran on is discarded.
+#[derive(Debug)]
+pub struct CaptureTimeout(pub Duration);
+
+impl fmt::Display for CaptureTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Capture timed out after {:?}", self.0)
+    }
+}
+
+impl std::error::Error for CaptureTimeout {}
+
+/// A streamed render failed after some of its output was written. Not
+/// retried, since what was written can't be taken back.
+#[derive(Debug)]
+struct StreamInterrupted(String);
+
+impl fmt::Display for StreamInterrupted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Render output interrupted: {}", self.0)
+    }
+}
+
+impl std::error::Error for StreamInterrupted {}
+
+/// Why a page failed to render, as opposed to the service failing to run
+/// it. The API maps each variant to its own status instead of a blanket 500.
+#[derive(Debug)]
+pub enum RenderError {
+    /// The page was not ready within `timeout_ms`; 408
+    Timeout { timeout: Duration, detail: String },
+    /// No template is registered under the requested name; 400
+    UnsupportedLibrary(String),
+    /// The init script reported a failure through `window.renderError`; 422
+    Initialization(String),
+    /// The browser went away while rendering; 500
+    BrowserCrashed(String),
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Timeout { timeout, detail } => write!(
+                f,
+                "Render not ready within timeout_ms ({}ms): {}",
+                timeout.as_millis(),
+                detail
+            ),
+            Self::UnsupportedLibrary(name) => write!(f, "Unsupported library: {}", name),
+            Self::Initialization(message) => {
+                write!(f, "Render initialization failed: {}", message)
+            }
+            Self::BrowserCrashed(message) => {
+                write!(f, "Browser crashed during render: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+/// Machine-readable code for a failed render, reported alongside the message
+/// so clients can branch without parsing it
+pub fn error_code(error: &anyhow::Error) -> &'static str {
+    if let Some(error) = error.downcast_ref::<RenderError>() {
+        return match error {
+            RenderError::Timeout { .. } => "TIMEOUT",
+            RenderError::UnsupportedLibrary(_) => "UNSUPPORTED_LIBRARY",
+            RenderError::Initialization(_) => "INIT_ERROR",
+            RenderError::BrowserCrashed(_) => "BROWSER_CRASH",
+        };
+    }
+    if error.is::<CaptureTimeout>() {
+        "CAPTURE_TIMEOUT"
+    } else if error.is::<BrowserUnavailable>() {
+        "BROWSER_UNAVAILABLE"
+    } else {
+        "INTERNAL_ERROR"
+    }
+}
+
+/// What the page logged before a render failed, attached to the error as
+/// context. Displays as the error it wraps, and downcasting still reaches
+/// that error, so only routes that look for it see the console output.
+#[derive(Debug)]
+pub struct ConsoleLog {
+    message: String,
+    pub messages: Vec<ConsoleMessage>,
+}
+
+impl ConsoleLog {
+    /// One `[level] text` line per message
+    pub fn transcript(&self) -> String {
+        self.messages
+            .iter()
+            .map(|message| format!("[{}] {}", message.level, message.text))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl fmt::Display for ConsoleLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Failure type label for `/metrics`.
+fn failure_kind(error: &anyhow::Error) -> &'static str {
+    if let Some(error) = error.downcast_ref::<RenderError>() {
+        return match error {
+            RenderError::Timeout { .. } => "timeout",
+            RenderError::UnsupportedLibrary(_) => "unsupported_library",
+            RenderError::Initialization(_) => "initialization",
+            RenderError::BrowserCrashed(_) => "browser_crashed",
+        };
+    }
+    if error.is::<CaptureTimeout>() {
+        "capture_timeout"
+    } else if error.is::<BrowserUnavailable>() {
+        "browser_unavailable"
+    } else {
+        "other"
+    }
+}
+
+/// Decoded capture for `format: "rgba"`: 8-bit RGBA, 4 bytes per pixel,
+/// rows top to bottom with no padding or header.
+pub struct RawImage {
+    pub pixels: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Time spent in each stage of a render, reported for slow renders.
+#[derive(Debug, Default, Clone, Copy)]
+struct PhaseTimings {
+    /// Waiting for a render permit
+    queue: Duration,
+    /// HTML generation, browser acquire, tab and viewport setup
+    setup: Duration,
+    /// Page load until render ready and any asset waits
+    load: Duration,
+    capture: Duration,
+}
 
 #[derive(Debug, Clone)]
 pub struct HealthStatus {
@@ -25,28 +374,116 @@ pub struct HealthStatus {
     pub total_capacity: usize,
     pub available_permits: usize,
     pub max_concurrent: usize,
+    pub pools: Vec<PoolStatus>,
+    pub warming: bool,
+    /// The default pool launches browsers on demand (`min_pool_size = 0`)
+    pub lazy_pool: bool,
+    /// The default pool is skipping launches after a failed one
+    pub launch_backoff: bool,
+    /// Renders over `slow_render_threshold` since startup
+    pub slow_renders: u64,
+    /// Renders completed successfully since startup
+    pub renders_served: u64,
+    /// Renders that returned an error since startup
+    pub renders_failed: u64,
+    /// Response cache lookups; all zero when caching is disabled
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    pub cache_entries: usize,
+}
+
+/// Outcome of the canary render in `deep_health_check`.
+#[derive(Debug, Clone)]
+pub struct CanaryStatus {
+    pub latency: Duration,
+    /// Why the canary failed; `None` when it rendered
+    pub error: Option<String>,
 }
 
+impl CanaryStatus {
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PoolStatus {
+    pub raster_engine: &'static str,
+    pub size: usize,
+    pub available: usize,
+    pub capacity: usize,
+    pub recent_scale_events: Vec<ScaleEventStatus>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleDirection {
+    Up,
+    Down,
+}
+
+impl ScaleDirection {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Up => "up",
+            Self::Down => "down",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScaleEventStatus {
+    pub direction: ScaleDirection,
+    pub from: usize,
+    pub to: usize,
+    pub seconds_ago: u64,
+}
+
+struct ScaleEvent {
+    direction: ScaleDirection,
+    from: usize,
+    to: usize,
+    at: Instant,
+}
+
+/// Closes its tab on drop. Declared after the `BrowserPoolGuard` in
+/// `with_rendered_page` so the tab is closed, and the browser has
+/// acknowledged it, before the instance goes back to the pool for the next
+/// `new_tab`.
 struct TabGuard {
     tab: Arc<Tab>,
+    close_on_drop: bool,
 }
 
 impl TabGuard {
     fn new(tab: Arc<Tab>) -> Self {
-        Self { tab }
+        Self {
+            tab,
+            close_on_drop: true,
+        }
     }
 
     fn as_ref(&self) -> &Arc<Tab> {
         &self.tab
     }
+
+    /// Skip closing the tab, for tabs whose browser is being discarded.
+    fn abandon(&mut self) {
+        self.close_on_drop = false;
+    }
 }
 
 impl Drop for TabGuard {
     fn drop(&mut self) {
-        if let Err(e) = self.tab.close(true) {
-            tracing::warn!("Failed to close tab during cleanup: {}", e);
-        } else {
-            tracing::debug!("Tab closed successfully");
+        if !self.close_on_drop {
+            return;
+        }
+        // Target.closeTarget returns once the browser has dropped the target.
+        // Page.close (close(true)) returns before that and waits on unload
+        // hooks, which let a quick follow-up render race the teardown.
+        match self.tab.close(false) {
+            Ok(true) => tracing::debug!("Tab closed successfully"),
+            Ok(false) => tracing::warn!("Browser declined to close tab during cleanup"),
+            Err(e) => tracing::warn!("Failed to close tab during cleanup: {}", e),
         }
     }
 }
@@ -54,20 +491,31 @@ impl Drop for TabGuard {
 struct BrowserInstance {
     browser: Browser,
     last_health_check: Arc<RwLock<Instant>>,
+    discarded: AtomicBool,
 }
 
 impl BrowserInstance {
     fn new(launch_options: &LaunchOptions<'static>) -> Result<Self> {
         let browser = Browser::new(launch_options.clone())?;
+        check_chrome_version(&browser)?;
         let now = Instant::now();
 
         Ok(Self {
             browser,
             last_health_check: Arc::new(RwLock::new(now)),
+            discarded: AtomicBool::new(false),
         })
     }
 
+    /// Mark the instance so it is dropped instead of returned to the pool.
+    fn discard(&self) {
+        self.discarded.store(true, Ordering::SeqCst);
+    }
+
     fn is_healthy(&self) -> bool {
+        if self.discarded.load(Ordering::SeqCst) {
+            return false;
+        }
         match self.browser.get_version() {
             Ok(_) => {
                 *self.last_health_check.write() = Instant::now();
@@ -84,6 +532,42 @@ impl BrowserInstance {
     }
 }
 
+/// Refuse browsers older than `MIN_CHROME_VERSION`, so an outdated install
+/// fails at launch with a clear message instead of with a CDP error on
+/// every render.
+fn check_chrome_version(browser: &Browser) -> Result<()> {
+    let product = browser.get_version()?.product;
+    match chrome_major_version(&product) {
+        Some(major) if major < MIN_CHROME_VERSION => Err(anyhow!(
+            "{} is not supported, Chrome {} or newer is required",
+            product,
+            MIN_CHROME_VERSION
+        )),
+        Some(_) => {
+            tracing::info!(
+                "Launched {} (minimum supported: Chrome {})",
+                product,
+                MIN_CHROME_VERSION
+            );
+            Ok(())
+        }
+        None => {
+            tracing::warn!(
+                "Could not determine the version of {}, assuming Chrome {} or newer",
+                product,
+                MIN_CHROME_VERSION
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Major version from a CDP product string such as
+/// `HeadlessChrome/120.0.6099.109`.
+pub fn chrome_major_version(product: &str) -> Option<u32> {
+    product.split_once('/')?.1.split('.').next()?.parse().ok()
+}
+
 struct BrowserPoolGuard {
     pool: Arc<BrowserPool>,
     instance: Option<Arc<BrowserInstance>>,
@@ -100,12 +584,26 @@ impl Drop for BrowserPoolGuard {
 struct BrowserPool {
     pool: ArrayQueue<Arc<BrowserInstance>>,
     launch_options: LaunchOptions<'static>,
+    min_size: usize,
     max_size: usize,
     current_size: Arc<RwLock<usize>>,
+    scale_cooldown: Duration,
+    scale_events: Mutex<VecDeque<ScaleEvent>>,
+    /// Launches are skipped until this passes after a failed launch
+    launch_backoff_until: Mutex<Option<Instant>>,
+    /// Since when usage has been under `SCALE_DOWN_THRESHOLD`
+    idle_since: Mutex<Option<Instant>>,
+    /// Set by `close`; no browsers are handed out or taken back after it
+    closed: AtomicBool,
 }
 
 impl BrowserPool {
-    fn new(min_size: usize, max_size: usize, launch_options: LaunchOptions<'static>) -> Result<Self> {
+    fn new(
+        min_size: usize,
+        max_size: usize,
+        scale_cooldown: Duration,
+        launch_options: LaunchOptions<'static>,
+    ) -> Result<Self> {
         let pool = ArrayQueue::new(max_size);
 
         // Start with minimum pool size
@@ -122,7 +620,8 @@ impl BrowserPool {
             }
         }
 
-        if pool.len() == 0 {
+        // A min_size of 0 makes the pool lazy, launching on first acquire
+        if min_size > 0 && pool.is_empty() {
             return Err(anyhow!("Failed to initialize browser pool"));
         }
 
@@ -138,27 +637,46 @@ impl BrowserPool {
         Ok(Self {
             pool,
             launch_options,
+            min_size,
             max_size,
             current_size: Arc::new(RwLock::new(initial_count)),
+            scale_cooldown,
+            scale_events: Mutex::new(VecDeque::with_capacity(MAX_SCALE_EVENTS)),
+            launch_backoff_until: Mutex::new(None),
+            idle_since: Mutex::new(None),
+            closed: AtomicBool::new(false),
         })
     }
 
     fn acquire(&self) -> Result<Arc<BrowserInstance>> {
+        if self.closed.load(Ordering::SeqCst) {
+            return Err(BrowserUnavailable("browser pool is shut down".to_string()).into());
+        }
+
         // Try to get from pool first
         if let Some(instance) = self.pool.pop() {
             if instance.is_healthy() {
                 return Ok(instance);
             } else {
                 tracing::warn!("Unhealthy browser detected, creating new instance");
+                let mut size = self.current_size.write();
+                *size = size.saturating_sub(1);
             }
         }
 
         // Check if we should scale up the pool
         let current = *self.current_size.read();
         let available = self.pool.len();
-        let usage_ratio = 1.0 - (available as f32 / current as f32);
+        let usage_ratio = if current == 0 {
+            1.0
+        } else {
+            1.0 - (available as f32 / current as f32)
+        };
 
-        if usage_ratio >= SCALE_UP_THRESHOLD && current < self.max_size {
+        if usage_ratio >= SCALE_UP_THRESHOLD
+            && current < self.max_size
+            && !self.in_cooldown(ScaleDirection::Up)
+        {
             let new_size = (current + 1).min(self.max_size);
             tracing::info!(
                 "Scaling up browser pool: {} -> {} (usage: {:.1}%)",
@@ -167,80 +685,568 @@ impl BrowserPool {
                 usage_ratio * 100.0
             );
 
-            match BrowserInstance::new(&self.launch_options) {
-                Ok(new_instance) => {
-                    let instance = Arc::new(new_instance);
-                    *self.current_size.write() = new_size;
-                    return Ok(instance);
-                }
-                Err(e) => {
-                    tracing::error!("Failed to scale up pool: {}", e);
-                }
-            }
+            // Don't fall through to a second launch attempt on failure
+            let instance = self.launch()?;
+            *self.current_size.write() = new_size;
+            self.record_scale_event(ScaleDirection::Up, current, new_size);
+            return Ok(instance);
         }
 
         // Fallback: create temporary instance
         tracing::debug!("Creating temporary browser instance (pool exhausted)");
-        BrowserInstance::new(&self.launch_options).map(Arc::new)
+        self.launch()
+    }
+
+    /// Launch a browser, failing fast with `BrowserUnavailable` while a
+    /// recent launch failure is backing off.
+    fn launch(&self) -> Result<Arc<BrowserInstance>> {
+        if let Some(until) = *self.launch_backoff_until.lock()
+            && Instant::now() < until
+        {
+            return Err(BrowserUnavailable(format!(
+                "browser launch failed recently, retrying in {:?}",
+                until - Instant::now()
+            ))
+            .into());
+        }
+
+        match BrowserInstance::new(&self.launch_options) {
+            Ok(instance) => {
+                *self.launch_backoff_until.lock() = None;
+                Ok(Arc::new(instance))
+            }
+            Err(e) => {
+                tracing::error!("Failed to launch browser: {}", e);
+                *self.launch_backoff_until.lock() = Some(Instant::now() + LAUNCH_FAILURE_BACKOFF);
+                Err(BrowserUnavailable(e.to_string()).into())
+            }
+        }
     }
 
     fn release(&self, instance: Arc<BrowserInstance>) {
-        if instance.is_healthy() {
+        if self.closed.load(Ordering::SeqCst) {
+            tracing::debug!("Pool closed, dropping browser instance");
+        } else if instance.is_healthy() {
             if self.pool.push(instance).is_err() {
                 tracing::debug!("Pool full, dropping browser instance");
             }
         } else {
             tracing::warn!("Not returning unhealthy instance to pool");
+            let mut size = self.current_size.write();
+            *size = size.saturating_sub(1);
         }
     }
 
     fn current_size(&self) -> usize {
         *self.current_size.read()
     }
+
+    fn in_launch_backoff(&self) -> bool {
+        self.launch_backoff_until
+            .lock()
+            .is_some_and(|until| Instant::now() < until)
+    }
+
+    /// Close every idle browser and stop handing out new ones. Browsers
+    /// still serving a render are closed when they are released.
+    fn close(&self) -> usize {
+        self.closed.store(true, Ordering::SeqCst);
+        let mut closed = 0;
+        while let Some(instance) = self.pool.pop() {
+            drop(instance);
+            closed += 1;
+        }
+        *self.current_size.write() = 0;
+        closed
+    }
+
+    /// Close idle browsers down to `min_size` once usage has stayed under
+    /// `SCALE_DOWN_THRESHOLD` for `idle_timeout`. Only instances waiting in
+    /// the queue are closed, never ones handed out to a render.
+    fn reap_idle(&self, idle_timeout: Duration) {
+        let current = self.current_size();
+        let usage_ratio = if current == 0 {
+            0.0
+        } else {
+            1.0 - (self.pool.len() as f32 / current as f32)
+        };
+
+        let mut idle_since = self.idle_since.lock();
+        if current <= self.min_size || usage_ratio >= SCALE_DOWN_THRESHOLD {
+            *idle_since = None;
+            return;
+        }
+        let since = *idle_since.get_or_insert_with(Instant::now);
+        if since.elapsed() < idle_timeout || self.in_cooldown(ScaleDirection::Down) {
+            return;
+        }
+
+        let mut reaped = 0;
+        while current - reaped > self.min_size
+            && let Some(instance) = self.pool.pop()
+        {
+            drop(instance);
+            reaped += 1;
+        }
+        *idle_since = None;
+        if reaped == 0 {
+            return;
+        }
+
+        let new_size = {
+            let mut size = self.current_size.write();
+            *size = size.saturating_sub(reaped);
+            *size
+        };
+        tracing::info!(
+            "Scaling down browser pool: {} -> {} (idle for {:?})",
+            current,
+            new_size,
+            since.elapsed()
+        );
+        self.record_scale_event(ScaleDirection::Down, current, new_size);
+    }
+
+    /// Whether scaling in `direction` is inhibited because the pool recently
+    /// scaled the opposite way.
+    fn in_cooldown(&self, direction: ScaleDirection) -> bool {
+        self.scale_events.lock().back().is_some_and(|last| {
+            last.direction != direction && last.at.elapsed() < self.scale_cooldown
+        })
+    }
+
+    fn record_scale_event(&self, direction: ScaleDirection, from: usize, to: usize) {
+        let mut events = self.scale_events.lock();
+        if events.len() == MAX_SCALE_EVENTS {
+            events.pop_front();
+        }
+        events.push_back(ScaleEvent {
+            direction,
+            from,
+            to,
+            at: Instant::now(),
+        });
+    }
+
+    fn recent_scale_events(&self) -> Vec<ScaleEventStatus> {
+        self.scale_events
+            .lock()
+            .iter()
+            .rev()
+            .map(|event| ScaleEventStatus {
+                direction: event.direction,
+                from: event.from,
+                to: event.to,
+                seconds_ago: event.at.elapsed().as_secs(),
+            })
+            .collect()
+    }
+}
+
+/// Periodically shrink every pool back towards its minimum, see
+/// `BrowserPool::reap_idle`. Runs on its own thread so engines built outside
+/// a Tokio runtime get it too, and stops once the engine is dropped.
+fn spawn_idle_reaper(
+    browser_pools: Weak<RwLock<HashMap<RasterEngine, Arc<BrowserPool>>>>,
+    idle_timeout: Duration,
+) {
+    let interval = (idle_timeout / 2).clamp(Duration::from_millis(100), Duration::from_secs(10));
+    std::thread::spawn(move || {
+        loop {
+            sleep(interval);
+            let Some(browser_pools) = browser_pools.upgrade() else {
+                return;
+            };
+            let pools: Vec<Arc<BrowserPool>> = browser_pools.read().values().cloned().collect();
+            drop(browser_pools);
+            for pool in pools {
+                pool.reap_idle(idle_timeout);
+            }
+        }
+    });
+}
+
+/// Rasterization path a browser pool is launched with. Each variant gets its
+/// own pool because the choice is fixed by Chrome's command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RasterEngine {
+    /// Software rasterization with the GPU disabled (`--disable-gpu`)
+    Default,
+    /// SwiftShader-backed GL, enabling WebGL and GPU canvas code paths
+    SwiftShader,
+}
+
+impl RasterEngine {
+    fn from_request(request: &RenderRequest) -> Self {
+        match request.options.raster_engine.as_deref() {
+            Some("swiftshader") => Self::SwiftShader,
+            _ => Self::Default,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::SwiftShader => "swiftshader",
+        }
+    }
+
+    fn launch_options(
+        &self,
+        chrome_path: Option<&Path>,
+        extra_args: &[String],
+    ) -> Result<LaunchOptions<'static>> {
+        let mut args = vec![
+            OsStr::new("--no-sandbox"),
+            OsStr::new("--disable-setuid-sandbox"),
+            OsStr::new("--disable-dev-shm-usage"),
+            OsStr::new("--disable-extensions"),
+            OsStr::new("--disable-background-networking"),
+            OsStr::new("--disable-sync"),
+            OsStr::new("--metrics-recording-only"),
+            OsStr::new("--mute-audio"),
+            OsStr::new("--no-first-run"),
+            OsStr::new("--disable-default-apps"),
+        ];
+
+        match self {
+            Self::Default => args.extend([
+                OsStr::new("--disable-gpu"),
+                OsStr::new("--disable-software-rasterizer"),
+            ]),
+            Self::SwiftShader => args.extend([
+                OsStr::new("--use-gl=angle"),
+                OsStr::new("--use-angle=swiftshader"),
+                OsStr::new("--enable-unsafe-swiftshader"),
+                OsStr::new("--ignore-gpu-blocklist"),
+                OsStr::new("--enable-webgl"),
+            ]),
+        }
+        // LaunchOptions borrows its args and pools keep theirs for good, so
+        // the few configured flags are leaked once per pool
+        args.extend(
+            extra_args
+                .iter()
+                .map(|arg| OsStr::new(Box::leak(arg.clone().into_boxed_str()) as &'static str)),
+        );
+
+        LaunchOptions::default_builder()
+            .headless(true)
+            .sandbox(false)
+            .path(chrome_path.map(Path::to_path_buf))
+            .args(args)
+            .build()
+            .map_err(|_| anyhow!("Could not find Chrome/Chromium binary"))
+    }
 }
 
 #[derive(Clone)]
 pub struct RenderingEngine {
-    browser_pool: Arc<BrowserPool>,
+    browser_pools: Arc<RwLock<HashMap<RasterEngine, Arc<BrowserPool>>>>,
+    settings: EngineSettings,
     render_semaphore: Arc<Semaphore>,
+    warming: Arc<AtomicBool>,
+    slow_renders: Arc<AtomicU64>,
+    renders_served: Arc<AtomicU64>,
+    renders_failed: Arc<AtomicU64>,
+    metrics: Arc<RenderMetrics>,
+    cache: Option<Arc<RenderCache>>,
+    /// Set once shutdown starts; new renders are refused with a 503
+    shutting_down: Arc<AtomicBool>,
 }
 
 impl RenderingEngine {
     pub fn new() -> Result<Self> {
-        Self::with_config(MIN_POOL_SIZE, MAX_POOL_SIZE, MAX_CONCURRENT_RENDERS)
+        Self::with_settings(EngineSettings::default())
     }
 
-    pub fn with_config(min_pool_size: usize, max_pool_size: usize, max_concurrent: usize) -> Result<Self> {
-        let launch_options = LaunchOptions::default_builder()
-            .headless(true)
-            .sandbox(false)
-            .args(vec![
-                OsStr::new("--no-sandbox"),
-                OsStr::new("--disable-setuid-sandbox"),
-                OsStr::new("--disable-dev-shm-usage"),
-                OsStr::new("--disable-gpu"),
-                OsStr::new("--disable-software-rasterizer"),
-                OsStr::new("--disable-extensions"),
-                OsStr::new("--disable-background-networking"),
-                OsStr::new("--disable-sync"),
-                OsStr::new("--metrics-recording-only"),
-                OsStr::new("--mute-audio"),
-                OsStr::new("--no-first-run"),
-                OsStr::new("--disable-default-apps"),
-            ])
-            .build()
-            .map_err(|_| anyhow!("Could not find Chrome/Chromium binary"))?;
+    pub fn with_config(
+        min_pool_size: usize,
+        max_pool_size: usize,
+        max_concurrent: usize,
+    ) -> Result<Self> {
+        Self::with_settings(EngineSettings {
+            min_pool_size,
+            max_pool_size,
+            max_concurrent,
+            ..Default::default()
+        })
+    }
 
-        let browser_pool = BrowserPool::new(min_pool_size, max_pool_size, launch_options)?;
-        let render_semaphore = Semaphore::new(max_concurrent);
+    pub fn with_settings(settings: EngineSettings) -> Result<Self> {
+        settings.validate()?;
+        let launch_options = RasterEngine::Default
+            .launch_options(settings.chrome_path.as_deref(), &settings.extra_chrome_args)?;
+        let browser_pool = BrowserPool::new(
+            settings.min_pool_size,
+            settings.max_pool_size,
+            settings.scale_cooldown,
+            launch_options,
+        )?;
+        let render_semaphore = Semaphore::new(settings.max_concurrent);
+
+        let browser_pools = Arc::new(RwLock::new(HashMap::from([(
+            RasterEngine::Default,
+            Arc::new(browser_pool),
+        )])));
+        spawn_idle_reaper(Arc::downgrade(&browser_pools), settings.pool_idle_timeout);
+        let cache = (settings.cache_max_entries > 0).then(|| {
+            Arc::new(RenderCache::new(
+                settings.cache_max_entries,
+                settings.cache_ttl,
+            ))
+        });
 
         Ok(Self {
-            browser_pool: Arc::new(browser_pool),
+            browser_pools,
+            settings,
             render_semaphore: Arc::new(render_semaphore),
+            warming: Arc::new(AtomicBool::new(false)),
+            slow_renders: Arc::new(AtomicU64::new(0)),
+            renders_served: Arc::new(AtomicU64::new(0)),
+            renders_failed: Arc::new(AtomicU64::new(0)),
+            metrics: Arc::new(RenderMetrics::default()),
+            cache,
+            shutting_down: Arc::new(AtomicBool::new(false)),
         })
     }
 
+    /// Render each library's self-test sample once in the background so the
+    /// first real requests don't pay for cold browsers and CDN downloads.
+    /// The engine reports itself as warming until this completes.
+    pub fn spawn_warm_up(&self) -> tokio::task::JoinHandle<()> {
+        self.warming.store(true, Ordering::SeqCst);
+
+        let engine = self.clone();
+        tokio::spawn(async move {
+            let start = Instant::now();
+            for library in registry::library_names() {
+                let Some(request) = selftest::sample_request(&library) else {
+                    continue;
+                };
+                if let Err(e) = engine.render(request).await {
+                    tracing::warn!("Warm-up render failed for {}: {}", library, e);
+                }
+            }
+
+            engine.warming.store(false, Ordering::SeqCst);
+            tracing::info!("Warm-up completed in {:?}", start.elapsed());
+        })
+    }
+
+    /// The page a render loads for `request`, as served by `/render/html`.
+    pub fn page_html(&self, request: &RenderRequest) -> Result<String> {
+        template::generate_html(request, &self.settings.allowed_cdn_hosts)
+    }
+
+    /// Hosts a request's `library.cdn_url` may load from.
+    pub fn allowed_cdn_hosts(&self) -> &[String] {
+        &self.settings.allowed_cdn_hosts
+    }
+
+    /// Refuse new renders and wait up to `timeout` for those in flight to
+    /// finish. Returns whether every render finished in time.
+    pub async fn drain(&self, timeout: Duration) -> bool {
+        self.shutting_down.store(true, Ordering::SeqCst);
+
+        let in_flight = self.settings.max_concurrent - self.render_semaphore.available_permits();
+        if in_flight > 0 {
+            tracing::info!("Waiting up to {:?} for {} render(s)", timeout, in_flight);
+        }
+        let all_permits = self
+            .render_semaphore
+            .acquire_many(self.settings.max_concurrent as u32);
+        match tokio::time::timeout(timeout, all_permits).await {
+            Ok(Ok(permits)) => {
+                // Held for good, so nothing starts after the drain
+                permits.forget();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Close every browser in every pool. Renders started afterwards fail
+    /// with `BrowserUnavailable`; call `drain` first to let in-flight ones
+    /// finish.
+    pub fn shutdown(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+        for (raster_engine, pool) in self.browser_pools.read().iter() {
+            let closed = pool.close();
+            tracing::info!("Closed {} {} browser(s)", closed, raster_engine.name());
+        }
+    }
+
+    pub fn is_warming(&self) -> bool {
+        self.warming.load(Ordering::SeqCst)
+    }
+
+    /// Pool for the requested raster engine. Non-default pools are launched
+    /// on first use so they cost nothing unless a request asks for them.
+    fn browser_pool(&self, raster_engine: RasterEngine) -> Result<Arc<BrowserPool>> {
+        if let Some(pool) = self.browser_pools.read().get(&raster_engine) {
+            return Ok(pool.clone());
+        }
+
+        let mut pools = self.browser_pools.write();
+        if let Some(pool) = pools.get(&raster_engine) {
+            return Ok(pool.clone());
+        }
+
+        tracing::info!("Launching '{}' browser pool", raster_engine.name());
+        let pool = Arc::new(BrowserPool::new(
+            self.settings.min_pool_size,
+            self.settings.max_pool_size,
+            self.settings.scale_cooldown,
+            raster_engine.launch_options(
+                self.settings.chrome_path.as_deref(),
+                &self.settings.extra_chrome_args,
+            )?,
+        )?);
+        pools.insert(raster_engine, pool.clone());
+
+        Ok(pool)
+    }
+
+    /// Render to bytes, served from the response cache when an identical
+    /// request was rendered within `cache_ttl` and `no_cache` isn't set.
     pub async fn render(&self, request: RenderRequest) -> Result<Vec<u8>> {
+        let cache = self
+            .cache
+            .as_ref()
+            .filter(|_| !request.options.no_cache.unwrap_or(false));
+        let Some(cache) = cache else {
+            return self.run_render(request, Self::render_sync).await;
+        };
+
+        let key = RenderCache::key(&request);
+        if let Some(bytes) = cache.get(&key) {
+            return Ok(bytes);
+        }
+        let bytes = self.run_render(request, Self::render_sync).await?;
+        cache.insert(key, bytes.clone());
+        Ok(bytes)
+    }
+
+    pub async fn render_base64(&self, request: RenderRequest) -> Result<Base64Response> {
+        let (result, console) = if request.options.capture_console.unwrap_or(false) {
+            let (result, console) = self
+                .run_render(request.clone(), Self::render_with_console_sync)
+                .await?;
+            (result, Some(console))
+        } else {
+            (self.render(request.clone()).await?, None)
+        };
+
+        let html = if request.options.return_html.unwrap_or(false) {
+            Some(self.page_html(&request)?)
+        } else {
+            None
+        };
+
+        Ok(Base64Response {
+            html,
+            console,
+            ..base64_response(&result, &request.options)
+        })
+    }
+
+    /// Render once and capture twice: a "before" image at render ready and an
+    /// "after" image once the configured interactions have been performed.
+    pub async fn render_pair(&self, request: RenderRequest) -> Result<CapturePairResponse> {
+        let options = request.options.clone();
+        let (before, after) = self.run_render(request, Self::render_pair_sync).await?;
+
+        Ok(CapturePairResponse {
+            before: base64_response(&before, &options),
+            after: base64_response(&after, &options),
+        })
+    }
+
+    /// Render with `format: "rgba"`, keeping the pixel dimensions that the
+    /// raw buffer alone does not carry.
+    pub async fn render_rgba(&self, request: RenderRequest) -> Result<RawImage> {
+        self.run_render(request, Self::render_rgba_sync).await
+    }
+
+    /// `render` that writes its output to `writer` and returns how many
+    /// bytes were written. Uncached PDFs are streamed from Chrome in
+    /// `PDF_STREAM_CHUNK` pieces instead of being held in memory whole;
+    /// everything else is rendered first and then written.
+    pub async fn render_to_writer<W: AsyncWrite + Unpin>(
+        &self,
+        request: RenderRequest,
+        writer: &mut W,
+    ) -> Result<u64> {
+        let cached = self.cache.is_some() && !request.options.no_cache.unwrap_or(false);
+        let streamable = request.options.format == "pdf"
+            && !request.options.embed_data.unwrap_or(false)
+            && request.options.output_ppi().is_none();
+        if cached || !streamable {
+            let output = self.render(request).await?;
+            writer.write_all(&output).await?;
+            writer.flush().await?;
+            return Ok(output.len() as u64);
+        }
+
+        let (sender, mut chunks) = tokio::sync::mpsc::channel(PDF_STREAM_BUFFERED_CHUNKS);
+        let render = self.run_render(request, move |engine, request, timings| {
+            engine.with_rendered_page(request, timings, |tab, deadline| {
+                stream_pdf(tab, request, deadline, &sender)
+            })
+        });
+        // Owns the receiver, so a failed write stops the render's reads
+        let write = async move {
+            let mut written = 0;
+            while let Some(chunk) = chunks.recv().await {
+                writer.write_all(&chunk).await?;
+                written += chunk.len() as u64;
+            }
+            writer.flush().await?;
+            Ok::<_, std::io::Error>(written)
+        };
+
+        let (rendered, written) = tokio::join!(render, write);
+        let written = written?;
+        rendered?;
+        Ok(written)
+    }
+
+    /// Capture every element matched by `sprite_selectors` from one page load
+    /// and pack them into a sprite sheet with matching CSS.
+    pub async fn render_sprite(&self, request: RenderRequest) -> Result<SpriteResponse> {
+        let scale = request.options.device_scale_factor.unwrap_or(1.0);
+        let captures = self.run_render(request, Self::render_sprite_sync).await?;
+        let sheet = sprite::build(captures, scale)?;
+        let (width, height) = metadata::image_dimensions(&sheet.png, "png").unwrap_or_default();
+
+        Ok(SpriteResponse {
+            sprite: Base64Response {
+                data: general_purpose::STANDARD.encode(&sheet.png),
+                mime_type: mime_type("png").to_string(),
+                width,
+                height,
+                size_bytes: sheet.png.len() as u64,
+                html: None,
+                console: None,
+            },
+            css: sheet.css,
+            frames: sheet.frames,
+        })
+    }
+
+    async fn run_render<T: Send + 'static>(
+        &self,
+        request: RenderRequest,
+        job: impl FnOnce(&RenderingEngine, &RenderRequest, &mut PhaseTimings) -> Result<T>
+        + Send
+        + 'static,
+    ) -> Result<T> {
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return Err(BrowserUnavailable("server is shutting down".to_string()).into());
+        }
+
+        let queued_at = Instant::now();
         let _permit = self
             .render_semaphore
             .acquire()
@@ -250,20 +1256,54 @@ impl RenderingEngine {
         tracing::debug!(
             "Render started - Available permits: {}/{}",
             self.render_semaphore.available_permits(),
-            MAX_CONCURRENT_RENDERS
+            self.settings.max_concurrent
         );
 
         let library_name = request.library.name.clone();
         let format = request.options.format.clone();
+        let (width, height) = (
+            request.options.pixel_width(),
+            request.options.pixel_height(),
+        );
 
+        // Unknown names share one label so callers can't grow the series
+        let library_label =
+            registry::canonical_name(&library_name).unwrap_or_else(|| "unknown".to_string());
         let engine = self.clone();
         let start = Instant::now();
+        let mut timings = PhaseTimings {
+            queue: start - queued_at,
+            ..Default::default()
+        };
 
-        let result = tokio::task::spawn_blocking(move || engine.render_sync(&request))
-            .await
-            .map_err(|e| anyhow!("Task join error: {}", e))??;
+        // Blocking threads don't inherit the caller's span, so enter it there
+        let span = tracing::Span::current();
+        let (result, timings) = match tokio::task::spawn_blocking(move || {
+            let _span = span.enter();
+            let result = job(&engine, &request, &mut timings);
+            (result, timings)
+        })
+        .await
+        {
+            Ok((Ok(result), timings)) => (result, timings),
+            Ok((Err(e), _)) => {
+                self.renders_failed.fetch_add(1, Ordering::Relaxed);
+                self.metrics.record_render(&library_label, &format);
+                self.metrics.record_failure(failure_kind(&e));
+                return Err(e);
+            }
+            Err(e) => {
+                self.renders_failed.fetch_add(1, Ordering::Relaxed);
+                self.metrics.record_render(&library_label, &format);
+                self.metrics.record_failure("panic");
+                return Err(anyhow!("Task join error: {}", e));
+            }
+        };
+        self.renders_served.fetch_add(1, Ordering::Relaxed);
 
         let duration = start.elapsed();
+        self.metrics.record_render(&library_label, &format);
+        self.metrics.record_duration(duration);
         tracing::info!(
             "Render completed in {:?} - Library: {}, Format: {}",
             duration,
@@ -271,105 +1311,591 @@ impl RenderingEngine {
             format
         );
 
+        if let Some(threshold) = self.settings.slow_render_threshold
+            && duration > threshold
+        {
+            self.slow_renders.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!(
+                library = %library_name,
+                format = %format,
+                width,
+                height,
+                total_ms = duration.as_millis() as u64,
+                queue_ms = timings.queue.as_millis() as u64,
+                setup_ms = timings.setup.as_millis() as u64,
+                load_ms = timings.load.as_millis() as u64,
+                capture_ms = timings.capture.as_millis() as u64,
+                "Slow render exceeded {:?}",
+                threshold
+            );
+        }
+
         Ok(result)
     }
 
-    pub async fn render_base64(&self, request: RenderRequest) -> Result<Base64Response> {
-        let result = self.render(request.clone()).await?;
+    fn render_sync(&self, request: &RenderRequest, timings: &mut PhaseTimings) -> Result<Vec<u8>> {
+        self.with_rendered_page(request, timings, |tab, deadline| {
+            self.capture_screenshot(tab, request, deadline)
+        })
+    }
 
-        let mime_type = match request.options.format.as_str() {
-            "png" => "image/png",
-            "jpeg" | "jpg" => "image/jpeg",
-            "pdf" => "application/pdf",
-            _ => "application/octet-stream",
-        };
+    fn render_with_console_sync(
+        &self,
+        request: &RenderRequest,
+        timings: &mut PhaseTimings,
+    ) -> Result<(Vec<u8>, Vec<ConsoleMessage>)> {
+        let mut console = Vec::new();
+        let result = self.with_logged_page(request, timings, &mut console, |tab, deadline| {
+            self.capture_screenshot(tab, request, deadline)
+        })?;
+        Ok((result, console))
+    }
 
-        Ok(Base64Response {
-            data: general_purpose::STANDARD.encode(&result),
-            mime_type: mime_type.to_string(),
+    fn render_rgba_sync(
+        &self,
+        request: &RenderRequest,
+        timings: &mut PhaseTimings,
+    ) -> Result<RawImage> {
+        self.with_rendered_page(request, timings, |tab, deadline| {
+            decode_rgba(&screenshot(
+                tab,
+                Page::CaptureScreenshotFormatOption::Png,
+                None,
+                None,
+                deadline,
+            )?)
         })
     }
 
-    fn render_sync(&self, request: &RenderRequest) -> Result<Vec<u8>> {
-        let html = template::generate_html(request)?;
+    fn render_pair_sync(
+        &self,
+        request: &RenderRequest,
+        timings: &mut PhaseTimings,
+    ) -> Result<(Vec<u8>, Vec<u8>)> {
+        self.with_rendered_page(request, timings, |tab, deadline| {
+            let before = self.capture_screenshot(tab, request, deadline)?;
+
+            self.perform_interactions(tab, request, deadline)?;
+            let delay = Duration::from_millis(
+                request
+                    .options
+                    .interaction_delay_ms
+                    .unwrap_or(INTERACTION_DELAY_MS),
+            );
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if delay > remaining {
+                sleep(remaining);
+                return Err(RenderError::Timeout {
+                    timeout: render_timeout(request),
+                    detail: format!("waiting interaction_delay_ms ({:?})", delay),
+                }
+                .into());
+            }
+            sleep(delay);
+
+            let after = self.capture_screenshot(tab, request, deadline)?;
+            Ok((before, after))
+        })
+    }
 
-        let browser_instance = self.browser_pool.acquire()?;
+    fn render_sprite_sync(
+        &self,
+        request: &RenderRequest,
+        timings: &mut PhaseTimings,
+    ) -> Result<Vec<(String, Vec<u8>)>> {
+        self.with_rendered_page(request, timings, |tab, deadline| {
+            let mut captures = Vec::new();
+
+            for selector in request.options.sprite_selectors.iter().flatten() {
+                let script = format!(
+                    "(() => {{ const el = document.querySelector({}); if (!el) return null; \
+                     const r = el.getBoundingClientRect(); \
+                     return JSON.stringify([r.x, r.y, r.width, r.height]); }})()",
+                    serde_json::to_string(selector)?
+                );
+                let rect = tab
+                    .evaluate(&script, false)?
+                    .value
+                    .and_then(|v| v.as_str().map(String::from))
+                    .ok_or_else(|| anyhow!("Sprite selector matched no element: {}", selector))?;
+                let [x, y, width, height]: [f64; 4] = serde_json::from_str(&rect)?;
+
+                let png = screenshot(
+                    tab,
+                    Page::CaptureScreenshotFormatOption::Png,
+                    None,
+                    Some(Page::Viewport {
+                        x,
+                        y,
+                        width,
+                        height,
+                        scale: 1.0,
+                    }),
+                    deadline,
+                )?;
+                captures.push((selector.clone(), png));
+            }
+
+            Ok(captures)
+        })
+    }
+
+    /// Load the request into a fresh tab, wait until it is ready and hand the
+    /// tab to `capture` along with the deadline its CDP captures must finish
+    /// by. The tab and browser instance are released afterwards.
+    fn with_rendered_page<T>(
+        &self,
+        request: &RenderRequest,
+        timings: &mut PhaseTimings,
+        capture: impl FnMut(&Arc<Tab>, Instant) -> Result<T>,
+    ) -> Result<T> {
+        self.with_logged_page(request, timings, &mut Vec::new(), capture)
+    }
+
+    /// `with_rendered_page` that, when `capture_console` is set, also collects
+    /// the page's console output into `console`, up to `MAX_CONSOLE_MESSAGES`.
+    ///
+    /// A render whose browser crashed is retried on a fresh browser up to
+    /// `crash_retries` times, each attempt with the full `timeout_ms`. Other
+    /// failures, `window.renderError` included, are returned as they are.
+    fn with_logged_page<T>(
+        &self,
+        request: &RenderRequest,
+        timings: &mut PhaseTimings,
+        console: &mut Vec<ConsoleMessage>,
+        mut capture: impl FnMut(&Arc<Tab>, Instant) -> Result<T>,
+    ) -> Result<T> {
+        let mut attempt = 0;
+        loop {
+            let result = self.render_attempt(request, timings, console, &mut capture);
+            match &result {
+                Err(e)
+                    if attempt < self.settings.crash_retries
+                        && matches!(
+                            e.downcast_ref::<RenderError>(),
+                            Some(RenderError::BrowserCrashed(_))
+                        ) =>
+                {
+                    attempt += 1;
+                    tracing::warn!("{}, retrying on a fresh browser (retry {})", e, attempt);
+                    console.clear();
+                }
+                _ => return result,
+            }
+        }
+    }
+
+    /// One try at `with_logged_page` on a browser from the pool. A browser
+    /// that crashed is dropped from the pool when released.
+    fn render_attempt<T>(
+        &self,
+        request: &RenderRequest,
+        timings: &mut PhaseTimings,
+        console: &mut Vec<ConsoleMessage>,
+        capture: &mut impl FnMut(&Arc<Tab>, Instant) -> Result<T>,
+    ) -> Result<T> {
+        let phase = Instant::now();
+        let deadline = phase + render_timeout(request);
+        let html = self.page_html(request)?;
+
+        let browser_pool = self.browser_pool(RasterEngine::from_request(request))?;
+        let browser_instance = browser_pool.acquire()?;
 
         let _pool_guard = BrowserPoolGuard {
-            pool: self.browser_pool.clone(),
+            pool: browser_pool,
             instance: Some(browser_instance.clone()),
         };
 
         let tab = browser_instance.new_tab()?;
-        let tab_guard = TabGuard::new(tab);
+        let mut tab_guard = TabGuard::new(tab);
         let tab = tab_guard.as_ref();
 
-        // Set viewport
+        let mut console_log = None;
+        let result = (|| {
+            // Set viewport
+            self.set_viewport(
+                tab,
+                request,
+                request.options.pixel_width(),
+                request.options.pixel_height(),
+            )?;
+
+            self.apply_media_emulation(tab, request)?;
+            self.apply_cpu_throttling(tab, request)?;
+            self.apply_transparency(tab, request)?;
+            self.apply_request_headers(tab, request)?;
+            // Always collected, to explain failures; only returned on
+            // success with capture_console
+            console_log = Some(collect_console(tab)?);
+            timings.setup = phase.elapsed();
+
+            // Navigate to HTML. Pages too large for a data: URL are served
+            // over loopback HTTP until the capture is done
+            let phase = Instant::now();
+            let _served_page = if html.len().div_ceil(3) * 4 > MAX_DATA_URL_BYTES {
+                let page = page_server::serve(html.clone())?;
+                tab.navigate_to(&page.url)?;
+                Some(page)
+            } else {
+                let data_url = format!(
+                    "data:text/html;base64,{}",
+                    general_purpose::STANDARD.encode(&html)
+                );
+                tab.navigate_to(&data_url)?;
+                None
+            };
+
+            // Get library template
+            let library_template = registry::get_template(&request.library.name)
+                .ok_or_else(|| RenderError::UnsupportedLibrary(request.library.name.clone()))?;
+
+            // Wait for container element, within what is left of timeout_ms
+            tab.wait_for_element_with_custom_timeout(
+                &library_template.wait_selector,
+                deadline.saturating_duration_since(Instant::now()),
+            )
+            .map_err(|e| RenderError::Timeout {
+                timeout: render_timeout(request),
+                detail: format!("waiting for '{}': {}", library_template.wait_selector, e),
+            })?;
+
+            // Wait for render ready signal
+            self.wait_for_render_ready(tab, request, deadline)?;
+
+            self.fit_viewport_to_content(tab, request)?;
+            timings.load = phase.elapsed();
+
+            let phase = Instant::now();
+            let result = capture(tab, deadline);
+            timings.capture = phase.elapsed();
+
+            result
+        })();
+
+        if let Err(e) = &result
+            && e.is::<CaptureTimeout>()
+        {
+            // The renderer is wedged: closing the tab would block on it too,
+            // so leave both to die with the discarded browser
+            tracing::warn!("{}, discarding browser instance", e);
+            tab_guard.abandon();
+            browser_instance.discard();
+        }
+
+        // An untyped CDP failure on a browser that no longer answers is a crash
+        let result = result.map_err(|e| {
+            if e.is::<CaptureTimeout>()
+                || e.is::<RenderError>()
+                || e.is::<StreamInterrupted>()
+                || browser_instance.is_healthy()
+            {
+                e
+            } else {
+                RenderError::BrowserCrashed(e.to_string()).into()
+            }
+        });
+
+        let mut messages = console_log
+            .map(|log| std::mem::take(&mut *log.lock()))
+            .unwrap_or_default();
+        let result = result.map_err(|e| {
+            if messages.is_empty() {
+                return e;
+            }
+            let recent = messages.len().saturating_sub(MAX_ERROR_CONSOLE_MESSAGES);
+            let log = ConsoleLog {
+                message: e.to_string(),
+                messages: messages[recent..].to_vec(),
+            };
+            e.context(log)
+        });
+        if request.options.capture_console.unwrap_or(false) {
+            console.append(&mut messages);
+        }
+
+        result
+    }
+
+    fn set_viewport(
+        &self,
+        tab: &Arc<Tab>,
+        request: &RenderRequest,
+        width: u32,
+        height: u32,
+    ) -> Result<()> {
         let scale_factor = request.options.device_scale_factor.unwrap_or(1.0);
+        let mobile = request.options.mobile.unwrap_or(false);
         tab.set_bounds(headless_chrome::types::Bounds::Normal {
             left: Some(0),
             top: Some(0),
-            width: Some(request.options.width as f64),
-            height: Some(request.options.height as f64),
+            width: Some(width as f64),
+            height: Some(height as f64),
         })?;
 
-        if scale_factor != 1.0 {
-            tab.call_method(
-                headless_chrome::protocol::cdp::Emulation::SetDeviceMetricsOverride {
-                    width: request.options.width,
-                    height: request.options.height,
-                    device_scale_factor: scale_factor,
-                    mobile: false,
-                    scale: Some(scale_factor),
-                    screen_width: Some(request.options.width),
-                    screen_height: Some(request.options.height),
-                    position_x: Some(0),
-                    position_y: Some(0),
-                    dont_set_visible_size: None,
-                    screen_orientation: None,
-                    viewport: None,
-                    display_feature: None,
-                    device_posture: None,
-                },
+        let orientation = request.options.screen_orientation.as_ref();
+        if scale_factor != 1.0 || mobile || orientation.is_some() {
+            tab.call_method(Emulation::SetDeviceMetricsOverride {
+                width,
+                height,
+                device_scale_factor: scale_factor,
+                mobile,
+                scale: Some(scale_factor),
+                screen_width: Some(width),
+                screen_height: Some(height),
+                position_x: Some(0),
+                position_y: Some(0),
+                dont_set_visible_size: None,
+                screen_orientation: orientation.map(Into::into),
+                viewport: None,
+                display_feature: None,
+                device_posture: None,
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Grow the viewport to the page's scrollable extent when `auto_width`
+    /// or `auto_height` is set, so content is not clipped on the right or
+    /// at the bottom.
+    fn fit_viewport_to_content(&self, tab: &Arc<Tab>, request: &RenderRequest) -> Result<()> {
+        let options = &request.options;
+        let (min_width, min_height) = (options.pixel_width(), options.pixel_height());
+
+        let width = if options.auto_width.unwrap_or(false) {
+            let content_width = measure(
+                tab,
+                "Math.max(document.body.scrollWidth, document.documentElement.scrollWidth)",
             )?;
+            fit_to_content("width", content_width, min_width)
+        } else {
+            min_width
+        };
+
+        // The container keeps its requested height, so content past it
+        // only shows up in its scrollHeight
+        let height = if options.auto_height.unwrap_or(false) {
+            let content_height = measure(
+                tab,
+                "document.getElementById('render-container').scrollHeight",
+            )?;
+            fit_to_content("height", content_height, min_height)
+        } else {
+            min_height
+        };
+
+        if (width, height) != (min_width, min_height) {
+            tracing::debug!("Resizing viewport to content: {}x{}px", width, height);
+            self.set_viewport(tab, request, width, height)?;
+        }
+
+        Ok(())
+    }
+
+    /// Extra headers sent with every request the page makes, including the
+    /// library script fetched from the CDN.
+    fn apply_request_headers(&self, tab: &Arc<Tab>, request: &RenderRequest) -> Result<()> {
+        let accept_language = request
+            .options
+            .accept_language
+            .as_deref()
+            .unwrap_or(DEFAULT_ACCEPT_LANGUAGE);
+
+        tab.set_extra_http_headers(HashMap::from([("Accept-Language", accept_language)]))?;
+        if let Some(user_agent) = &request.options.user_agent {
+            tab.set_user_agent(user_agent, None, None)?;
         }
 
-        // Navigate to HTML
-        let data_url = format!(
-            "data:text/html;base64,{}",
-            general_purpose::STANDARD.encode(&html)
+        if let Some(headers) = &request.options.inject_fetch_headers
+            && !headers.is_empty()
+        {
+            self.inject_fetch_headers(tab, headers)?;
+        }
+        Ok(())
+    }
+
+    /// Add `headers` to the page's own requests (fetch/XHR and other
+    /// subresources) to `fetch_header_hosts` only. `Network.setExtraHTTPHeaders`
+    /// applies to every host, CDNs included, so requests are intercepted and
+    /// rewritten per host instead. Values are never logged.
+    fn inject_fetch_headers(
+        &self,
+        tab: &Arc<Tab>,
+        headers: &HashMap<String, String>,
+    ) -> Result<()> {
+        let allowed_hosts = self.settings.fetch_header_hosts.clone();
+        let injected: Vec<(String, String)> = headers
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+
+        tracing::debug!(
+            headers = ?injected.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            hosts = ?allowed_hosts,
+            "Injecting fetch headers"
         );
-        tab.navigate_to(&data_url)?;
 
-        // Get library template
-        let library_template = LIBRARY_REGISTRY
-            .get(&request.library.name)
-            .ok_or_else(|| anyhow!("Unsupported library: {}", request.library.name))?;
+        tab.enable_fetch(None, None)?;
+        tab.enable_request_interception(Arc::new(
+            move |_transport, _session_id, event: RequestPausedEvent| {
+                let request = &event.params.request;
+                if !is_allowed_host(&request.url, &allowed_hosts) {
+                    return RequestPausedDecision::Continue(None);
+                }
 
-        // Wait for container element
-        tab.wait_for_element_with_custom_timeout(
-            &library_template.wait_selector,
-            Duration::from_secs(10),
-        )?;
+                let mut merged: Vec<Fetch::HeaderEntry> = request
+                    .headers
+                    .0
+                    .as_ref()
+                    .and_then(|headers| headers.as_object())
+                    .into_iter()
+                    .flatten()
+                    .filter(|(name, _)| {
+                        !injected
+                            .iter()
+                            .any(|(injected, _)| injected.eq_ignore_ascii_case(name))
+                    })
+                    .map(|(name, value)| Fetch::HeaderEntry {
+                        name: name.clone(),
+                        value: value.as_str().unwrap_or_default().to_string(),
+                    })
+                    .collect();
+                merged.extend(injected.iter().map(|(name, value)| Fetch::HeaderEntry {
+                    name: name.clone(),
+                    value: value.clone(),
+                }));
+
+                RequestPausedDecision::Continue(Some(Fetch::ContinueRequest {
+                    request_id: event.params.request_id.clone(),
+                    url: None,
+                    method: None,
+                    post_data: None,
+                    headers: Some(merged),
+                    intercept_response: None,
+                }))
+            },
+        ))?;
+        Ok(())
+    }
 
-        // Wait for render ready signal
-        self.wait_for_render_ready(tab, request)?;
+    fn apply_media_emulation(&self, tab: &Arc<Tab>, request: &RenderRequest) -> Result<()> {
+        let options = &request.options;
+        let features: Vec<Emulation::MediaFeature> = [
+            ("prefers-reduced-motion", &options.prefers_reduced_motion),
+            ("forced-colors", &options.forced_colors),
+            ("prefers-color-scheme", &options.prefers_color_scheme),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| {
+            value.as_ref().map(|value| Emulation::MediaFeature {
+                name: name.to_string(),
+                value: value.clone(),
+            })
+        })
+        .collect();
 
-        // Capture based on format
-        let result = self.capture_screenshot(tab, request)?;
+        if !features.is_empty() {
+            tab.call_method(Emulation::SetEmulatedMedia {
+                media: None,
+                features: Some(features),
+            })?;
+        }
 
-        Ok(result)
+        Ok(())
     }
 
-    fn wait_for_render_ready(&self, tab: &Arc<Tab>, request: &RenderRequest) -> Result<()> {
-        let mut attempts = 0;
-        const MAX_ATTEMPTS: u32 = 50;
+    fn apply_cpu_throttling(&self, tab: &Arc<Tab>, request: &RenderRequest) -> Result<()> {
+        if let Some(rate) = request.options.cpu_throttling_rate
+            && rate > 1.0
+        {
+            tab.call_method(Emulation::SetCPUThrottlingRate { rate })?;
+        }
+        Ok(())
+    }
+
+    /// Make Chrome's default white canvas transparent, so pages without a
+    /// background of their own capture with alpha.
+    fn apply_transparency(&self, tab: &Arc<Tab>, request: &RenderRequest) -> Result<()> {
+        if request.options.transparent.unwrap_or(false) {
+            tab.call_method(Emulation::SetDefaultBackgroundColorOverride {
+                color: Some(DOM::RGBA {
+                    r: 0,
+                    g: 0,
+                    b: 0,
+                    a: Some(0.0),
+                }),
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Replay `interactions` in order. Waiting for an interaction's selector
+    /// counts against the render's `deadline`.
+    fn perform_interactions(
+        &self,
+        tab: &Arc<Tab>,
+        request: &RenderRequest,
+        deadline: Instant,
+    ) -> Result<()> {
+        for interaction in request.options.interactions.iter().flatten() {
+            let click = interaction.action == "click";
+
+            if let Some(selector) = &interaction.selector {
+                let element = tab
+                    .wait_for_element_with_custom_timeout(
+                        selector,
+                        deadline.saturating_duration_since(Instant::now()),
+                    )
+                    .map_err(|e| RenderError::Timeout {
+                        timeout: render_timeout(request),
+                        detail: format!("waiting for interaction selector '{}': {}", selector, e),
+                    })?;
+                if click {
+                    element.click()?;
+                } else {
+                    element.move_mouse_over()?;
+                }
+                continue;
+            }
+
+            let (Some(x), Some(y)) = (interaction.x, interaction.y) else {
+                return Err(anyhow!(
+                    "Interaction '{}' needs either a selector or x/y coordinates",
+                    interaction.action
+                ));
+            };
+
+            let point = Point { x, y };
+            if click {
+                tab.click_point(point)?;
+            } else {
+                tab.move_mouse_to_point(point)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Poll for `renderReady` every `poll_interval_ms`, giving up after
+    /// `timeout_ms / poll_interval_ms` attempts or at `deadline`, whichever
+    /// comes first. Then wait for `wait_for_selector`, if set, by `deadline`.
+    fn wait_for_render_ready(
+        &self,
+        tab: &Arc<Tab>,
+        request: &RenderRequest,
+        deadline: Instant,
+    ) -> Result<()> {
         const POLL_INTERVAL_MS: u64 = 100;
-        let poll_interval =
-            Duration::from_millis(request.options.poll_interval_ms.unwrap_or(POLL_INTERVAL_MS));
+        const RENDER_DELAY_MS: u64 = 500;
+        let poll_interval_ms = request.options.poll_interval_ms.unwrap_or(POLL_INTERVAL_MS);
+        let poll_interval = Duration::from_millis(poll_interval_ms);
+        let timeout = render_timeout(request);
+        let max_attempts = (timeout.as_millis() as u64 / poll_interval_ms.max(1)).max(1);
 
-        while attempts < MAX_ATTEMPTS {
-            let ready: bool = tab
+        let mut attempts = 0;
+        let mut ready = false;
+        while attempts < max_attempts && Instant::now() < deadline {
+            ready = tab
                 .evaluate("window.renderReady === true", false)?
                 .value
                 .and_then(|v| v.as_bool())
@@ -386,62 +1912,636 @@ impl RenderingEngine {
                 .and_then(|v| v.as_str().map(String::from));
 
             if let Some(err) = error {
-                return Err(anyhow!("Render initialization failed: {}", err));
+                return Err(RenderError::Initialization(err).into());
             }
 
             sleep(poll_interval);
             attempts += 1;
         }
 
-        if attempts >= MAX_ATTEMPTS {
-            return Err(anyhow!(
-                "Timeout waiting for render to complete after {} attempts",
-                MAX_ATTEMPTS
-            ));
+        if !ready {
+            return Err(RenderError::Timeout {
+                timeout,
+                detail: format!("renderReady not set after {} attempts", attempts),
+            }
+            .into());
         }
 
+        // For libraries that report ready before their output is in the DOM
+        if let Some(selector) = &request.options.wait_for_selector {
+            tab.wait_for_element_with_custom_timeout(
+                selector,
+                deadline.saturating_duration_since(Instant::now()),
+            )
+            .map_err(|e| RenderError::Timeout {
+                timeout,
+                detail: format!("waiting for wait_for_selector '{}': {}", selector, e),
+            })?;
+        }
+
+        self.wait_for_page_assets(tab, poll_interval, request, deadline, timeout)?;
+
         let render_delay =
-            Duration::from_millis(request.options.poll_interval_ms.unwrap_or(POLL_INTERVAL_MS));
+            Duration::from_millis(request.options.render_delay_ms.unwrap_or(RENDER_DELAY_MS));
         sleep(render_delay);
 
         Ok(())
     }
 
-    fn capture_screenshot(&self, tab: &Arc<Tab>, request: &RenderRequest) -> Result<Vec<u8>> {
+    /// Optional waits that run after `renderReady`, for charts whose fonts,
+    /// images or fetched data arrive after the library reports done. They
+    /// share the render's `deadline`, failing with a `RenderError::Timeout`.
+    fn wait_for_page_assets(
+        &self,
+        tab: &Arc<Tab>,
+        poll_interval: Duration,
+        request: &RenderRequest,
+        deadline: Instant,
+        timeout: Duration,
+    ) -> Result<()> {
+        let options = &request.options;
+
+        // Fonts requested after the library reported ready, e.g. by labels
+        // drawn in a later frame
+        if options.wait_for_fonts.unwrap_or(true) {
+            wait_until(
+                tab,
+                "document.fonts.status === 'loaded'",
+                poll_interval,
+                (deadline, timeout),
+                "fonts",
+            )?;
+        }
+
+        if options.wait_for_images.unwrap_or(false) {
+            wait_until(
+                tab,
+                "Array.from(document.images).every(img => img.complete)",
+                poll_interval,
+                (deadline, timeout),
+                "images",
+            )?;
+        }
+
+        if options.wait_for_network_idle.unwrap_or(false) {
+            let idle_for = Duration::from_millis(NETWORK_IDLE_MS);
+            let mut resources = measure(tab, "performance.getEntriesByType('resource').length")?;
+            let mut last_change = Instant::now();
+
+            while last_change.elapsed() < idle_for {
+                if Instant::now() >= deadline {
+                    return Err(RenderError::Timeout {
+                        timeout,
+                        detail: "waiting for network idle".to_string(),
+                    }
+                    .into());
+                }
+                sleep(poll_interval);
+
+                let current = measure(tab, "performance.getEntriesByType('resource').length")?;
+                if current != resources {
+                    resources = current;
+                    last_change = Instant::now();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn capture_screenshot(
+        &self,
+        tab: &Arc<Tab>,
+        request: &RenderRequest,
+        deadline: Instant,
+    ) -> Result<Vec<u8>> {
+        let clip: Option<Page::Viewport> = request.options.clip.as_ref().map(Into::into);
         let result = match request.options.format.as_str() {
+            // Lossless: Chrome takes no quality for PNG
             "png" => {
-                let quality = request.options.quality.unwrap_or(90) as i64;
-                tab.capture_screenshot(
+                let png = screenshot(
+                    tab,
                     Page::CaptureScreenshotFormatOption::Png,
-                    Some(quality.try_into().unwrap()),
                     None,
-                    true,
-                )?
-            }
-            "jpeg" | "jpg" => {
-                let quality = request.options.quality.unwrap_or(90) as i64;
-                tab.capture_screenshot(
-                    Page::CaptureScreenshotFormatOption::Jpeg,
-                    Some(quality.try_into().unwrap()),
+                    clip,
+                    deadline,
+                )?;
+                if request.options.bit_depth == Some(16) {
+                    metadata::png_to_16bit(&png)?
+                } else {
+                    png
+                }
+            }
+            "jpeg" | "jpg" => screenshot(
+                tab,
+                Page::CaptureScreenshotFormatOption::Jpeg,
+                request.options.capture_quality(),
+                clip,
+                deadline,
+            )?,
+            "webp" => screenshot(
+                tab,
+                Page::CaptureScreenshotFormatOption::Webp,
+                request.options.capture_quality(),
+                clip,
+                deadline,
+            )?,
+            "rgba" => {
+                let png = screenshot(
+                    tab,
+                    Page::CaptureScreenshotFormatOption::Png,
                     None,
-                    true,
-                )?
+                    clip,
+                    deadline,
+                )?;
+                decode_rgba(&png)?.pixels
+            }
+            "svg" => {
+                let library_template = registry::get_template(&request.library.name)
+                    .ok_or_else(|| RenderError::UnsupportedLibrary(request.library.name.clone()))?;
+                let selector = library_template.svg_selector.ok_or_else(|| {
+                    anyhow!("Library '{}' can't produce svg", request.library.name)
+                })?;
+                extract_svg(tab, &selector)?
+            }
+            "pdf" => {
+                let pdf_options = request.options.pdf_options.as_ref().map(Into::into);
+                let pdf = bounded_capture(tab, deadline, move |tab| tab.print_to_pdf(pdf_options))?;
+                if request.options.embed_data.unwrap_or(false) {
+                    metadata::embed_pdf_attachment(
+                        pdf,
+                        "data.json",
+                        "application/json",
+                        serde_json::to_vec_pretty(&request.data)?,
+                    )?
+                } else {
+                    pdf
+                }
             }
-            "pdf" => tab.print_to_pdf(None)?,
             _ => {
                 return Err(anyhow!("Unsupported format: {}", request.options.format));
             }
         };
 
-        Ok(result)
+        match request.options.output_ppi() {
+            Some(ppi) => metadata::stamp_ppi(result, &request.options.format, ppi),
+            None => Ok(result),
+        }
+    }
+
+    /// Render counters, the duration histogram and pool gauges in
+    /// Prometheus text format.
+    pub fn prometheus_metrics(&self) -> String {
+        self.metrics.encode(&self.health_check())
+    }
+
+    /// Render a small fixed ECharts bar chart end to end, bypassing the
+    /// cache. Unlike `health_check` this catches a browser that answers CDP
+    /// but can no longer run the page, at the cost of a render slot.
+    pub async fn deep_health_check(&self) -> CanaryStatus {
+        let start = Instant::now();
+        let outcome = match selftest::sample_request("apache-echarts") {
+            Some(mut request) => {
+                request.options.no_cache = Some(true);
+                request.options.timeout_ms = Some(CANARY_TIMEOUT_MS);
+                self.render(request)
+                    .await
+                    .and_then(|bytes| selftest::check_png(&bytes))
+            }
+            None => Err(anyhow!("No canary request for apache-echarts")),
+        };
+
+        CanaryStatus {
+            latency: start.elapsed(),
+            error: outcome.err().map(|e| e.to_string()),
+        }
     }
 
     pub fn health_check(&self) -> HealthStatus {
+        let browser_pools = self.browser_pools.read();
+        let default_pool = &browser_pools[&RasterEngine::Default];
+
+        let mut pools: Vec<PoolStatus> = browser_pools
+            .iter()
+            .map(|(raster_engine, pool)| PoolStatus {
+                raster_engine: raster_engine.name(),
+                size: pool.current_size(),
+                available: pool.pool.len(),
+                capacity: pool.max_size,
+                recent_scale_events: pool.recent_scale_events(),
+            })
+            .collect();
+        pools.sort_by_key(|pool| pool.raster_engine);
+
         HealthStatus {
-            pool_size: self.browser_pool.current_size(),
-            total_capacity: self.browser_pool.max_size,
+            pool_size: default_pool.current_size(),
+            total_capacity: default_pool.max_size,
             available_permits: self.render_semaphore.available_permits(),
-            max_concurrent: MAX_CONCURRENT_RENDERS,
+            max_concurrent: self.settings.max_concurrent,
+            pools,
+            warming: self.is_warming(),
+            lazy_pool: default_pool.min_size == 0,
+            launch_backoff: default_pool.in_launch_backoff(),
+            slow_renders: self.slow_renders.load(Ordering::Relaxed),
+            renders_served: self.renders_served.load(Ordering::Relaxed),
+            renders_failed: self.renders_failed.load(Ordering::Relaxed),
+            cache_hits: self.cache.as_ref().map_or(0, |cache| cache.hits()),
+            cache_misses: self.cache.as_ref().map_or(0, |cache| cache.misses()),
+            cache_entries: self.cache.as_ref().map_or(0, |cache| cache.len()),
+        }
+    }
+}
+
+impl From<&ClipRegion> for Page::Viewport {
+    fn from(clip: &ClipRegion) -> Self {
+        Self {
+            x: clip.x as f64,
+            y: clip.y as f64,
+            width: clip.width as f64,
+            height: clip.height as f64,
+            scale: 1.0,
+        }
+    }
+}
+
+impl From<&ScreenOrientation> for Emulation::ScreenOrientation {
+    fn from(orientation: &ScreenOrientation) -> Self {
+        use Emulation::ScreenOrientationType as Type;
+
+        let (orientation_type, angle) = match orientation.orientation_type.as_str() {
+            "landscape-primary" => (Type::LandscapePrimary, 90),
+            "portrait-secondary" => (Type::PortraitSecondary, 180),
+            "landscape-secondary" => (Type::LandscapeSecondary, 270),
+            _ => (Type::PortraitPrimary, 0),
+        };
+        Self {
+            Type: orientation_type,
+            angle: orientation.angle.unwrap_or(angle),
+        }
+    }
+}
+
+impl From<&PdfOptions> for PrintToPdfOptions {
+    fn from(options: &PdfOptions) -> Self {
+        Self {
+            scale: options.scale,
+            page_ranges: options.page_ranges.clone(),
+            prefer_css_page_size: options.prefer_css_page_size,
+            display_header_footer: options.display_header_footer,
+            header_template: options.header_template.clone(),
+            footer_template: options.footer_template.clone(),
+            paper_width: options.paper_width,
+            paper_height: options.paper_height,
+            landscape: options.landscape,
+            margin_top: options.margin_top,
+            margin_bottom: options.margin_bottom,
+            margin_left: options.margin_left,
+            margin_right: options.margin_right,
+            print_background: options.print_background,
+            ..Default::default()
+        }
+    }
+}
+
+/// Print `request` to PDF as a CDP stream and send it on in
+/// `PDF_STREAM_CHUNK` pieces. Reads and sends share `deadline`: a client
+/// too slow to keep up fails the render with a `RenderError::Timeout`, and
+/// a wedged read with a `CaptureTimeout`. Failures after the first piece is
+/// sent are also `StreamInterrupted`.
+fn stream_pdf(
+    tab: &Arc<Tab>,
+    request: &RenderRequest,
+    deadline: Instant,
+    chunks: &tokio::sync::mpsc::Sender<Vec<u8>>,
+) -> Result<()> {
+    let options: PrintToPdfOptions = request
+        .options
+        .pdf_options
+        .as_ref()
+        .map(Into::into)
+        .unwrap_or_default();
+    let print = Page::PrintToPDF {
+        landscape: options.landscape,
+        display_header_footer: options.display_header_footer,
+        print_background: options.print_background,
+        scale: options.scale,
+        paper_width: options.paper_width,
+        paper_height: options.paper_height,
+        margin_top: options.margin_top,
+        margin_bottom: options.margin_bottom,
+        margin_left: options.margin_left,
+        margin_right: options.margin_right,
+        page_ranges: options.page_ranges,
+        header_template: options.header_template,
+        footer_template: options.footer_template,
+        prefer_css_page_size: options.prefer_css_page_size,
+        transfer_mode: Some(Page::PrintToPDFTransfer_modeOption::ReturnAsStream),
+        ..Default::default()
+    };
+    let handle = bounded_capture(tab, deadline, move |tab| {
+        tab.call_method(print)?
+            .stream
+            .ok_or_else(|| anyhow!("Chrome returned no PDF stream"))
+    })?;
+
+    let timed_out = || -> anyhow::Error {
+        RenderError::Timeout {
+            timeout: render_timeout(request),
+            detail: "streaming PDF output".to_string(),
+        }
+        .into()
+    };
+    // Runs on a blocking thread, which may still wait on the runtime
+    let runtime = tokio::runtime::Handle::current();
+
+    let mut sent = false;
+    let result = (|| {
+        loop {
+            if Instant::now() >= deadline {
+                return Err(timed_out());
+            }
+            let stream = handle.clone();
+            let read = bounded_capture(tab, deadline, move |tab| {
+                tab.call_method(IO::Read {
+                    handle: stream,
+                    offset: None,
+                    size: Some(PDF_STREAM_CHUNK),
+                })
+            })?;
+            let chunk = if read.base_64_encoded.unwrap_or(false) {
+                general_purpose::STANDARD.decode(&read.data)?
+            } else {
+                read.data.into_bytes()
+            };
+            if !chunk.is_empty() {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                match runtime.block_on(chunks.send_timeout(chunk, remaining)) {
+                    Ok(()) => sent = true,
+                    Err(SendTimeoutError::Timeout(_)) => return Err(timed_out()),
+                    Err(SendTimeoutError::Closed(_)) => return Err(anyhow!("output closed")),
+                }
+            }
+            if read.eof {
+                return Ok(());
+            }
+        }
+    })();
+
+    match result {
+        // The renderer is wedged, so closing the stream would block too;
+        // the caller discards the browser
+        Err(e) if e.is::<CaptureTimeout>() => Err(e),
+        result => {
+            let _ = tab.call_method(IO::Close { handle });
+            // Context keeps a timeout visible as one
+            result.map_err(|e| {
+                if sent {
+                    let interrupted = StreamInterrupted(e.to_string());
+                    e.context(interrupted)
+                } else {
+                    e
+                }
+            })
         }
     }
 }
+
+/// Collect the page's `console.*` calls from now on. Messages beyond
+/// `MAX_CONSOLE_MESSAGES` are counted in a final summary entry, and each text
+/// is cut at `MAX_CONSOLE_TEXT_CHARS`.
+fn collect_console(tab: &Arc<Tab>) -> Result<Arc<Mutex<Vec<ConsoleMessage>>>> {
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let dropped = AtomicU64::new(0);
+
+    tab.enable_runtime()?;
+    let collected = messages.clone();
+    tab.add_event_listener(Arc::new(move |event: &Event| {
+        if !matches!(
+            event,
+            Event::RuntimeConsoleAPICalled(_) | Event::RuntimeExceptionThrown(_)
+        ) {
+            return;
+        }
+
+        let mut messages = collected.lock();
+        if messages.len() >= MAX_CONSOLE_MESSAGES {
+            let dropped = dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            messages.truncate(MAX_CONSOLE_MESSAGES);
+            messages.push(ConsoleMessage {
+                level: "info".to_string(),
+                text: format!("{} more console messages omitted", dropped),
+            });
+            return;
+        }
+
+        let (level, text) = match event {
+            Event::RuntimeConsoleAPICalled(call) => {
+                let text = call
+                    .params
+                    .args
+                    .iter()
+                    .map(|arg| match &arg.value {
+                        Some(serde_json::Value::String(s)) => s.clone(),
+                        Some(value) => value.to_string(),
+                        None => arg.description.clone().unwrap_or_default(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let level = serde_json::to_value(&call.params.Type)
+                    .ok()
+                    .and_then(|level| level.as_str().map(str::to_string))
+                    .unwrap_or_else(|| "log".to_string());
+                (level, text)
+            }
+            // Uncaught, e.g. thrown from a library callback outside the
+            // init script's try/catch
+            Event::RuntimeExceptionThrown(thrown) => {
+                let details = &thrown.params.exception_details;
+                let text = details
+                    .exception
+                    .as_ref()
+                    .and_then(|exception| exception.description.clone())
+                    .unwrap_or_else(|| details.text.clone());
+                ("error".to_string(), text)
+            }
+            _ => return,
+        };
+
+        messages.push(ConsoleMessage {
+            level,
+            text: text.chars().take(MAX_CONSOLE_TEXT_CHARS).collect(),
+        });
+    }))?;
+
+    Ok(messages)
+}
+
+/// Whether `url`'s host is one of `allowed_hosts` or a subdomain of one.
+fn is_allowed_host(url: &str, allowed_hosts: &[String]) -> bool {
+    let Some(host) = url::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_ascii_lowercase))
+    else {
+        return false;
+    };
+
+    allowed_hosts.iter().any(|allowed| {
+        host == *allowed
+            || host
+                .strip_suffix(allowed.as_str())
+                .is_some_and(|prefix| prefix.ends_with('.'))
+    })
+}
+
+/// Poll a boolean expression until it is true, or fail with the render's
+/// `RenderError::Timeout` once `deadline` passes.
+fn wait_until(
+    tab: &Arc<Tab>,
+    expression: &str,
+    poll_interval: Duration,
+    (deadline, timeout): (Instant, Duration),
+    what: &str,
+) -> Result<()> {
+    loop {
+        let done = tab
+            .evaluate(expression, false)?
+            .value
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        if done {
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            return Err(RenderError::Timeout {
+                timeout,
+                detail: format!("waiting for {}", what),
+            }
+            .into());
+        }
+        sleep(poll_interval);
+    }
+}
+
+/// Content size along one axis, kept between the requested size and
+/// `MAX_CAPTURE_DIMENSION`.
+fn fit_to_content(axis: &str, content: u32, requested: u32) -> u32 {
+    if content > MAX_CAPTURE_DIMENSION {
+        tracing::warn!(
+            "Content {} {}px exceeds capture limit, clipping to {}px",
+            axis,
+            content,
+            MAX_CAPTURE_DIMENSION
+        );
+    }
+    content.clamp(requested, MAX_CAPTURE_DIMENSION)
+}
+
+/// Evaluate a numeric page expression, e.g. a scroll dimension in CSS pixels.
+fn measure(tab: &Arc<Tab>, expression: &str) -> Result<u32> {
+    tab.evaluate(expression, false)?
+        .value
+        .and_then(|v| v.as_f64())
+        .map(|v| v.ceil() as u32)
+        .ok_or_else(|| anyhow!("Failed to evaluate '{}'", expression))
+}
+
+/// Serialize the element matching `selector` as a standalone SVG document.
+/// XMLSerializer, unlike `outerHTML`, always writes the `xmlns` attribute.
+fn extract_svg(tab: &Arc<Tab>, selector: &str) -> Result<Vec<u8>> {
+    let expression = format!(
+        r#"(() => {{
+            const svg = document.querySelector({});
+            return svg ? new XMLSerializer().serializeToString(svg) : null;
+        }})()"#,
+        serde_json::to_string(selector)?
+    );
+    let svg = tab
+        .evaluate(&expression, false)?
+        .value
+        .and_then(|v| v.as_str().map(String::from))
+        .ok_or_else(|| anyhow!("No SVG element matches '{}'", selector))?;
+
+    Ok(svg.into_bytes())
+}
+
+/// Timeout for the whole render, from `timeout_ms`.
+fn render_timeout(request: &RenderRequest) -> Duration {
+    Duration::from_millis(request.options.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS))
+}
+
+fn screenshot(
+    tab: &Arc<Tab>,
+    format: Page::CaptureScreenshotFormatOption,
+    quality: Option<u32>,
+    clip: Option<Page::Viewport>,
+    deadline: Instant,
+) -> Result<Vec<u8>> {
+    bounded_capture(tab, deadline, move |tab| {
+        tab.capture_screenshot(format, quality, clip, true)
+    })
+}
+
+/// Run a capture CDP call on a helper thread and give up on it at
+/// `deadline` (but no sooner than `MIN_CAPTURE_TIMEOUT`). headless_chrome
+/// otherwise waits on a wedged renderer for the browser's idle timeout.
+fn bounded_capture<T: Send + 'static>(
+    tab: &Arc<Tab>,
+    deadline: Instant,
+    call: impl FnOnce(&Tab) -> Result<T> + Send + 'static,
+) -> Result<T> {
+    let timeout = deadline
+        .saturating_duration_since(Instant::now())
+        .max(MIN_CAPTURE_TIMEOUT);
+
+    let tab = tab.clone();
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        // The receiver is gone if we already timed out
+        let _ = sender.send(call(&tab));
+    });
+
+    receiver
+        .recv_timeout(timeout)
+        .map_err(|_| CaptureTimeout(timeout))?
+}
+
+fn decode_rgba(png: &[u8]) -> Result<RawImage> {
+    let image = image::load_from_memory_with_format(png, image::ImageFormat::Png)
+        .map_err(|e| anyhow!("Failed to decode capture: {}", e))?
+        .into_rgba8();
+
+    Ok(RawImage {
+        width: image.width(),
+        height: image.height(),
+        pixels: image.into_raw(),
+    })
+}
+
+/// `bytes` rendered with `options`, base64 encoded along with its size.
+/// Raster dimensions come from the image itself, so they include content
+/// sizing; SVG and PDF report the viewport they were rendered at.
+fn base64_response(bytes: &[u8], options: &RenderOptions) -> Base64Response {
+    let (width, height) = metadata::image_dimensions(bytes, &options.format)
+        .unwrap_or_else(|| options.output_dimensions());
+
+    Base64Response {
+        data: general_purpose::STANDARD.encode(bytes),
+        mime_type: mime_type(&options.format).to_string(),
+        width,
+        height,
+        size_bytes: bytes.len() as u64,
+        html: None,
+        console: None,
+    }
+}
+
+pub(crate) fn mime_type(format: &str) -> &'static str {
+    match format {
+        "png" => "image/png",
+        "jpeg" | "jpg" => "image/jpeg",
+        "webp" => "image/webp",
+        "pdf" => "application/pdf",
+        "svg" => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
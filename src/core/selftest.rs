@@ -0,0 +1,207 @@
+use anyhow::{Result, anyhow};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::core::registry;
+use crate::core::renderer::RenderingEngine;
+use crate::schemas::render::RenderRequest;
+
+const PNG_SIGNATURE: &[u8] = &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+pub struct SelfTestResult {
+    pub library: String,
+    pub duration: Duration,
+    pub outcome: Result<PathBuf>,
+}
+
+impl SelfTestResult {
+    pub fn passed(&self) -> bool {
+        self.outcome.is_ok()
+    }
+}
+
+/// Render one sample chart per registered library into `output_dir` and
+/// check that each output is a valid PNG.
+pub async fn run(engine: &RenderingEngine, output_dir: &Path) -> Vec<SelfTestResult> {
+    let libraries = registry::library_names();
+
+    let mut results = Vec::with_capacity(libraries.len());
+    for library in libraries {
+        let start = Instant::now();
+        let outcome = render_sample(engine, &library, output_dir).await;
+
+        results.push(SelfTestResult {
+            library,
+            duration: start.elapsed(),
+            outcome,
+        });
+    }
+
+    results
+}
+
+async fn render_sample(
+    engine: &RenderingEngine,
+    library: &str,
+    output_dir: &Path,
+) -> Result<PathBuf> {
+    let request = sample_request(library)
+        .ok_or_else(|| anyhow!("No self-test sample defined for library"))?;
+
+    let bytes = engine.render(request).await?;
+    check_png(&bytes)?;
+
+    let path = output_dir.join(format!("{}.png", library));
+    std::fs::write(&path, &bytes)?;
+    Ok(path)
+}
+
+pub(crate) fn check_png(bytes: &[u8]) -> Result<()> {
+    if !bytes.starts_with(PNG_SIGNATURE) {
+        return Err(anyhow!("Output is not a valid PNG ({} bytes)", bytes.len()));
+    }
+    Ok(())
+}
+
+/// Minimal known-good payload for each registered library.
+pub fn sample_request(library: &str) -> Option<RenderRequest> {
+    let (version, data) = match library {
+        "apache-echarts" => (
+            "5.4.0",
+            json!({
+                "xAxis": { "data": ["A", "B", "C"] },
+                "yAxis": {},
+                "series": [{ "type": "bar", "data": [10, 20, 30] }]
+            }),
+        ),
+        "echarts-map" => (
+            "5.4.0",
+            json!({
+                "geojson": {
+                    "type": "FeatureCollection",
+                    "features": [{
+                        "type": "Feature",
+                        "properties": { "name": "Square" },
+                        "geometry": {
+                            "type": "Polygon",
+                            "coordinates": [[[0, 0], [10, 0], [10, 10], [0, 10], [0, 0]]]
+                        }
+                    }]
+                },
+                "option": {
+                    "series": [{ "type": "map", "map": "geo", "data": [{ "name": "Square", "value": 1 }] }]
+                }
+            }),
+        ),
+        "chartjs" => (
+            "4.4.0",
+            json!({
+                "type": "bar",
+                "data": {
+                    "labels": ["A", "B", "C"],
+                    "datasets": [{ "label": "Self-test", "data": [10, 20, 30] }]
+                }
+            }),
+        ),
+        "konvajs" => (
+            "9.2.0",
+            json!({
+                "shapes": [{
+                    "type": "Rect",
+                    "config": { "x": 20, "y": 20, "width": 100, "height": 60, "fill": "steelblue" }
+                }]
+            }),
+        ),
+        "konvajs-json" => (
+            "9.2.0",
+            json!({
+                "attrs": { "width": 400, "height": 300 },
+                "className": "Stage",
+                "children": [{
+                    "attrs": {},
+                    "className": "Layer",
+                    "children": [{
+                        "attrs": { "x": 20, "y": 20, "radius": 40, "fill": "tomato" },
+                        "className": "Circle"
+                    }]
+                }]
+            }),
+        ),
+        "billboardjs" => (
+            "3.14.0",
+            json!({
+                "data": { "columns": [["Self-test", 10, 20, 30]], "type": "bar" }
+            }),
+        ),
+        "highcharts" => (
+            "11.4.8",
+            json!({
+                "chart": { "type": "bar" },
+                "title": { "text": "Self-test" },
+                "xAxis": { "categories": ["A", "B", "C"] },
+                "series": [{ "name": "Self-test", "data": [10, 20, 30] }]
+            }),
+        ),
+        "apexcharts" => (
+            "3.54.1",
+            json!({
+                "chart": { "type": "bar" },
+                "series": [{ "name": "Self-test", "data": [10, 20, 30] }],
+                "xaxis": { "categories": ["A", "B", "C"] }
+            }),
+        ),
+        "plotly" => (
+            "2.35.2",
+            json!({
+                "traces": [{ "type": "bar", "x": ["A", "B", "C"], "y": [10, 20, 30] }],
+                "layout": { "title": { "text": "Self-test" } }
+            }),
+        ),
+        "d3" => (
+            "7.9.0",
+            json!({
+                "values": [10, 20, 30],
+                "renderCode": "d3.select(container).append('svg').attr('width', width).attr('height', height).selectAll('rect').data(data.values).join('rect').attr('x', (d, i) => i * 60 + 20).attr('y', d => height - d * 5).attr('width', 40).attr('height', d => d * 5).attr('fill', 'steelblue');"
+            }),
+        ),
+        "raw-html" => (
+            "1.0.0",
+            json!({
+                "html": "<svg width=\"400\" height=\"300\"><rect x=\"50\" y=\"50\" width=\"300\" height=\"200\" fill=\"steelblue\"/></svg>"
+            }),
+        ),
+        "mermaid" => (
+            "11.4.1",
+            json!({ "definition": "graph LR; Self-test --> Passed" }),
+        ),
+        "leaflet" => (
+            "1.9.4",
+            json!({
+                "center": [51.505, -0.09],
+                "zoom": 13,
+                "markers": [{ "lat": 51.505, "lng": -0.09, "label": "Self-test" }]
+            }),
+        ),
+        "vega-lite" => (
+            "5.21.0",
+            json!({
+                "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+                "data": { "values": [{ "x": "A", "y": 10 }, { "x": "B", "y": 20 }, { "x": "C", "y": 30 }] },
+                "mark": "bar",
+                "encoding": {
+                    "x": { "field": "x", "type": "nominal" },
+                    "y": { "field": "y", "type": "quantitative" }
+                }
+            }),
+        ),
+        _ => return None,
+    };
+
+    serde_json::from_value(json!({
+        "library": { "name": library, "version": version },
+        "data": data,
+        "options": { "width": 400, "height": 300, "format": "png" }
+    }))
+    .ok()
+}
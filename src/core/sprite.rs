@@ -0,0 +1,77 @@
+use anyhow::{Result, anyhow};
+use image::{ImageFormat, RgbaImage, imageops};
+use std::io::Cursor;
+
+use crate::schemas::render::SpriteFrame;
+
+pub struct Sprite {
+    pub png: Vec<u8>,
+    pub css: String,
+    pub frames: Vec<SpriteFrame>,
+}
+
+/// Stack captured PNG regions vertically into one sprite sheet and generate
+/// CSS that shows each region by class (`.sprite-0`, `.sprite-1`, ...).
+///
+/// `scale` is the device scale factor the regions were captured at; CSS
+/// sizes and offsets are expressed in CSS pixels so the sheet displays at
+/// its intended size on any screen.
+pub fn build(captures: Vec<(String, Vec<u8>)>, scale: f64) -> Result<Sprite> {
+    let images = captures
+        .into_iter()
+        .map(|(selector, png)| {
+            image::load_from_memory_with_format(&png, ImageFormat::Png)
+                .map(|image| (selector, image.to_rgba8()))
+                .map_err(|e| anyhow!("Failed to decode sprite frame: {}", e))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let width = images
+        .iter()
+        .map(|(_, image)| image.width())
+        .max()
+        .unwrap_or(0);
+    let height = images.iter().map(|(_, image)| image.height()).sum();
+    if width == 0 || height == 0 {
+        return Err(anyhow!("Sprite sheet would be empty"));
+    }
+
+    let css_px = |pixels: u32| pixels as f64 / scale;
+
+    let mut sheet = RgbaImage::new(width, height);
+    let mut css = format!(
+        ".sprite {{ background-repeat: no-repeat; background-size: {}px {}px; }}\n",
+        css_px(width),
+        css_px(height)
+    );
+    let mut frames = Vec::with_capacity(images.len());
+
+    let mut offset = 0;
+    for (index, (selector, image)) in images.into_iter().enumerate() {
+        imageops::replace(&mut sheet, &image, 0, offset as i64);
+
+        let class_name = format!("sprite-{}", index);
+        css.push_str(&format!(
+            ".{} {{ width: {}px; height: {}px; background-position: 0 -{}px; }}\n",
+            class_name,
+            css_px(image.width()),
+            css_px(image.height()),
+            css_px(offset)
+        ));
+        frames.push(SpriteFrame {
+            selector,
+            class_name,
+            x: 0,
+            y: offset,
+            width: image.width(),
+            height: image.height(),
+        });
+
+        offset += image.height();
+    }
+
+    let mut png = Vec::new();
+    sheet.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+
+    Ok(Sprite { png, css, frames })
+}
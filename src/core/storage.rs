@@ -0,0 +1,103 @@
+use anyhow::{Result, anyhow};
+use object_store::aws::{AmazonS3, AmazonS3Builder};
+use object_store::path::Path;
+use object_store::{Attribute, Attributes, ObjectStore, PutPayload};
+use std::fmt;
+
+use crate::settings::Config;
+
+/// Uploading a finished render to object storage failed. The render itself
+/// succeeded, so the API maps this to 502 rather than 500.
+#[derive(Debug)]
+pub struct UploadFailed(pub String);
+
+impl fmt::Display for UploadFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Upload to object storage failed: {}", self.0)
+    }
+}
+
+impl std::error::Error for UploadFailed {}
+
+/// S3-compatible bucket that renders with `output_key` are written to.
+pub struct ObjectStorage {
+    store: AmazonS3,
+    public_url: String,
+}
+
+impl ObjectStorage {
+    /// Build the sink from `storage_*` settings, falling back to the standard
+    /// `AWS_*` environment variables for anything unset. Returns `None` when
+    /// no `storage_bucket` is configured, which disables the feature.
+    pub fn from_config(config: &Config) -> Result<Option<Self>> {
+        let Some(bucket) = &config.storage_bucket else {
+            return Ok(None);
+        };
+
+        let mut builder = AmazonS3Builder::from_env().with_bucket_name(bucket);
+        if let Some(region) = &config.storage_region {
+            builder = builder.with_region(region);
+        }
+        if let Some(endpoint) = &config.storage_endpoint {
+            builder = builder
+                .with_endpoint(endpoint)
+                .with_allow_http(endpoint.starts_with("http://"));
+        }
+        if let Some(access_key_id) = &config.storage_access_key_id {
+            builder = builder.with_access_key_id(access_key_id);
+        }
+        if let Some(secret_access_key) = &config.storage_secret_access_key {
+            builder = builder.with_secret_access_key(secret_access_key);
+        }
+
+        let public_url = match (&config.storage_public_url, &config.storage_endpoint) {
+            (Some(url), _) => url.trim_end_matches('/').to_string(),
+            (None, Some(endpoint)) => format!("{}/{}", endpoint.trim_end_matches('/'), bucket),
+            (None, None) => format!(
+                "https://{}.s3.{}.amazonaws.com",
+                bucket,
+                config.storage_region.as_deref().unwrap_or("us-east-1")
+            ),
+        };
+
+        Ok(Some(Self {
+            store: builder.build()?,
+            public_url,
+        }))
+    }
+
+    /// Upload `bytes` under `key` and return the object's URL.
+    pub async fn upload(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> Result<String> {
+        let path = Path::parse(key).map_err(|e| anyhow!("Invalid output_key: {}", e))?;
+
+        let mut attributes = Attributes::new();
+        attributes.insert(Attribute::ContentType, content_type.to_string().into());
+
+        self.store
+            .put_opts(&path, PutPayload::from(bytes), attributes.into())
+            .await
+            .map_err(|e| UploadFailed(e.to_string()))?;
+
+        Ok(format!("{}/{}", self.public_url, path))
+    }
+}
+
+/// Output format implied by `key`'s extension, e.g. `pdf` for
+/// `reports/q3.pdf`.
+pub fn format_for_key(key: &str) -> Option<&'static str> {
+    let (_, extension) = key.rsplit_once('.')?;
+    match extension.to_ascii_lowercase().as_str() {
+        "png" => Some("png"),
+        "jpg" | "jpeg" => Some("jpeg"),
+        "pdf" => Some("pdf"),
+        "webp" => Some("webp"),
+        "rgba" => Some("rgba"),
+        "svg" => Some("svg"),
+        _ => None,
+    }
+}
+
+/// Whether `key` is usable as an object key.
+pub fn is_valid_key(key: &str) -> bool {
+    !key.is_empty() && Path::parse(key).is_ok()
+}
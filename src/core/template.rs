@@ -1,31 +1,159 @@
 use anyhow::{Result, anyhow};
+use serde_json::{Value, json};
+use std::{fmt, io};
 use url::Url;
 
-use crate::{core::registry::LIBRARY_REGISTRY, schemas::render::RenderRequest};
+use crate::{
+    core::{
+        registry::{self, LibraryTemplate, ReadyStrategy},
+        renderer::RenderError,
+        validation,
+    },
+    schemas::render::RenderRequest,
+};
 
-pub fn generate_html(request: &RenderRequest) -> Result<String> {
-    let library_template = LIBRARY_REGISTRY
-        .get(&request.library.name)
-        .ok_or_else(|| anyhow!("Unsupported library: {}", request.library.name))?;
+/// Hosts a caller-supplied `cdn_url` may load from unless the server sets
+/// `allowed_cdn_hosts`. Templates loaded from `library_registry_path` are
+/// checked against these whatever `allowed_cdn_hosts` says; built-in
+/// templates are not checked.
+pub const DEFAULT_CDN_HOSTS: [&str; 3] = ["cdn.jsdelivr.net", "unpkg.com", "cdnjs.cloudflare.com"];
+
+/// Hosts `options.fonts` URLs may load from besides the CDN hosts.
+pub const FONT_HOSTS: [&str; 2] = ["fonts.googleapis.com", "fonts.gstatic.com"];
+
+/// Largest serialized `data` a page is built with. Bigger scenes take long
+/// to encode and load, then fail obscurely, so they are refused up front.
+pub const MAX_DATA_BYTES: usize = 32 * 1024 * 1024;
+
+/// A request's `data` serializes to more than `MAX_DATA_BYTES`.
+#[derive(Debug)]
+pub struct DataTooLarge(pub usize);
+
+impl fmt::Display for DataTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "data exceeds {} bytes once serialized", self.0)
+    }
+}
+
+impl std::error::Error for DataTooLarge {}
+
+/// What `{data}` expands to in init scripts: the request data, parsed from
+/// the inert `render-data` block rather than spliced into script source.
+const READ_RENDER_DATA: &str = "JSON.parse(document.getElementById('render-data').textContent)";
+
+/// With `wait_for_fonts`, an init script setting `renderReady` only takes
+/// effect once `document.fonts.ready` resolves. Hooking the setter covers
+/// async libraries that report done from a callback, and custom templates,
+/// without each script awaiting fonts itself.
+const FONT_READY_GATE: &str = r#"(() => {
+            let ready = false;
+            Object.defineProperty(window, 'renderReady', {
+                configurable: true,
+                get: () => ready,
+                set: (value) => {
+                    if (value !== true) {
+                        ready = value;
+                        return;
+                    }
+                    document.fonts.ready.then(() => { ready = true; });
+                },
+            });
+        })();"#;
+
+pub fn generate_html(request: &RenderRequest, allowed_cdn_hosts: &[String]) -> Result<String> {
+    let library = registry::canonical_name(&request.library.name)
+        .ok_or_else(|| RenderError::UnsupportedLibrary(request.library.name.clone()))?;
+    let library_template = registry::get_template(&library)
+        .ok_or_else(|| RenderError::UnsupportedLibrary(request.library.name.clone()))?;
+
+    if let Some((_, message)) = version_error(request, &library_template) {
+        return Err(anyhow!(message));
+    }
+    let version = resolve_version(request, &library_template);
 
     let cdn_url = if let Some(ref custom_url) = request.library.cdn_url {
-        validate_cdn_url(custom_url)?;
+        validate_cdn_url(custom_url, allowed_cdn_hosts)?;
         custom_url.clone()
     } else {
-        library_template
-            .cdn_url
-            .replace("{version}", &request.library.version)
+        library_template.cdn_url.replace("{version}", version)
     };
 
-    let data_json = serde_json::to_string(&request.data)?;
+    let mut plugin_scripts = Vec::new();
+    let mut plugin_globals = Vec::new();
+    for name in request.library.plugins.iter().flatten() {
+        let plugin = library_template.plugins.get(name).ok_or_else(|| {
+            anyhow!(
+                "Library '{}' has no plugin '{}'",
+                request.library.name,
+                name
+            )
+        })?;
+        plugin_scripts.push(plugin.script_url.clone());
+        plugin_globals.push(format!(
+            "window[{}]",
+            serde_json::to_string(&plugin.global)?
+        ));
+    }
+
+    let library_script = std::iter::once(cdn_url)
+        .filter(|url| !url.is_empty())
+        .chain(
+            library_template
+                .extra_scripts
+                .iter()
+                .map(|url| url.replace("{version}", version)),
+        )
+        .chain(plugin_scripts)
+        .map(|url| format!(r#"<script src="{}"></script>"#, url))
+        .collect::<Vec<_>>()
+        .join("\n    ");
+
+    let head_html = library_template
+        .head_html
+        .as_deref()
+        .map(|html| format!("\n    {}", html.trim()))
+        .unwrap_or_default();
+
+    let data_json = script_safe_json(&serialize_data(&request.data, MAX_DATA_BYTES)?);
+
+    let theme = match &request.options.theme {
+        Some(name) => {
+            let config = library_template.themes.get(name).ok_or_else(|| {
+                anyhow!("Library '{}' has no theme '{}'", request.library.name, name)
+            })?;
+            script_safe_json(&serde_json::to_string(
+                &json!({ "name": name, "config": config }),
+            )?)
+        }
+        None => "null".to_string(),
+    };
 
     let init_script = library_template
         .init_script
-        .replace("{data}", "JSON.parse(dataJson)")
-        .replace("{width}", &request.options.width.to_string())
-        .replace("{height}", &request.options.height.to_string());
+        .replace("{data}", READ_RENDER_DATA)
+        .replace("{plugins}", &format!("[{}]", plugin_globals.join(", ")))
+        .replace("{theme}", &theme)
+        .replace(
+            "{animation}",
+            if request.options.disable_animation.unwrap_or(true) {
+                "false"
+            } else {
+                "true"
+            },
+        )
+        .replace(
+            "{renderer}",
+            if request.options.svg_renderer() {
+                "'svg'"
+            } else {
+                "'canvas'"
+            },
+        )
+        .replace("{width}", &request.options.pixel_width().to_string())
+        .replace("{height}", &request.options.pixel_height().to_string());
+    let init_script = with_ready_strategy(&init_script, library_template.ready_strategy);
 
-    let canvas_element = if request.library.name == "chartjs" {
+    let canvas_element = if library == "chartjs" {
         r#"<canvas id="chart-canvas"></canvas>"#
     } else {
         ""
@@ -33,6 +161,92 @@ pub fn generate_html(request: &RenderRequest) -> Result<String> {
 
     let device_pixel_ratio = request.options.device_scale_factor.unwrap_or(1.0);
 
+    // Runs before the library loads, so any layout randomness it draws on
+    // comes from this seeded PRNG (mulberry32)
+    let seeded_random = match request.options.seed {
+        Some(seed) => format!(
+            r#"
+        Math.random = (() => {{
+            let state = {};
+            return () => {{
+                state = (state + 0x6d2b79f5) >>> 0;
+                let t = state;
+                t = Math.imul(t ^ (t >>> 15), t | 1);
+                t ^= t + Math.imul(t ^ (t >>> 7), t | 61);
+                return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+            }};
+        }})();"#,
+            (seed ^ (seed >> 32)) as u32
+        ),
+        None => String::new(),
+    };
+
+    // With visible overflow the container is pinned to the top-left so that
+    // content spilling past it extends the page right/down, where
+    // auto_width/auto_height can measure and capture it.
+    let overflow = request.options.overflow.as_deref().unwrap_or("hidden");
+    let justify_content = if overflow == "visible" {
+        "flex-start"
+    } else {
+        "center"
+    };
+
+    let background = match &request.options.background_color {
+        Some(color) if !validation::is_css_color(color) => {
+            return Err(anyhow!("'{}' is not a CSS color", color));
+        }
+        Some(color) => color.trim(),
+        None if request.options.transparent.unwrap_or(false) => "transparent",
+        None => "white",
+    };
+
+    let mut font_rules = String::new();
+    let mut font_families = Vec::new();
+    for font in request.options.fonts.iter().flatten() {
+        if !validation::is_font_family(&font.family) {
+            return Err(anyhow!("'{}' is not a valid font family", font.family));
+        }
+        if let Some(url) = font.url.as_ref().or(font.stylesheet_url.as_ref()) {
+            validate_font_host(url, allowed_cdn_hosts)?;
+        }
+        match (&font.url, &font.stylesheet_url) {
+            (Some(url), None) => font_rules.push_str(&format!(
+                "\n    <style>@font-face {{ font-family: \"{}\"; src: url(\"{}\"); }}</style>",
+                font.family,
+                font_url(url)?
+            )),
+            (None, Some(url)) => font_rules.push_str(&format!(
+                "\n    <link rel=\"stylesheet\" href=\"{}\">",
+                font_url(url)?
+            )),
+            _ => {
+                return Err(anyhow!(
+                    "Font '{}' needs one of url or stylesheet_url",
+                    font.family
+                ));
+            }
+        }
+        font_families.push(&font.family);
+    }
+
+    // Fonts are only fetched once something uses them, so they are loaded
+    // explicitly before init; canvas text drawn with a missing font keeps
+    // the fallback glyphs.
+    let run_init = if font_families.is_empty() {
+        "init();".to_string()
+    } else {
+        format!(
+            "Promise.all({}.map(family => document.fonts.load(`16px \"${{family}}\"`)))\n                .catch(error => console.warn('Font loading failed:', error))\n                .then(init);",
+            serde_json::to_string(&font_families)?
+        )
+    };
+
+    let ready_flag = if request.options.wait_for_fonts.unwrap_or(true) {
+        FONT_READY_GATE
+    } else {
+        "window.renderReady = false;"
+    };
+
     let html = format!(
         r#"<!DOCTYPE html>
 <html>
@@ -47,11 +261,11 @@ pub fn generate_html(request: &RenderRequest) -> Result<String> {
             box-sizing: border-box;
         }}
         body {{
-            background: white;
-            overflow: hidden;
+            background: {};
+            overflow: {};
             display: flex;
             align-items: center;
-            justify-content: center;
+            justify-content: {};
         }}
         #render-container {{
             width: {}px;
@@ -60,60 +274,221 @@ pub fn generate_html(request: &RenderRequest) -> Result<String> {
         #chart-canvas {{
             display: block;
         }}
-    </style>
+    </style>{}{}
 </head>
 <body>
     <div id="render-container">
         {}
     </div>
 
+    <script type="application/json" id="render-data">{}</script>
     <script>
-        window.devicePixelRatio = {};
-        const dataJson = '{}';
+        window.devicePixelRatio = {};{}
     </script>
-    <script src="{}"></script>
+    {}
 
     <script>
-        window.renderReady = false;
+        {}
         window.renderError = null;
 
         window.addEventListener('DOMContentLoaded', () => {{
-            try {{
-                {}
-            }} catch (error) {{
-                console.error('Render initialization error:', error);
-                window.renderError = error.message;
-            }}
+            const init = () => {{
+                try {{
+                    {}
+                }} catch (error) {{
+                    console.error('Render initialization error:', error);
+                    window.renderError = error.message;
+                }}
+            }};
+            {}
         }});
     </script>
 </body>
 </html>"#,
-        request.options.width,
-        request.options.height,
+        background,
+        overflow,
+        justify_content,
+        request.options.pixel_width(),
+        request.options.pixel_height(),
+        font_rules,
+        head_html,
         canvas_element,
+        data_json,
         device_pixel_ratio,
-        data_json.replace('\'', "\\'").replace('\n', "\\n"),
-        cdn_url,
-        init_script
+        seeded_random,
+        library_script,
+        ready_flag,
+        init_script,
+        run_init
     );
 
     Ok(html)
 }
 
-fn validate_cdn_url(url: &str) -> Result<()> {
-    const ALLOWED_DOMAINS: &[&str] = &["cdn.jsdelivr.net", "unpkg.com", "cdnjs.cloudflare.com"];
+/// `library.version`, or the template's default when it is left out.
+fn resolve_version<'a>(request: &'a RenderRequest, template: &'a LibraryTemplate) -> &'a str {
+    match request.library.version.trim() {
+        "" => template.default_version.as_deref().unwrap_or_default(),
+        version => version,
+    }
+}
+
+/// The request field and message when a script URL would keep a literal
+/// `{version}`: the page would fail to load the script and the render would
+/// only end at `timeout_ms`.
+pub(crate) fn version_error(
+    request: &RenderRequest,
+    template: &LibraryTemplate,
+) -> Option<(&'static str, String)> {
+    let version = resolve_version(request, template);
+    if let Some(url) = &request.library.cdn_url
+        && (url.contains("{version}") || url.to_ascii_lowercase().contains("%7bversion%7d"))
+    {
+        return Some((
+            "cdn_url",
+            "cdn_url must name a version, {version} is not substituted in it".to_string(),
+        ));
+    }
+
+    let mut template_urls = request
+        .library
+        .cdn_url
+        .is_none()
+        .then_some(&template.cdn_url)
+        .into_iter()
+        .chain(&template.extra_scripts);
+    if version.is_empty() && template_urls.any(|url| url.contains("{version}")) {
+        return Some((
+            "version",
+            format!(
+                "library '{}' has no default version, set library.version",
+                request.library.name
+            ),
+        ));
+    }
+
+    None
+}
+
+/// Wrap `script` so it sets `renderReady` the way `strategy` describes.
+/// Errors thrown synchronously reach the `init` wrapper's catch as before.
+fn with_ready_strategy(script: &str, strategy: Option<ReadyStrategy>) -> String {
+    match strategy {
+        None => script.to_string(),
+        Some(ReadyStrategy::Sync) => {
+            format!(
+                "{}\n                window.renderReady = true;",
+                script.trim_end()
+            )
+        }
+        Some(ReadyStrategy::PromiseThen) => format!(
+            r#"Promise.resolve((() => {{{}}})())
+                        .then(() => {{ window.renderReady = true; }})
+                        .catch((error) => {{ window.renderError = error.message || String(error); }});"#,
+            script
+        ),
+        Some(ReadyStrategy::CallbackEvent) => format!(
+            r#"const renderDone = (error) => {{
+                        if (error) {{
+                            window.renderError = error.message || String(error);
+                        }} else {{
+                            window.renderReady = true;
+                        }}
+                    }};{}"#,
+            script
+        ),
+    }
+}
+
+/// Serialize `data`, giving up with `DataTooLarge` as soon as the output
+/// passes `limit` rather than building the whole string first.
+fn serialize_data(data: &Value, limit: usize) -> Result<String> {
+    struct Limited {
+        buf: Vec<u8>,
+        limit: usize,
+    }
+
+    impl io::Write for Limited {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            if self.buf.len() + bytes.len() > self.limit {
+                return Err(io::Error::other(DataTooLarge(self.limit)));
+            }
+            self.buf.extend_from_slice(bytes);
+            Ok(bytes.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut writer = Limited {
+        buf: Vec::new(),
+        limit,
+    };
+    match serde_json::to_writer(&mut writer, data) {
+        Ok(()) => Ok(String::from_utf8(writer.buf)?),
+        Err(e) if e.is_io() => Err(DataTooLarge(limit).into()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Escape `<`, `>` and `&` in serialized JSON so it can sit inside a
+/// `<script>` element without `</script>` or `<!--` ending it early. These
+/// only occur inside JSON strings, where `\u003c` etc. decode back to the
+/// same characters.
+fn script_safe_json(json: &str) -> String {
+    json.replace('<', "\\u003c")
+        .replace('>', "\\u003e")
+        .replace('&', "\\u0026")
+}
+
+/// A font or font stylesheet URL, normalized for quoting into HTML and CSS.
+/// Parsing percent-encodes quotes and angle brackets; backslashes, which
+/// would escape the closing quote in CSS, are encoded too.
+pub(crate) fn font_url(url: &str) -> Result<String> {
+    let parsed = Url::parse(url).map_err(|_| anyhow!("Invalid font URL '{}'", url))?;
+    if parsed.scheme() != "https" {
+        return Err(anyhow!("Font URL must use HTTPS"));
+    }
+    Ok(parsed.as_str().replace('\\', "%5C"))
+}
+
+/// Fonts load from the CDN hosts the server allows or from `FONT_HOSTS`.
+pub(crate) fn validate_font_host(url: &str, allowed_cdn_hosts: &[String]) -> Result<()> {
+    let parsed = Url::parse(url).map_err(|_| anyhow!("Invalid font URL '{}'", url))?;
+    let host = parsed.host_str().unwrap_or_default();
+    if FONT_HOSTS.contains(&host) || allowed_cdn_hosts.iter().any(|allowed| allowed == host) {
+        return Ok(());
+    }
+    Err(anyhow!(
+        "font host '{}' not allowed. Allowed hosts: {}",
+        host,
+        allowed_cdn_hosts
+            .iter()
+            .map(String::as_str)
+            .chain(FONT_HOSTS)
+            .collect::<Vec<_>>()
+            .join(", ")
+    ))
+}
 
+pub(crate) fn validate_cdn_url<S: AsRef<str>>(url: &str, allowed_hosts: &[S]) -> Result<()> {
     let parsed = Url::parse(url).map_err(|_| anyhow!("Invalid CDN URL format"))?;
 
     let host = parsed
         .host_str()
         .ok_or_else(|| anyhow!("CDN URL must have a host"))?;
 
-    if !ALLOWED_DOMAINS.iter().any(|&allowed| host == allowed) {
+    if !allowed_hosts.iter().any(|allowed| allowed.as_ref() == host) {
         return Err(anyhow!(
-            "CDN domain '{}' not allowed. Allowed domains: {:?}",
+            "CDN domain '{}' not allowed. Allowed domains: {}",
             host,
-            ALLOWED_DOMAINS
+            allowed_hosts
+                .iter()
+                .map(AsRef::as_ref)
+                .collect::<Vec<_>>()
+                .join(", ")
         ));
     }
 
@@ -0,0 +1,406 @@
+use serde_json::Value as JsonValue;
+use std::ops::RangeInclusive;
+
+use crate::core::{data_schema, registry, storage, template};
+use crate::schemas::common::UnprocessableEntityResponse;
+use crate::schemas::render::RenderRequest;
+
+/// Same bounds as the `width`/`height` schema validators
+const PIXEL_RANGE: RangeInclusive<u32> = 100..=4000;
+
+/// Same bound as the `interaction_delay_ms` schema validator
+const MAX_INTERACTION_DELAY_MS: u64 = 5000;
+
+/// CSS named colors, accepted by `background_color` besides hex and
+/// rgb()/hsl() notation
+const CSS_COLOR_NAMES: &str = "aliceblue antiquewhite aqua aquamarine azure beige bisque black \
+    blanchedalmond blue blueviolet brown burlywood cadetblue chartreuse chocolate coral \
+    cornflowerblue cornsilk crimson cyan darkblue darkcyan darkgoldenrod darkgray darkgreen \
+    darkgrey darkkhaki darkmagenta darkolivegreen darkorange darkorchid darkred darksalmon \
+    darkseagreen darkslateblue darkslategray darkslategrey darkturquoise darkviolet deeppink \
+    deepskyblue dimgray dimgrey dodgerblue firebrick floralwhite forestgreen fuchsia gainsboro \
+    ghostwhite gold goldenrod gray green greenyellow grey honeydew hotpink indianred indigo ivory \
+    khaki lavender lavenderblush lawngreen lemonchiffon lightblue lightcoral lightcyan \
+    lightgoldenrodyellow lightgray lightgreen lightgrey lightpink lightsalmon lightseagreen \
+    lightskyblue lightslategray lightslategrey lightsteelblue lightyellow lime limegreen linen \
+    magenta maroon mediumaquamarine mediumblue mediumorchid mediumpurple mediumseagreen \
+    mediumslateblue mediumspringgreen mediumturquoise mediumvioletred midnightblue mintcream \
+    mistyrose moccasin navajowhite navy oldlace olive olivedrab orange orangered orchid \
+    palegoldenrod palegreen paleturquoise palevioletred papayawhip peachpuff peru pink plum \
+    powderblue purple rebeccapurple red rosybrown royalblue saddlebrown salmon sandybrown \
+    seagreen seashell sienna silver skyblue slateblue slategray slategrey snow springgreen \
+    steelblue tan teal thistle tomato turquoise violet wheat white whitesmoke yellow yellowgreen \
+    transparent";
+
+/// Cheap request checks that run before any browser work, so malformed input
+/// fails fast with a 422 instead of timing out inside the page.
+pub fn validate_request(request: &RenderRequest) -> UnprocessableEntityResponse {
+    let mut errors = UnprocessableEntityResponse::new();
+
+    if let Some(template) = registry::get_template(&request.library.name)
+        && template.expects_object
+        && !request.data.is_object()
+    {
+        errors.add_error(
+            loc(&["data"]),
+            format!(
+                "library '{}' expects data to be a JSON object, got {}",
+                request.library.name,
+                json_kind(&request.data)
+            ),
+        );
+    } else if let Some(template) = registry::get_template(&request.library.name)
+        && let Some(schema) = &template.data_schema
+        && request.library.cdn_url.is_none()
+    {
+        // A custom cdn_url may load a build whose data differs from the template's
+        for (path, message) in data_schema::validate(&request.data, schema) {
+            let mut location = loc(&["data"]);
+            location.extend(path);
+            errors.add_error(location, message);
+        }
+    }
+
+    if let Some(theme) = &request.options.theme
+        && let Some(template) = registry::get_template(&request.library.name)
+        && !template.themes.contains_key(theme)
+    {
+        let mut available: Vec<&str> = template.themes.keys().map(String::as_str).collect();
+        available.sort();
+        errors.add_error(
+            loc(&["options", "theme"]),
+            format!(
+                "library '{}' has no theme '{}' (available: {})",
+                request.library.name,
+                theme,
+                if available.is_empty() {
+                    "none".to_string()
+                } else {
+                    available.join(", ")
+                }
+            ),
+        );
+    }
+
+    if let Some(template) = registry::get_template(&request.library.name)
+        && let Some((field, message)) = template::version_error(request, &template)
+    {
+        errors.add_error(loc(&["library", field]), message);
+    }
+
+    if let Some(template) = registry::get_template(&request.library.name) {
+        for plugin in request.library.plugins.iter().flatten() {
+            if template.plugins.contains_key(plugin) {
+                continue;
+            }
+            let mut available: Vec<&str> = template.plugins.keys().map(String::as_str).collect();
+            available.sort();
+            errors.add_error(
+                loc(&["library", "plugins"]),
+                format!(
+                    "library '{}' has no plugin '{}' (available: {})",
+                    request.library.name,
+                    plugin,
+                    if available.is_empty() {
+                        "none".to_string()
+                    } else {
+                        available.join(", ")
+                    }
+                ),
+            );
+        }
+    }
+
+    if let Some(key) = &request.options.output_key
+        && !storage::is_valid_key(key)
+    {
+        errors.add_error(
+            loc(&["options", "output_key"]),
+            "output_key must be a relative path without empty, '.' or '..' segments".to_string(),
+        );
+    }
+
+    if request.options.format.is_empty() {
+        errors.add_error(
+            loc(&["options", "format"]),
+            "format is required unless output_key ends in .png, .jpg, .jpeg, .webp, .pdf, .rgba or .svg"
+                .to_string(),
+        );
+    } else if let Some(key) = &request.options.output_key
+        && let Some(implied) = storage::format_for_key(key)
+        && implied != normalized_format(&request.options.format)
+    {
+        errors.add_error(
+            loc(&["options", "format"]),
+            format!(
+                "format '{}' does not match output_key extension ('{}')",
+                request.options.format, implied
+            ),
+        );
+    }
+
+    if request.options.format == "svg"
+        && let Some(template) = registry::get_template(&request.library.name)
+        && template.svg_selector.is_none()
+    {
+        errors.add_error(
+            loc(&["options", "format"]),
+            format!(
+                "library '{}' draws to a canvas and can't produce svg",
+                request.library.name
+            ),
+        );
+    }
+
+    if request.options.format == "svg" && !request.options.svg_renderer() {
+        errors.add_error(
+            loc(&["options", "renderer"]),
+            "svg output needs the svg renderer".to_string(),
+        );
+    }
+
+    if let Some(color) = &request.options.background_color {
+        if !is_css_color(color) {
+            errors.add_error(
+                loc(&["options", "background_color"]),
+                format!(
+                    "'{}' is not a CSS color (name, #hex, rgb() or hsl())",
+                    color
+                ),
+            );
+        } else if request.options.transparent.unwrap_or(false) {
+            errors.add_error(
+                loc(&["options", "background_color"]),
+                "background_color and transparent are mutually exclusive".to_string(),
+            );
+        }
+    }
+
+    if request.options.transparent.unwrap_or(false)
+        && matches!(request.options.format.as_str(), "jpeg" | "jpg")
+    {
+        errors.add_error(
+            loc(&["options", "transparent"]),
+            "transparent requires a format with alpha (png, webp, rgba), jpeg has none".to_string(),
+        );
+    }
+
+    if let Some(clip) = &request.options.clip {
+        if matches!(request.options.format.as_str(), "pdf" | "svg") {
+            errors.add_error(
+                loc(&["options", "clip"]),
+                format!("clip is not supported for {}", request.options.format),
+            );
+        } else if clip.x.saturating_add(clip.width) > request.options.pixel_width()
+            || clip.y.saturating_add(clip.height) > request.options.pixel_height()
+        {
+            errors.add_error(
+                loc(&["options", "clip"]),
+                format!(
+                    "clip {}x{} at ({}, {}) exceeds the {}x{} page",
+                    clip.width,
+                    clip.height,
+                    clip.x,
+                    clip.y,
+                    request.options.pixel_width(),
+                    request.options.pixel_height()
+                ),
+            );
+        }
+    }
+
+    if let Some(pdf_options) = &request.options.pdf_options {
+        let (width, height) = pdf_options.content_size();
+        if width <= 0.0 || height <= 0.0 {
+            errors.add_error(
+                loc(&["options", "pdf_options"]),
+                format!(
+                    "margins leave no printable area ({:.2}in x {:.2}in)",
+                    width, height
+                ),
+            );
+        }
+    }
+
+    if request.options.bit_depth == Some(16) && request.options.format != "png" {
+        errors.add_error(
+            loc(&["options", "bit_depth"]),
+            format!(
+                "bit_depth 16 requires png format, got {}",
+                request.options.format
+            ),
+        );
+    }
+
+    if let Some(delay) = request.options.interaction_delay_ms
+        && delay > MAX_INTERACTION_DELAY_MS
+    {
+        errors.add_error(
+            loc(&["options", "interaction_delay_ms"]),
+            format!(
+                "interaction_delay_ms must be at most {}, got {}",
+                MAX_INTERACTION_DELAY_MS, delay
+            ),
+        );
+    }
+
+    if let Some(interactions) = &request.options.interactions {
+        if !interactions.is_empty() && !request.options.capture_pair.unwrap_or(false) {
+            errors.add_error(
+                loc(&["options", "interactions"]),
+                "interactions are only performed with capture_pair".to_string(),
+            );
+        }
+        for (index, interaction) in interactions.iter().enumerate() {
+            if interaction.selector.is_none()
+                && (interaction.x.is_none() || interaction.y.is_none())
+            {
+                errors.add_error(
+                    loc(&["options", "interactions", &index.to_string()]),
+                    "either selector or both x and y are required".to_string(),
+                );
+            }
+        }
+    }
+
+    for (name, value) in request.options.inject_fetch_headers.iter().flatten() {
+        let valid_name = !name.is_empty()
+            && name
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b));
+        if !valid_name || value.contains(['\r', '\n']) {
+            errors.add_error(
+                loc(&["options", "inject_fetch_headers", name]),
+                "header names must be HTTP tokens and values must not contain line breaks"
+                    .to_string(),
+            );
+        }
+    }
+
+    for (index, font) in request.options.fonts.iter().flatten().enumerate() {
+        let index = index.to_string();
+        if !is_font_family(&font.family) {
+            errors.add_error(
+                loc(&["options", "fonts", &index, "family"]),
+                "family must be 1-100 letters, digits, spaces, '-' or '_'".to_string(),
+            );
+        }
+        match (&font.url, &font.stylesheet_url) {
+            (Some(url), None) | (None, Some(url)) => {
+                if let Err(e) = template::font_url(url) {
+                    errors.add_error(loc(&["options", "fonts", &index]), e.to_string());
+                }
+            }
+            _ => errors.add_error(
+                loc(&["options", "fonts", &index]),
+                "exactly one of url or stylesheet_url is required".to_string(),
+            ),
+        }
+    }
+
+    let options = &request.options;
+    validate_dimension(
+        &mut errors,
+        ("width", options.width),
+        ("width_mm", options.width_mm),
+        options.pixel_width(),
+    );
+    validate_dimension(
+        &mut errors,
+        ("height", options.height),
+        ("height_mm", options.height_mm),
+        options.pixel_height(),
+    );
+
+    errors
+}
+
+/// Exactly one of the pixel or physical size must be set for each axis, and a
+/// physical size must convert to a pixel size the API would accept directly.
+fn validate_dimension(
+    errors: &mut UnprocessableEntityResponse,
+    (pixel_name, pixels): (&str, Option<u32>),
+    (physical_name, physical): (&str, Option<f64>),
+    resolved: u32,
+) {
+    match (pixels, physical) {
+        (Some(_), Some(_)) => errors.add_error(
+            loc(&["options", physical_name]),
+            format!(
+                "{} and {} are mutually exclusive",
+                pixel_name, physical_name
+            ),
+        ),
+        (None, None) => errors.add_error(
+            loc(&["options", pixel_name]),
+            format!("either {} or {} is required", pixel_name, physical_name),
+        ),
+        (None, Some(_)) if !PIXEL_RANGE.contains(&resolved) => errors.add_error(
+            loc(&["options", physical_name]),
+            format!(
+                "{} resolves to {}px at the requested ppi, must be between {} and {}",
+                physical_name,
+                resolved,
+                PIXEL_RANGE.start(),
+                PIXEL_RANGE.end()
+            ),
+        ),
+        _ => {}
+    }
+}
+
+/// Accepts the color syntaxes `background_color` documents. Function
+/// arguments are limited to numbers, units and separators, so the value can't
+/// break out of the CSS rule it is substituted into.
+pub(crate) fn is_css_color(color: &str) -> bool {
+    let color = color.trim().to_ascii_lowercase();
+
+    if let Some(hex) = color.strip_prefix('#') {
+        return matches!(hex.len(), 3 | 4 | 6 | 8) && hex.bytes().all(|b| b.is_ascii_hexdigit());
+    }
+
+    for function in ["rgba(", "rgb(", "hsla(", "hsl("] {
+        if let Some(arguments) = color
+            .strip_prefix(function)
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            return arguments.bytes().any(|b| b.is_ascii_digit())
+                && arguments
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b" .,%/+-".contains(&b));
+        }
+    }
+
+    CSS_COLOR_NAMES.split_whitespace().any(|name| name == color)
+}
+
+/// Family names are quoted into CSS and script, so they are kept to a
+/// character set that needs no escaping in either.
+pub(crate) fn is_font_family(family: &str) -> bool {
+    (1..=100).contains(&family.len())
+        && family
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b" -_".contains(&b))
+}
+
+fn normalized_format(format: &str) -> &str {
+    if format == "jpg" { "jpeg" } else { format }
+}
+
+fn loc(path: &[&str]) -> Vec<String> {
+    std::iter::once("body")
+        .chain(path.iter().copied())
+        .map(String::from)
+        .collect()
+}
+
+pub(crate) fn json_kind(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Bool(_) => "a boolean",
+        JsonValue::Number(_) => "a number",
+        JsonValue::String(_) => "a string",
+        JsonValue::Array(_) => "an array",
+        JsonValue::Object(_) => "an object",
+    }
+}
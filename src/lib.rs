@@ -6,34 +6,56 @@ use poem::{
 };
 use poem_openapi::OpenApiService;
 
+use auth::ApiKeyAuth;
+use body_limit::BodyLimit;
 use core::renderer::RenderingEngine;
+use core::storage::ObjectStorage;
+use request_id::RequestIdTracing;
 use settings::Config;
 
-use crate::routes::render::ApiRender;
+use crate::routes::{admin::ApiAdmin, render::ApiRender};
 
+pub mod auth;
+pub mod body_limit;
 pub mod core;
+pub mod listener;
+pub mod request_id;
 pub mod routes;
 pub mod schemas;
 pub mod settings;
 
+const DEFAULT_MAX_REQUEST_BODY_BYTES: usize = 10 * 1024 * 1024;
+
 pub struct AppState {
     pub engine: Arc<RenderingEngine>,
+    /// Object storage sink for `output_key`, if configured
+    pub storage: Option<ObjectStorage>,
 }
 
 pub fn init_openapi_route(
     app_state: Arc<AppState>,
     config: &Config,
-) -> CorsEndpoint<AddDataEndpoint<Route, Arc<AppState>>> {
+) -> CorsEndpoint<AddDataEndpoint<AddDataEndpoint<Route, Arc<AppState>>, Config>> {
     let prefix = config.prefix.clone().unwrap_or("/".to_string());
-    let openapi_route =
-        OpenApiService::new(ApiRender, "Renderer Engine API", "1.0").server(prefix.clone());
+    let openapi_route = OpenApiService::new((ApiRender, ApiAdmin), "Renderer Engine API", "1.0")
+        .server(prefix.clone());
 
     let openapi_json_endpoint = openapi_route.spec_endpoint();
     let ui = openapi_route.swagger_ui();
+    let max_body_bytes = config
+        .max_request_body_bytes
+        .unwrap_or(DEFAULT_MAX_REQUEST_BODY_BYTES);
     Route::new()
-        .nest(prefix, openapi_route)
+        .nest(
+            prefix,
+            openapi_route
+                .with(BodyLimit::new(max_body_bytes))
+                .with(ApiKeyAuth::new(config.api_key.clone()))
+                .with(RequestIdTracing),
+        )
         .nest("/docs", ui)
         .at("openapi.json", openapi_json_endpoint)
         .with(AddData::new(app_state))
+        .with(AddData::new(config.clone()))
         .with(Cors::new())
 }
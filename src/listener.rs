@@ -0,0 +1,137 @@
+use anyhow::{Result, anyhow};
+use poem::http::uri::Scheme;
+use poem::listener::{Acceptor, TcpAcceptor};
+use poem::web::{LocalAddr, RemoteAddr};
+use std::io;
+use std::net::ToSocketAddrs;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, ReadBuf};
+use tokio::net::{TcpSocket, TcpStream};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::settings::Config;
+
+const DEFAULT_ACCEPT_BACKLOG: u32 = 1024;
+const REJECT_WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+/// Written to connections over `max_connections` before they are closed
+const OVERLOADED_RESPONSE: &[u8] =
+    b"HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+
+/// TCP acceptor that keeps at most `max_connections` connections open.
+/// Connections over the limit get a bare 503 and are closed right away
+/// instead of queueing behind slow renders and holding file descriptors.
+pub struct LimitedAcceptor {
+    inner: TcpAcceptor,
+    connections: Option<Arc<Semaphore>>,
+}
+
+impl LimitedAcceptor {
+    /// Bind `host:port` with the configured `accept_backlog` and
+    /// `max_connections`. Must be called within a Tokio runtime.
+    pub fn bind(config: &Config) -> Result<Self> {
+        let addr = (config.host.as_str(), config.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| anyhow!("{}:{} did not resolve", config.host, config.port))?;
+
+        let socket = if addr.is_ipv4() {
+            TcpSocket::new_v4()?
+        } else {
+            TcpSocket::new_v6()?
+        };
+        socket.set_reuseaddr(true)?;
+        socket.bind(addr)?;
+        let listener = socket.listen(config.accept_backlog.unwrap_or(DEFAULT_ACCEPT_BACKLOG))?;
+
+        Ok(Self {
+            inner: TcpAcceptor::from_tokio(listener)?,
+            connections: config
+                .max_connections
+                .map(|max| Arc::new(Semaphore::new(max))),
+        })
+    }
+}
+
+impl Acceptor for LimitedAcceptor {
+    type Io = LimitedStream;
+
+    fn local_addr(&self) -> Vec<LocalAddr> {
+        self.inner.local_addr()
+    }
+
+    async fn accept(&mut self) -> io::Result<(Self::Io, LocalAddr, RemoteAddr, Scheme)> {
+        loop {
+            let (stream, local_addr, remote_addr, scheme) = self.inner.accept().await?;
+
+            let permit = match &self.connections {
+                Some(connections) => match connections.clone().try_acquire_owned() {
+                    Ok(permit) => Some(permit),
+                    Err(_) => {
+                        tracing::warn!(
+                            "Connection limit reached, rejecting connection from {}",
+                            remote_addr
+                        );
+                        // Best effort, the socket is closed either way
+                        tokio::spawn(async move {
+                            let mut stream = stream;
+                            let _ = tokio::time::timeout(
+                                REJECT_WRITE_TIMEOUT,
+                                stream.write_all(OVERLOADED_RESPONSE),
+                            )
+                            .await;
+                        });
+                        continue;
+                    }
+                },
+                None => None,
+            };
+
+            return Ok((
+                LimitedStream {
+                    stream,
+                    _permit: permit,
+                },
+                local_addr,
+                remote_addr,
+                scheme,
+            ));
+        }
+    }
+}
+
+/// Accepted connection holding its slot until dropped.
+pub struct LimitedStream {
+    stream: TcpStream,
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl AsyncRead for LimitedStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for LimitedStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_shutdown(cx)
+    }
+}
@@ -0,0 +1,81 @@
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use poem::http::HeaderValue;
+use poem::{Endpoint, IntoResponse, Middleware, Request, Response, Result};
+use tracing::Instrument;
+
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Longest caller-supplied ID that is kept; longer ones are replaced.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+static REQUESTS: AtomicU64 = AtomicU64::new(0);
+
+/// ID correlating a request with its log lines, available to handlers as
+/// `Data<&RequestId>`.
+#[derive(Clone, Debug)]
+pub struct RequestId(pub String);
+
+/// Tags each request with the caller's `X-Request-Id`, or a generated one,
+/// and runs it in a `request` span carrying that ID. The ID is echoed in the
+/// response's `X-Request-Id` header.
+pub struct RequestIdTracing;
+
+impl<E: Endpoint> Middleware<E> for RequestIdTracing {
+    type Output = RequestIdTracingEndpoint<E>;
+
+    fn transform(&self, inner: E) -> Self::Output {
+        RequestIdTracingEndpoint { inner }
+    }
+}
+
+pub struct RequestIdTracingEndpoint<E> {
+    inner: E,
+}
+
+impl<E: Endpoint> Endpoint for RequestIdTracingEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, mut req: Request) -> Result<Self::Output> {
+        let id = req
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .filter(|id| is_valid(id))
+            .map(str::to_string)
+            .unwrap_or_else(generate);
+        req.set_data(RequestId(id.clone()));
+
+        let span = tracing::info_span!(
+            "request",
+            request_id = %id,
+            method = %req.method(),
+            path = %req.uri().path()
+        );
+        let mut response = match self.inner.call(req).instrument(span).await {
+            Ok(output) => output.into_response(),
+            Err(e) => e.into_response(),
+        };
+        let header = HeaderValue::from_str(&id).expect("request IDs are visible ASCII");
+        response.headers_mut().insert(REQUEST_ID_HEADER, header);
+        Ok(response)
+    }
+}
+
+/// Caller IDs end up in logs, so only short, printable ones are trusted.
+fn is_valid(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id.bytes().all(|byte| byte.is_ascii_graphic())
+}
+
+fn generate() -> String {
+    let sequence = REQUESTS.fetch_add(1, Ordering::Relaxed);
+    format!(
+        "{:016x}{:016x}",
+        RandomState::new().hash_one(sequence),
+        RandomState::new().hash_one(sequence)
+    )
+}
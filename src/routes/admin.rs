@@ -0,0 +1,84 @@
+use std::path::PathBuf;
+
+use poem::web::Data;
+use poem_openapi::{OpenApi, Tags, param::Header, payload::Json};
+
+use crate::{
+    auth::keys_match,
+    core::registry,
+    schemas::{
+        admin::{RegistryReloadResponse, RegistryReloadResult},
+        common::{ForbiddenResponse, UnauthorizedResponse, UnprocessableEntityResponse},
+    },
+    settings::Config,
+};
+
+#[derive(Tags)]
+enum ApiAdminTags {
+    Admin,
+}
+
+pub struct ApiAdmin;
+
+#[OpenApi()]
+impl ApiAdmin {
+    /// Reload Library Registry
+    ///
+    /// Re-read the templates file at `library_registry_path` and swap it into
+    /// the registry. Requires the `X-Admin-Key` header to match
+    /// `admin_api_key`. If any template is invalid nothing is changed.
+    #[oai(
+        path = "/admin/registry/reload",
+        method = "post",
+        tag = "ApiAdminTags::Admin"
+    )]
+    async fn reload_registry(
+        &self,
+        #[oai(name = "X-Admin-Key")] admin_key: Header<Option<String>>,
+        config: Data<&Config>,
+    ) -> RegistryReloadResponse {
+        let Some(expected_key) = &config.admin_api_key else {
+            return RegistryReloadResponse::Forbidden(Json(ForbiddenResponse {
+                message: "admin API is disabled on this server (admin_api_key)".to_string(),
+            }));
+        };
+        let provided = admin_key.0.as_deref().map(str::as_bytes);
+        if !provided.is_some_and(|key| keys_match(key, expected_key.as_bytes())) {
+            return RegistryReloadResponse::Unauthorized(Json(UnauthorizedResponse::default()));
+        }
+
+        let mut errors = UnprocessableEntityResponse::new();
+        let loc = vec!["config".to_string(), "library_registry_path".to_string()];
+        let Some(path) = &config.library_registry_path else {
+            errors.add_error(loc, "no library registry file configured".to_string());
+            return RegistryReloadResponse::UnprocessableEntity(Json(errors));
+        };
+
+        // Reading and validating the file is blocking IO
+        let file = PathBuf::from(path);
+        let reloaded = tokio::task::spawn_blocking(move || registry::reload(&file))
+            .await
+            .unwrap_or_else(|e| Err(anyhow::anyhow!("registry reload task failed: {}", e)));
+        match reloaded {
+            Ok(changes) => {
+                tracing::info!(
+                    "Reloaded library registry from {}: added {:?}, updated {:?}, removed {:?}",
+                    path,
+                    changes.added,
+                    changes.updated,
+                    changes.removed
+                );
+                RegistryReloadResponse::Ok(Json(RegistryReloadResult {
+                    added: changes.added,
+                    updated: changes.updated,
+                    removed: changes.removed,
+                }))
+            }
+            Err(e) => {
+                tracing::warn!("Library registry reload failed: {}", e);
+                errors.add_error(loc, e.to_string());
+                RegistryReloadResponse::UnprocessableEntity(Json(errors))
+            }
+        }
+    }
+}
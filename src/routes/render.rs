@@ -1,20 +1,56 @@
-use std::sync::Arc;
+use std::{collections::HashSet, io::Write, sync::Arc};
 
-use poem::web::Data;
+use poem::{Body, web::Data};
 use poem_openapi::{
     OpenApi, Tags,
-    payload::{Attachment, Json},
+    param::{Path, Query},
+    payload::{Attachment, Binary, Html, Json, PlainText},
 };
 
 use crate::{
     AppState,
-    core::registry::LIBRARY_REGISTRY,
+    core::{
+        error_card,
+        registry::{self, LIBRARY_REGISTRY},
+        renderer::{
+            self, BrowserUnavailable, CaptureTimeout, ConsoleLog, RenderError, RenderingEngine,
+        },
+        storage::{self, ObjectStorage, UploadFailed},
+        template, validation,
+    },
+    request_id::RequestId,
     schemas::{
-        common::InternalServerErrorResponse,
-        render::{LibraryConfig, ListLibrariesResponse, RenderRequest, RenderResponse},
+        common::{
+            BadGatewayResponse, BadRequestResponse, GatewayTimeoutResponse,
+            InternalServerErrorResponse, NotFoundResponse, PayloadTooLargeResponse,
+            RequestTimeoutResponse, ServiceUnavailableResponse, UnprocessableEntityResponse,
+        },
+        render::{
+            BatchItemResult, BatchRenderRequest, BatchRenderResponse, HealthResponse,
+            LibraryDetails, LibraryDetailsResponse, LibraryInfo, ListLibrariesResponse,
+            MetricsResponse, RenderHtmlResponse, RenderOptions, RenderRequest, RenderResponse,
+            StoredResponse,
+        },
     },
+    settings::Config,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    task::{JoinError, JoinSet},
+};
+use tracing::Instrument;
+use zip::{
+    CompressionMethod, ZipWriter,
+    result::ZipResult,
+    write::{SimpleFileOptions, StreamWriter},
 };
 
+const FAST_RENDER_DELAY_MS: u64 = 50;
+const QUALITY_RENDER_DELAY_MS: u64 = 500;
+const DEFAULT_MAX_BATCH_SIZE: usize = 50;
+const ZIP_STREAM_BUFFER: usize = 64 * 1024;
+const RENDER_STREAM_BUFFER: usize = 64 * 1024;
+
 #[derive(Tags)]
 enum ApiRenderTags {
     Render,
@@ -49,56 +85,308 @@ impl ApiRender {
     ///   }
     /// }
     /// ```
+    ///
+    /// The same bar chart with ApexCharts, whose `data` is the
+    /// `new ApexCharts(el, options)` options:
+    /// ```json
+    /// {
+    ///   "library": { "name": "apexcharts", "version": "3.54.1" },
+    ///   "data": {
+    ///     "chart": { "type": "bar" },
+    ///     "series": [{ "name": "Sales", "data": [120, 200, 150] }],
+    ///     "xaxis": { "categories": ["Mon", "Tue", "Wed"] }
+    ///   },
+    ///   "options": { "width": 800, "height": 600, "format": "png" }
+    /// }
+    /// ```
+    ///
+    /// Set `options.capture_pair` together with `options.interactions` to get
+    /// a `before`/`after` pair of base64 images from a single page load, e.g.
+    /// the default chart next to its hovered tooltip state.
     #[oai(path = "/render", method = "post", tag = "ApiRenderTags::Render")]
     async fn render(
         &self,
-        Json(json): Json<RenderRequest>,
+        Json(mut json): Json<RenderRequest>,
         state: Data<&Arc<AppState>>,
+        config: Data<&Config>,
+        Data(request_id): Data<&RequestId>,
     ) -> RenderResponse {
+        apply_request_defaults(&mut json, &config);
+
         tracing::info!(
             "Rendering: library={}, size={}x{}",
             json.library.name,
-            json.options.width,
-            json.options.height
+            json.options.pixel_width(),
+            json.options.pixel_height()
         );
 
-        let return_base64 = json.options.return_base64.unwrap_or(false);
-
-        if return_base64 {
-            let result = match state.engine.render_base64(json).await {
-                Ok(res) => res,
-                Err(e) => {
-                    tracing::error!("Render error: {}", e);
-                    return RenderResponse::InternalServerError(Json(
-                        InternalServerErrorResponse::new(
-                            "route.render",
-                            "render",
-                            "Rendering failed",
-                            &e.to_string(),
-                        ),
-                    ));
-                }
-            };
+        if let Some(message) = unsupported_library(&json) {
+            return RenderResponse::BadRequest(Json(BadRequestResponse { message }));
+        }
+
+        let errors = validation::validate_request(&json);
+        if errors.is_has_error() {
+            return RenderResponse::UnprocessableEntity(Json(errors));
+        }
 
-            RenderResponse::Base64(Json(result))
+        if let Some(message) = disabled_option(&json, &state, &config) {
+            return RenderResponse::BadRequest(Json(BadRequestResponse { message }));
+        }
+
+        let error_card = json
+            .options
+            .error_as_image
+            .unwrap_or(false)
+            .then(|| (json.options.pixel_width(), json.options.pixel_height()));
+
+        let engine = &state.engine;
+        let result = if json.options.sprite_selectors.is_some() {
+            engine
+                .render_sprite(json)
+                .await
+                .map(|res| RenderResponse::Sprite(Json(res)))
+        } else if json.options.capture_pair.unwrap_or(false) {
+            engine
+                .render_pair(json)
+                .await
+                .map(|res| RenderResponse::Pair(Json(res)))
+        } else if let (Some(key), Some(storage)) = (json.options.output_key.clone(), &state.storage)
+        {
+            render_to_storage(engine, storage, key, json)
+                .await
+                .map(|res| RenderResponse::Stored(Json(res)))
+        } else if json.options.return_base64.unwrap_or(false) {
+            engine
+                .render_base64(json)
+                .await
+                .map(|res| RenderResponse::Base64(Json(res)))
+        } else if json.options.format == "webp" {
+            engine
+                .render(json)
+                .await
+                .map(|webp| RenderResponse::Webp(Binary(webp)))
+        } else if json.options.format == "svg" {
+            engine
+                .render(json)
+                .await
+                .map(|svg| RenderResponse::Svg(Binary(svg)))
+        } else if json.options.format == "rgba" {
+            engine
+                .render_rgba(json)
+                .await
+                .map(|raw| RenderResponse::Raw(Binary(raw.pixels), raw.width, raw.height))
         } else {
-            let result = match state.engine.render(json).await {
-                Ok(res) => res,
-                Err(e) => {
-                    tracing::error!("Render error: {}", e);
-                    return RenderResponse::InternalServerError(Json(
-                        InternalServerErrorResponse::new(
-                            "route.render",
-                            "render",
-                            "Rendering failed",
-                            &e.to_string(),
-                        ),
-                    ));
+            render_streamed(engine, json)
+                .await
+                .map(|body| RenderResponse::Binary(Attachment::new(body)))
+        };
+
+        result.unwrap_or_else(|e| {
+            if let Some((width, height)) = error_card {
+                tracing::error!("Render error, returning error card: {}", e);
+                match error_card::render(&e.to_string(), width, height) {
+                    Ok(png) => return RenderResponse::ErrorImage(Binary(png), true),
+                    Err(card_error) => tracing::error!("Error card failed: {}", card_error),
                 }
-            };
+            }
+            if let Some(upload) = e.downcast_ref::<UploadFailed>() {
+                tracing::error!("Render upload error: {}", upload);
+                return RenderResponse::BadGateway(Json(BadGatewayResponse {
+                    message: upload.to_string(),
+                }));
+            }
+            if let Some(timeout) = e.downcast_ref::<CaptureTimeout>() {
+                tracing::error!("Render error: {}", timeout);
+                return RenderResponse::GatewayTimeout(Json(GatewayTimeoutResponse {
+                    message: timeout.to_string(),
+                }));
+            }
+            if let Some(unavailable) = e.downcast_ref::<BrowserUnavailable>() {
+                tracing::warn!("Render rejected: {}", unavailable);
+                return RenderResponse::ServiceUnavailable(Json(ServiceUnavailableResponse {
+                    message: unavailable.to_string(),
+                }));
+            }
+            if let Some(too_large) = e.downcast_ref::<template::DataTooLarge>() {
+                tracing::warn!("Render rejected: {}", too_large);
+                return RenderResponse::PayloadTooLarge(Json(PayloadTooLargeResponse {
+                    message: too_large.to_string(),
+                }));
+            }
+            match e.downcast_ref::<RenderError>() {
+                Some(timeout @ RenderError::Timeout { .. }) => {
+                    tracing::warn!("Render error: {}", timeout);
+                    return RenderResponse::RequestTimeout(Json(RequestTimeoutResponse {
+                        message: with_console_log(timeout.to_string(), &e),
+                    }));
+                }
+                Some(unsupported @ RenderError::UnsupportedLibrary(_)) => {
+                    tracing::warn!("Render rejected: {}", unsupported);
+                    return RenderResponse::BadRequest(Json(BadRequestResponse {
+                        message: unsupported.to_string(),
+                    }));
+                }
+                Some(initialization @ RenderError::Initialization(_)) => {
+                    tracing::warn!("Render error: {}", initialization);
+                    let mut errors = UnprocessableEntityResponse::new();
+                    errors.add_error(
+                        vec!["body".to_string(), "data".to_string()],
+                        with_console_log(initialization.to_string(), &e),
+                    );
+                    return RenderResponse::UnprocessableEntity(Json(errors));
+                }
+                Some(RenderError::BrowserCrashed(_)) | None => {}
+            }
+
+            tracing::error!("Render error: {}", e);
+            RenderResponse::InternalServerError(Json(
+                InternalServerErrorResponse::new(
+                    "route.render",
+                    "render",
+                    "Rendering failed",
+                    &with_console_log(e.to_string(), &e),
+                )
+                .with_code(renderer::error_code(&e), e.to_string())
+                .with_request_id(&request_id.0),
+            ))
+        })
+    }
+
+    /// Render HTML
+    ///
+    /// Return the page `/render` would load for the request, without
+    /// launching a browser, to open and debug in your own browser. Requires
+    /// `allow_return_html` in the server config.
+    #[oai(path = "/render/html", method = "post", tag = "ApiRenderTags::Render")]
+    async fn render_html(
+        &self,
+        Json(mut json): Json<RenderRequest>,
+        state: Data<&Arc<AppState>>,
+        config: Data<&Config>,
+    ) -> RenderHtmlResponse {
+        if !config.allow_return_html {
+            return RenderHtmlResponse::BadRequest(Json(BadRequestResponse {
+                message: "/render/html is disabled on this server (allow_return_html)".to_string(),
+            }));
+        }
+
+        apply_request_defaults(&mut json, &config);
+
+        if let Some(message) = unsupported_library(&json) {
+            return RenderHtmlResponse::BadRequest(Json(BadRequestResponse { message }));
+        }
+
+        let errors = validation::validate_request(&json);
+        if errors.is_has_error() {
+            return RenderHtmlResponse::UnprocessableEntity(Json(errors));
+        }
+
+        if let Some(message) = disabled_option(&json, &state, &config) {
+            return RenderHtmlResponse::BadRequest(Json(BadRequestResponse { message }));
+        }
+
+        match state.engine.page_html(&json) {
+            Ok(html) => RenderHtmlResponse::Ok(Html(html)),
+            Err(e) if e.is::<template::DataTooLarge>() => {
+                RenderHtmlResponse::PayloadTooLarge(Json(PayloadTooLargeResponse {
+                    message: e.to_string(),
+                }))
+            }
+            Err(e) => RenderHtmlResponse::BadRequest(Json(BadRequestResponse {
+                message: e.to_string(),
+            })),
+        }
+    }
+
+    /// Render Batch
+    ///
+    /// Render several charts in one request. Each item is a full render
+    /// request; a failing item reports its error without failing the others.
+    /// Results are base64 encoded JSON by default, or a ZIP archive streamed
+    /// as items complete with `output: "zip"`. More than `max_batch_size`
+    /// items are rejected with 422.
+    #[oai(path = "/render/batch", method = "post", tag = "ApiRenderTags::Render")]
+    async fn render_batch(
+        &self,
+        json: Json<BatchRenderRequest>,
+        state: Data<&Arc<AppState>>,
+        config: Data<&Config>,
+    ) -> BatchRenderResponse {
+        let BatchRenderRequest { items, output } = json.0;
+        let max_batch_size = config.max_batch_size.unwrap_or(DEFAULT_MAX_BATCH_SIZE);
+
+        // Checked before anything is spawned so an oversized batch costs nothing
+        if items.is_empty() || items.len() > max_batch_size {
+            let mut errors = UnprocessableEntityResponse::new();
+            errors.add_error(
+                vec!["body".to_string(), "items".to_string()],
+                format!(
+                    "Batch must contain between 1 and {} items, got {}",
+                    max_batch_size,
+                    items.len()
+                ),
+            );
+            return BatchRenderResponse::UnprocessableEntity(Json(errors));
+        }
+
+        let items: Vec<PreparedItem> = items
+            .into_iter()
+            .enumerate()
+            .map(|(index, item)| {
+                let mut request = item.request;
+                apply_request_defaults(&mut request, &config);
+                let name = item.name.unwrap_or_else(|| {
+                    format!("{}.{}", index, file_extension(&request.options.format))
+                });
+                let rejected = batch_item_error(&request, &state, &config);
+                PreparedItem {
+                    index,
+                    name,
+                    request,
+                    rejected,
+                }
+            })
+            .collect();
 
-            RenderResponse::Binary(Attachment::new(result))
+        let zip = output.as_deref() == Some("zip");
+        if zip {
+            let errors = validate_entry_names(&items);
+            if errors.is_has_error() {
+                return BatchRenderResponse::UnprocessableEntity(Json(errors));
+            }
+        }
+
+        tracing::info!("Rendering batch of {} charts", items.len());
+
+        if zip {
+            let tasks = spawn_batch(&state.engine, items, |engine, request| async move {
+                engine.render(request).await
+            });
+            let body = stream_zip(tasks);
+            return BatchRenderResponse::Zip(Attachment::new(body).filename("charts.zip"));
+        }
+
+        let mut tasks = spawn_batch(&state.engine, items, |engine, request| async move {
+            engine.render_base64(request).await
+        });
+        let mut results = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            let Some((index, _, outcome)) = joined_item(joined) else {
+                continue;
+            };
+            let (result, error) = match outcome {
+                Ok(res) => (Some(res), None),
+                Err(error) => (None, Some(error)),
+            };
+            results.push(BatchItemResult {
+                index,
+                result,
+                error,
+            });
         }
+        results.sort_by_key(|item| item.index);
+
+        BatchRenderResponse::Ok(Json(results))
     }
 
     /// List Supported Libraries
@@ -107,33 +395,523 @@ impl ApiRender {
     #[oai(path = "/libraries", method = "get")]
     async fn list_libraries(&self) -> ListLibrariesResponse {
         let libraries = LIBRARY_REGISTRY
+            .read()
             .iter()
-            .map(|(name, template)| LibraryConfig {
-                name: name.clone(),
-                version: "latest".to_string(),
-                cdn_url: Some(template.cdn_url.clone()),
+            .map(|(name, template)| {
+                let mut plugins: Vec<String> = template.plugins.keys().cloned().collect();
+                plugins.sort();
+                LibraryInfo {
+                    name: name.clone(),
+                    aliases: registry::aliases(name),
+                    version: template.default_version.clone(),
+                    cdn_url: Some(template.cdn_url.clone()).filter(|url| !url.is_empty()),
+                    plugins,
+                }
             })
             .collect();
 
         ListLibrariesResponse::Ok(Json(libraries))
     }
 
+    /// Library Details
+    ///
+    /// Get one library's template: its default CDN URL, with the `{version}`
+    /// placeholder kept, and the element the renderer waits for. Aliases
+    /// resolve like `library.name` does.
+    #[oai(path = "/libraries/:name", method = "get")]
+    async fn library_details(&self, Path(name): Path<String>) -> LibraryDetailsResponse {
+        let Some((name, template)) = registry::canonical_name(&name)
+            .and_then(|name| registry::get_template(&name).map(|template| (name, template)))
+        else {
+            return LibraryDetailsResponse::NotFound(Json(NotFoundResponse {
+                message: format!("Library '{}' is not registered", name),
+            }));
+        };
+
+        let mut themes: Vec<String> = template.themes.into_keys().collect();
+        themes.sort();
+        let mut plugins: Vec<String> = template.plugins.into_keys().collect();
+        plugins.sort();
+
+        LibraryDetailsResponse::Ok(Json(LibraryDetails {
+            aliases: registry::aliases(&name),
+            name,
+            cdn_url: Some(template.cdn_url).filter(|url| !url.is_empty()),
+            default_version: template.default_version,
+            wait_selector: template.wait_selector,
+            themes,
+            plugins,
+            data_schema: template.data_schema,
+        }))
+    }
+
+    /// Health
+    ///
+    /// Pool and render counters, cheap enough for liveness probes. With
+    /// `deep=true` a small chart is also rendered end to end, and a failed
+    /// render reports `unhealthy` with a 503.
     #[oai(path = "/health", method = "get")]
-    async fn health(&self, state: Data<&Arc<AppState>>) -> Json<serde_json::Value> {
+    async fn health(
+        &self,
+        state: Data<&Arc<AppState>>,
+        deep: Query<Option<bool>>,
+    ) -> HealthResponse {
         let status = state.engine.health_check();
+        let canary = match deep.0 {
+            Some(true) => Some(state.engine.deep_health_check().await),
+            _ => None,
+        };
+
+        let browser_pools: serde_json::Map<String, serde_json::Value> = status
+            .pools
+            .iter()
+            .map(|pool| {
+                (
+                    pool.raster_engine.to_string(),
+                    serde_json::json!({
+                        "size": pool.size,
+                        "idle": pool.available,
+                        "capacity": pool.capacity,
+                        "recent_scaling": pool
+                            .recent_scale_events
+                            .iter()
+                            .map(|event| serde_json::json!({
+                                "direction": event.direction.name(),
+                                "from": event.from,
+                                "to": event.to,
+                                "seconds_ago": event.seconds_ago,
+                            }))
+                            .collect::<Vec<_>>(),
+                    }),
+                )
+            })
+            .collect();
 
-        Json(serde_json::json!({
-            "status": "healthy",
+        let unhealthy = canary.as_ref().is_some_and(|canary| !canary.passed());
+        let mut body = serde_json::json!({
+            "status": if status.warming {
+                "warming"
+            } else if unhealthy {
+                "unhealthy"
+            } else {
+                "healthy"
+            },
             "browser_pool": {
                 "available": status.pool_size,
                 "capacity": status.total_capacity,
                 "utilization_pct": ((status.total_capacity - status.pool_size) as f64 / status.total_capacity as f64 * 100.0)
             },
+            "browser_pools": browser_pools,
+            "renders": {
+                "served": status.renders_served,
+                "failed": status.renders_failed,
+                "slow": status.slow_renders
+            },
+            "cache": {
+                "hits": status.cache_hits,
+                "misses": status.cache_misses,
+                "entries": status.cache_entries
+            },
             "render_slots": {
                 "available": status.available_permits,
                 "capacity": status.max_concurrent,
                 "utilization_pct": ((status.max_concurrent - status.available_permits) as f64 / status.max_concurrent as f64 * 100.0)
             }
-        }))
+        });
+
+        if let Some(canary) = canary {
+            body["canary"] = serde_json::json!({
+                "passed": canary.passed(),
+                "latency_ms": canary.latency.as_millis() as u64,
+                "error": canary.error,
+            });
+        }
+
+        if status.warming || unhealthy {
+            HealthResponse::ServiceUnavailable(Json(body))
+        } else {
+            HealthResponse::Ok(Json(body))
+        }
+    }
+
+    /// Liveness
+    ///
+    /// Always 200 while the process is serving requests, for liveness
+    /// probes. Never touches the browser pools.
+    #[oai(path = "/health/live", method = "get")]
+    async fn health_live(&self) -> HealthResponse {
+        HealthResponse::Ok(Json(serde_json::json!({ "status": "alive" })))
+    }
+
+    /// Readiness
+    ///
+    /// 200 when the default pool has a browser and a render slot is free,
+    /// otherwise 503 with the reason, for readiness probes. Also 503 while
+    /// warming up. An empty lazy pool counts as ready until a launch fails.
+    #[oai(path = "/health/ready", method = "get")]
+    async fn health_ready(&self, state: Data<&Arc<AppState>>) -> HealthResponse {
+        let status = state.engine.health_check();
+        let launches_on_demand = status.lazy_pool && !status.launch_backoff;
+        let reason = if status.warming {
+            Some("warming up")
+        } else if status.pool_size == 0 && !launches_on_demand {
+            Some("no browser in the pool")
+        } else if status.available_permits == 0 {
+            Some("no render slot available")
+        } else {
+            None
+        };
+
+        match reason {
+            None => HealthResponse::Ok(Json(serde_json::json!({ "status": "ready" }))),
+            Some(reason) => HealthResponse::ServiceUnavailable(Json(serde_json::json!({
+                "status": "not_ready",
+                "reason": reason,
+            }))),
+        }
+    }
+
+    /// Metrics
+    ///
+    /// Render counters, a render duration histogram and browser pool gauges
+    /// in Prometheus text format, for scraping.
+    #[oai(path = "/metrics", method = "get")]
+    async fn metrics(&self, state: Data<&Arc<AppState>>) -> MetricsResponse {
+        MetricsResponse::Ok(PlainText(state.engine.prometheus_metrics()))
+    }
+}
+
+async fn render_to_storage(
+    engine: &RenderingEngine,
+    storage: &ObjectStorage,
+    key: String,
+    request: RenderRequest,
+) -> anyhow::Result<StoredResponse> {
+    let content_type = renderer::mime_type(&request.options.format);
+    let bytes = engine.render(request).await?;
+    let url = storage.upload(&key, bytes, content_type).await?;
+
+    Ok(StoredResponse { url, key })
+}
+
+/// Render into a streamed body. The response only starts once the first
+/// bytes are ready, so a render that fails before that still gets its
+/// error status; one that fails later just ends the body early.
+async fn render_streamed(engine: &RenderingEngine, request: RenderRequest) -> anyhow::Result<Body> {
+    let (mut writer, reader) = tokio::io::duplex(RENDER_STREAM_BUFFER);
+    let engine = engine.clone();
+    let render = tokio::spawn(
+        async move { engine.render_to_writer(request, &mut writer).await }.in_current_span(),
+    );
+
+    let mut reader = BufReader::new(reader);
+    if !reader.fill_buf().await?.is_empty() {
+        tokio::spawn(
+            async move {
+                if let Ok(Err(e)) = render.await {
+                    tracing::error!("Render failed mid-stream: {}", e);
+                }
+            }
+            .in_current_span(),
+        );
+        return Ok(Body::from_async_read(reader));
+    }
+    // Nothing was written: the render failed, or its output is empty
+    render.await??;
+    Ok(Body::empty())
+}
+
+/// Normalize the library name and fill option defaults from the server
+/// config and the `mode` preset.
+fn apply_request_defaults(request: &mut RenderRequest, config: &Config) {
+    if let Some(name) = registry::canonical_name(&request.library.name) {
+        request.library.name = name;
+    }
+    request.options.ppi = request.options.ppi.or(config.default_ppi);
+    apply_mode_defaults(&mut request.options);
+    infer_format(&mut request.options);
+}
+
+/// Rejects a library name the registry can't resolve, listing the ones it
+/// can, so a typo costs no browser work.
+fn unsupported_library(request: &RenderRequest) -> Option<String> {
+    if registry::canonical_name(&request.library.name).is_some() {
+        return None;
+    }
+    Some(format!(
+        "Unsupported library: {} (available: {})",
+        request.library.name,
+        registry::library_names().join(", ")
+    ))
+}
+
+/// Explain why the request uses an option this server has turned off.
+fn disabled_option(request: &RenderRequest, state: &AppState, config: &Config) -> Option<String> {
+    let options = &request.options;
+    if options.return_html.unwrap_or(false) && !config.allow_return_html {
+        return Some("return_html is disabled on this server (allow_return_html)".to_string());
+    }
+    if !config.allow_request_code
+        && registry::get_template(&request.library.name).is_some_and(|t| t.runs_request_code)
+    {
+        return Some(format!(
+            "{} runs code from the request and is disabled on this server (allow_request_code)",
+            request.library.name
+        ));
+    }
+    if let Some(url) = &request.library.cdn_url
+        && let Err(e) = template::validate_cdn_url(url, state.engine.allowed_cdn_hosts())
+    {
+        return Some(format!("library.cdn_url rejected: {}", e));
+    }
+    for (index, font) in options.fonts.iter().flatten().enumerate() {
+        if let Some(url) = font.url.as_ref().or(font.stylesheet_url.as_ref())
+            && let Err(e) = template::validate_font_host(url, state.engine.allowed_cdn_hosts())
+        {
+            return Some(format!("options.fonts.{} rejected: {}", index, e));
+        }
+    }
+    if options.output_key.is_some() && state.storage.is_none() {
+        return Some(
+            "output_key requires object storage on this server (storage_bucket)".to_string(),
+        );
+    }
+    if options.inject_fetch_headers.is_some() && config.fetch_header_hosts.is_none() {
+        return Some(
+            "inject_fetch_headers is disabled on this server (fetch_header_hosts)".to_string(),
+        );
+    }
+    None
+}
+
+/// `message` followed by what the page logged before the render failed,
+/// if it logged anything.
+fn with_console_log(message: String, error: &anyhow::Error) -> String {
+    match error.downcast_ref::<ConsoleLog>() {
+        Some(log) => format!("{}\nConsole output:\n{}", message, log.transcript()),
+        None => message,
+    }
+}
+
+/// A batch item with defaults applied, ready to be queued.
+struct PreparedItem {
+    index: usize,
+    /// File name inside the ZIP archive
+    name: String,
+    request: RenderRequest,
+    /// Set when the item fails without rendering
+    rejected: Option<String>,
+}
+
+type BatchOutcome<T> = (usize, String, Result<T, String>);
+
+/// Queue every item on the engine, yielding results in completion order.
+fn spawn_batch<T, F, Fut>(
+    engine: &Arc<RenderingEngine>,
+    items: Vec<PreparedItem>,
+    render: F,
+) -> JoinSet<BatchOutcome<T>>
+where
+    T: Send + 'static,
+    F: Fn(Arc<RenderingEngine>, RenderRequest) -> Fut,
+    Fut: Future<Output = anyhow::Result<T>> + Send + 'static,
+{
+    let mut tasks = JoinSet::new();
+    for item in items {
+        let render = item
+            .rejected
+            .is_none()
+            .then(|| render(engine.clone(), item.request));
+        let rejected = item.rejected;
+        let (index, name) = (item.index, item.name);
+        tasks.spawn(
+            async move {
+                let outcome = match render {
+                    Some(render) => render
+                        .await
+                        .map_err(|e| with_console_log(e.to_string(), &e)),
+                    None => Err(rejected.unwrap_or_default()),
+                };
+                (index, name, outcome)
+            }
+            .in_current_span(),
+        );
+    }
+    tasks
+}
+
+/// Unwrap a finished batch task, logging failed items. A task only fails to
+/// join if it panicked, in which case its item is left out.
+fn joined_item<T>(joined: Result<BatchOutcome<T>, JoinError>) -> Option<BatchOutcome<T>> {
+    match joined {
+        Ok((index, name, outcome)) => {
+            if let Err(error) = &outcome {
+                tracing::error!("Batch item {} failed: {}", index, error);
+            }
+            Some((index, name, outcome))
+        }
+        Err(e) => {
+            tracing::error!("Batch render task failed: {}", e);
+            None
+        }
+    }
+}
+
+/// Stream a ZIP archive of the batch, writing each file as soon as its render
+/// completes so at most the finished-but-unsent renders are held in memory.
+/// A failed item is written as `<name>.error.txt` holding the error.
+fn stream_zip(mut tasks: JoinSet<BatchOutcome<Vec<u8>>>) -> Body {
+    let (mut writer, reader) = tokio::io::duplex(ZIP_STREAM_BUFFER);
+    let write = async move {
+        let buffer = ArchiveBuffer::default();
+        let mut archive = ZipWriter::new_stream(buffer.clone());
+        while let Some(joined) = tasks.join_next().await {
+            let Some((_, name, outcome)) = joined_item(joined) else {
+                continue;
+            };
+            let entry = match outcome {
+                Ok(bytes) => write_entry(&mut archive, &name, &bytes),
+                Err(error) => write_entry(
+                    &mut archive,
+                    &format!("{}.error.txt", name),
+                    error.as_bytes(),
+                ),
+            };
+            if let Err(e) = entry {
+                tracing::error!("Batch archive entry '{}' failed: {}", name, e);
+                return;
+            }
+            if writer.write_all(&buffer.take()).await.is_err() {
+                // Client went away; dropping the tasks aborts the remaining renders
+                tracing::warn!("Batch archive download closed early");
+                return;
+            }
+        }
+        match archive.finish() {
+            Ok(_) => {
+                if writer.write_all(&buffer.take()).await.is_err() {
+                    tracing::warn!("Batch archive download closed early");
+                }
+            }
+            Err(e) => tracing::error!("Batch archive failed: {}", e),
+        }
+    };
+    tokio::spawn(write.in_current_span());
+    Body::from_async_read(reader)
+}
+
+/// Where `stream_zip`'s archive is written, drained into the response after
+/// each entry.
+#[derive(Clone, Default)]
+struct ArchiveBuffer(Arc<parking_lot::Mutex<Vec<u8>>>);
+
+impl ArchiveBuffer {
+    fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.0.lock())
+    }
+}
+
+impl Write for ArchiveBuffer {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
     }
 }
+
+/// Add one file to the archive. Entries are stored uncompressed: rendered
+/// PNG/JPEG and PDF output is already compressed.
+fn write_entry(
+    archive: &mut ZipWriter<StreamWriter<ArchiveBuffer>>,
+    name: &str,
+    data: &[u8],
+) -> ZipResult<()> {
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Stored)
+        .large_file(data.len() as u64 >= u32::MAX as u64);
+    archive.start_file(name, options)?;
+    archive.write_all(data)?;
+    Ok(())
+}
+
+/// ZIP entry names must be plain, unique file names.
+fn validate_entry_names(items: &[PreparedItem]) -> UnprocessableEntityResponse {
+    let mut errors = UnprocessableEntityResponse::new();
+    let mut seen = HashSet::new();
+    for item in items {
+        let loc = vec![
+            "body".to_string(),
+            "items".to_string(),
+            item.index.to_string(),
+            "name".to_string(),
+        ];
+        let name = item.name.as_str();
+        if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+            errors.add_error(loc, format!("'{}' is not a valid file name", name));
+        } else if !seen.insert(name) {
+            errors.add_error(loc, format!("Duplicate file name '{}'", name));
+        }
+    }
+    errors
+}
+
+fn file_extension(format: &str) -> &str {
+    match format {
+        "jpeg" => "jpg",
+        format => format,
+    }
+}
+
+/// Reason a batch item can't be rendered, checked before it is queued.
+fn batch_item_error(request: &RenderRequest, state: &AppState, config: &Config) -> Option<String> {
+    if let Some(message) = unsupported_library(request) {
+        return Some(message);
+    }
+
+    let errors = validation::validate_request(request);
+    if errors.is_has_error() {
+        return Some(errors.summary());
+    }
+
+    let options = &request.options;
+    if options.sprite_selectors.is_some()
+        || options.capture_pair.unwrap_or(false)
+        || options.output_key.is_some()
+    {
+        return Some(
+            "sprite_selectors, capture_pair and output_key are not supported in batches"
+                .to_string(),
+        );
+    }
+
+    disabled_option(request, state, config)
+}
+
+/// Take an omitted `format` from the `output_key` extension, if it has one.
+fn infer_format(options: &mut RenderOptions) {
+    if options.format.is_empty()
+        && let Some(format) = options
+            .output_key
+            .as_deref()
+            .and_then(storage::format_for_key)
+    {
+        options.format = format.to_string();
+    }
+}
+
+/// Fill the wait options left unset from the `mode` preset.
+fn apply_mode_defaults(options: &mut RenderOptions) {
+    let (wait, render_delay_ms) = match options.mode.as_deref() {
+        Some("fast") => (false, FAST_RENDER_DELAY_MS),
+        Some("quality") => (true, QUALITY_RENDER_DELAY_MS),
+        _ => return,
+    };
+
+    options.wait_for_fonts = options.wait_for_fonts.or(Some(wait));
+    options.wait_for_network_idle = options.wait_for_network_idle.or(Some(wait));
+    options.wait_for_images = options.wait_for_images.or(Some(wait));
+    options.render_delay_ms = options.render_delay_ms.or(Some(render_delay_ms));
+}
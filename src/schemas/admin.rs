@@ -0,0 +1,28 @@
+use poem_openapi::{ApiResponse, Object, payload::Json};
+
+use super::common::{ForbiddenResponse, UnauthorizedResponse, UnprocessableEntityResponse};
+
+#[derive(Object)]
+pub struct RegistryReloadResult {
+    /// Libraries that were not registered before the reload
+    pub added: Vec<String>,
+    /// Libraries whose template changed
+    pub updated: Vec<String>,
+    /// Libraries no longer registered after the reload
+    pub removed: Vec<String>,
+}
+
+#[derive(ApiResponse)]
+pub enum RegistryReloadResponse {
+    #[oai(status = 200)]
+    Ok(Json<RegistryReloadResult>),
+
+    #[oai(status = 401)]
+    Unauthorized(Json<UnauthorizedResponse>),
+
+    #[oai(status = 403)]
+    Forbidden(Json<ForbiddenResponse>),
+
+    #[oai(status = 422)]
+    UnprocessableEntity(Json<UnprocessableEntityResponse>),
+}
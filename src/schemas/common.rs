@@ -33,6 +33,39 @@ pub struct NotFoundResponse {
     pub message: String,
 }
 
+#[derive(Object, Debug)]
+pub struct PayloadTooLargeResponse {
+    pub message: String,
+}
+
+impl PayloadTooLargeResponse {
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            message: format!("request body exceeds {} bytes", max_bytes),
+        }
+    }
+}
+
+#[derive(Object, Debug)]
+pub struct RequestTimeoutResponse {
+    pub message: String,
+}
+
+#[derive(Object, Debug)]
+pub struct BadGatewayResponse {
+    pub message: String,
+}
+
+#[derive(Object, Debug)]
+pub struct ServiceUnavailableResponse {
+    pub message: String,
+}
+
+#[derive(Object, Debug)]
+pub struct GatewayTimeoutResponse {
+    pub message: String,
+}
+
 #[derive(Object, Debug, Clone)]
 pub struct ValidateItem {
     loc: Vec<String>,
@@ -62,11 +95,27 @@ impl UnprocessableEntityResponse {
     pub fn add_error(&mut self, loc: Vec<String>, msg: String) {
         self.detail.push(ValidateItem { loc, msg });
     }
+
+    /// All errors on one line, as `loc: msg; ...`
+    pub fn summary(&self) -> String {
+        self.detail
+            .iter()
+            .map(|item| format!("{}: {}", item.loc.join("."), item.msg))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
 }
 
 #[derive(Object, Debug)]
 pub struct InternalServerErrorResponse {
+    /// Machine-readable failure class, e.g. `BROWSER_CRASH`
+    pub code: String,
+    /// The error alone, without location or console output
+    pub message: String,
     pub detail: String,
+    /// `X-Request-Id` of the failed request, to quote when reporting it
+    #[oai(skip_serializing_if_is_none)]
+    pub request_id: Option<String>,
 }
 
 impl InternalServerErrorResponse {
@@ -77,7 +126,21 @@ impl InternalServerErrorResponse {
         );
         tracing::error!("{}", msg);
         Self {
+            code: "INTERNAL_ERROR".to_string(),
+            message: err.to_string(),
             detail: msg.to_string(),
+            request_id: None,
         }
     }
+
+    pub fn with_code(mut self, code: &str, message: String) -> Self {
+        self.code = code.to_string();
+        self.message = message;
+        self
+    }
+
+    pub fn with_request_id(mut self, request_id: &str) -> Self {
+        self.request_id = Some(request_id.to_string());
+        self
+    }
 }
@@ -1,46 +1,306 @@
+use poem::Body;
 use poem_openapi::{
     ApiResponse, Object,
-    payload::{Attachment, Json},
+    payload::{Attachment, Binary, Html, Json, PlainText},
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
+use std::collections::HashMap;
 
-use super::common::{InternalServerErrorResponse, UnauthorizedResponse};
+use super::common::{
+    BadGatewayResponse, BadRequestResponse, GatewayTimeoutResponse, InternalServerErrorResponse,
+    NotFoundResponse, PayloadTooLargeResponse, RequestTimeoutResponse, ServiceUnavailableResponse,
+    UnauthorizedResponse, UnprocessableEntityResponse,
+};
+
+const DEFAULT_PPI: u32 = 96;
+const MM_PER_INCH: f64 = 25.4;
+const DEFAULT_QUALITY: u8 = 90; // JPEG/WebP when `quality` is unset
 
 #[derive(Object, Deserialize, Clone)]
 pub struct LibraryConfig {
     /// Library name (e.g., "apache-echarts", "chartjs")
     pub name: String,
 
-    /// Library version
+    /// Library version. Defaults to the library's `default_version`, see
+    /// `GET /libraries`
+    #[oai(default)]
+    #[serde(default)]
     pub version: String,
 
     /// Custom CDN URL (optional)
     pub cdn_url: Option<String>,
+
+    /// Plugins to load with the library, by name (e.g. "datalabels" for
+    /// chartjs). `GET /libraries` lists each library's plugins
+    #[oai(validator(max_items = 10))]
+    pub plugins: Option<Vec<String>>,
+}
+
+#[derive(Object, Clone)]
+pub struct LibraryInfo {
+    /// Canonical library name
+    pub name: String,
+
+    /// Other names accepted in `library.name`. Case and separators are
+    /// ignored when matching, e.g. `Apache_ECharts` also works
+    pub aliases: Vec<String>,
+
+    /// Version loaded when a request leaves `library.version` out
+    pub version: Option<String>,
+
+    /// Default CDN URL template
+    pub cdn_url: Option<String>,
+
+    /// Names accepted in `library.plugins`
+    pub plugins: Vec<String>,
+}
+
+#[derive(Object, Clone)]
+pub struct LibraryDetails {
+    /// Canonical library name
+    pub name: String,
+
+    /// Other names accepted in `library.name`
+    pub aliases: Vec<String>,
+
+    /// Default CDN URL, with the `{version}` placeholder `library.version`
+    /// fills in
+    pub cdn_url: Option<String>,
+
+    /// Version loaded when a request leaves `library.version` out
+    pub default_version: Option<String>,
+
+    /// Element the renderer waits for before checking `renderReady`
+    pub wait_selector: String,
+
+    /// Names accepted in `options.theme`
+    pub themes: Vec<String>,
+
+    /// Names accepted in `library.plugins`
+    pub plugins: Vec<String>,
+
+    /// Schema `data` must match, checked before rendering
+    #[oai(skip_serializing_if_is_none)]
+    pub data_schema: Option<JsonValue>,
+}
+
+#[derive(Object, Deserialize, Clone)]
+pub struct Interaction {
+    /// Interaction type (hover, click)
+    #[oai(validator(pattern = "^(hover|click)$"))]
+    pub action: String,
+
+    /// CSS selector of the target element (takes precedence over x/y)
+    pub selector: Option<String>,
+
+    /// X coordinate in CSS pixels, used when no selector is given
+    pub x: Option<f64>,
+
+    /// Y coordinate in CSS pixels, used when no selector is given
+    pub y: Option<f64>,
+}
+
+#[derive(Object, Deserialize, Clone)]
+pub struct FontSpec {
+    /// Family name the chart's font settings refer to (letters, digits,
+    /// spaces, `-` and `_`)
+    pub family: String,
+
+    /// HTTPS URL of a font file (e.g. woff2), declared with `@font-face`
+    pub url: Option<String>,
+
+    /// HTTPS URL of a stylesheet declaring the family, e.g. a Google Fonts
+    /// `https://fonts.googleapis.com/css2?family=...` link
+    pub stylesheet_url: Option<String>,
+}
+
+#[derive(Object, Deserialize, Clone)]
+pub struct PdfOptions {
+    /// Print scale of the page rendering
+    /// Default: 1.0
+    #[oai(validator(minimum(value = "0.1"), maximum(value = "2.0")))]
+    pub scale: Option<f64>,
+
+    /// Pages to print, e.g. "1-5, 8, 11-13". Default: all pages
+    pub page_ranges: Option<String>,
+
+    /// Prefer page size as defined by CSS `@page` over paper size
+    pub prefer_css_page_size: Option<bool>,
+
+    /// Print header and footer using the templates below
+    pub display_header_footer: Option<bool>,
+
+    /// HTML template for the print header. Supports the `date`, `title`,
+    /// `url`, `pageNumber` and `totalPages` classes for injected values
+    pub header_template: Option<String>,
+
+    /// HTML template for the print footer, same format as `header_template`
+    pub footer_template: Option<String>,
+
+    /// Paper width in inches, before `landscape` is applied. Default: 8.5
+    #[oai(validator(minimum(value = "1"), maximum(value = "200")))]
+    pub paper_width: Option<f64>,
+
+    /// Paper height in inches, before `landscape` is applied. Default: 11
+    #[oai(validator(minimum(value = "1"), maximum(value = "200")))]
+    pub paper_height: Option<f64>,
+
+    /// Rotate the paper to landscape orientation. Default: false
+    pub landscape: Option<bool>,
+
+    /// Top margin in inches. Default: 0.4 (1cm)
+    #[oai(validator(minimum(value = "0")))]
+    pub margin_top: Option<f64>,
+
+    /// Bottom margin in inches. Default: 0.4 (1cm)
+    #[oai(validator(minimum(value = "0")))]
+    pub margin_bottom: Option<f64>,
+
+    /// Left margin in inches. Default: 0.4 (1cm)
+    #[oai(validator(minimum(value = "0")))]
+    pub margin_left: Option<f64>,
+
+    /// Right margin in inches. Default: 0.4 (1cm)
+    #[oai(validator(minimum(value = "0")))]
+    pub margin_right: Option<f64>,
+
+    /// Print background colors and images. Default: false
+    pub print_background: Option<bool>,
+}
+
+impl PdfOptions {
+    /// Chrome's defaults for options left unset: US Letter, 1cm margins
+    pub const DEFAULT_PAPER_WIDTH: f64 = 8.5;
+    pub const DEFAULT_PAPER_HEIGHT: f64 = 11.0;
+    pub const DEFAULT_MARGIN: f64 = 0.4;
+
+    /// Printable width and height in inches, after orientation and margins.
+    pub fn content_size(&self) -> (f64, f64) {
+        let (mut width, mut height) = (
+            self.paper_width.unwrap_or(Self::DEFAULT_PAPER_WIDTH),
+            self.paper_height.unwrap_or(Self::DEFAULT_PAPER_HEIGHT),
+        );
+        if self.landscape.unwrap_or(false) {
+            (width, height) = (height, width);
+        }
+        let margin = |value: Option<f64>| value.unwrap_or(Self::DEFAULT_MARGIN);
+        (
+            width - margin(self.margin_left) - margin(self.margin_right),
+            height - margin(self.margin_top) - margin(self.margin_bottom),
+        )
+    }
+}
+
+#[derive(Object, Deserialize, Clone)]
+pub struct ClipRegion {
+    /// Left edge in CSS pixels from the page's top-left corner
+    pub x: u32,
+
+    /// Top edge in CSS pixels
+    pub y: u32,
+
+    #[oai(validator(minimum(value = "1")))]
+    pub width: u32,
+
+    #[oai(validator(minimum(value = "1")))]
+    pub height: u32,
+}
+
+#[derive(Object, Deserialize, Clone)]
+pub struct ScreenOrientation {
+    /// portrait-primary, portrait-secondary, landscape-primary or
+    /// landscape-secondary
+    #[oai(
+        rename = "type",
+        validator(pattern = "^(portrait|landscape)-(primary|secondary)$")
+    )]
+    #[serde(rename = "type")]
+    pub orientation_type: String,
+
+    /// Rotation in degrees reported alongside the type.
+    /// Default: 0, 90, 180 and 270 for portrait-primary, landscape-primary,
+    /// portrait-secondary and landscape-secondary
+    #[oai(validator(maximum(value = "359")))]
+    pub angle: Option<u32>,
 }
 
 #[derive(Object, Deserialize, Clone)]
 pub struct RenderOptions {
-    /// Image width in pixels
+    /// Image width in pixels. Mutually exclusive with `width_mm`
     #[oai(validator(minimum(value = "100"), maximum(value = "4000")))]
-    pub width: u32,
+    pub width: Option<u32>,
 
-    /// Image height in pixels
+    /// Image height in pixels. Mutually exclusive with `height_mm`
     #[oai(validator(minimum(value = "100"), maximum(value = "4000")))]
-    pub height: u32,
+    pub height: Option<u32>,
+
+    /// Physical width in millimeters, converted to pixels at `ppi` (96 if
+    /// unset) and stamped into the output metadata
+    #[oai(validator(minimum(value = "1"), maximum(value = "2000")))]
+    pub width_mm: Option<f64>,
 
-    /// Output format (png, jpeg, pdf)
-    #[oai(validator(pattern = "^(png|jpeg|jpg|pdf)$"))]
+    /// Physical height in millimeters, see `width_mm`
+    #[oai(validator(minimum(value = "1"), maximum(value = "2000")))]
+    pub height_mm: Option<f64>,
+
+    /// Output format (png, jpeg, webp, pdf, rgba, svg). `rgba` returns raw 8-bit
+    /// RGBA pixels (4 bytes per pixel, rows top to bottom, no header) with
+    /// the size in the `X-Image-Width`/`X-Image-Height` headers. `svg` returns
+    /// the chart's SVG markup, for libraries that draw SVG. May be omitted
+    /// when `output_key` ends in one of these extensions
+    #[oai(default, validator(pattern = "^(png|jpeg|jpg|webp|pdf|rgba|svg)$"))]
+    #[serde(default)]
     pub format: String,
 
-    /// Image quality for JPEG (1-100)
+    /// Renderer for libraries that offer both (ECharts, and custom templates
+    /// reading `{renderer}`): `svg` keeps the chart vector, e.g. for sharp
+    /// PDFs. Default: svg for `format: "svg"`, canvas otherwise
+    #[oai(validator(pattern = "^(canvas|svg)$"))]
+    pub renderer: Option<String>,
+
+    /// Image quality for JPEG and WebP (1-100, default 90). Ignored for
+    /// lossless formats
     #[oai(validator(minimum(value = "1"), maximum(value = "100")))]
     pub quality: Option<u8>,
 
+    /// Turn off chart animations (ECharts, Chart.js) so the capture never
+    /// lands mid-animation and repeated renders are identical.
+    /// Default: true
+    pub disable_animation: Option<bool>,
+
+    /// Leave the page background transparent instead of white, for charts
+    /// overlaid on colored UI. Not available for jpeg, which has no alpha
+    pub transparent: Option<bool>,
+
+    /// Page background as a CSS color (`#222`, `rgb(34, 34, 34)`, `navy`),
+    /// e.g. for dark-mode dashboards. Default: white
+    #[oai(validator(max_length = 64))]
+    pub background_color: Option<String>,
+
+    /// Capture only this region of the page instead of the whole viewport.
+    /// Must lie within `width`x`height`. Not available for pdf and svg
+    pub clip: Option<ClipRegion>,
+
     /// Device scale factor for high-DPI displays
     #[oai(validator(minimum(value = "0.5"), maximum(value = "3.0")))]
     pub device_scale_factor: Option<f64>,
 
+    /// Emulate a mobile device: meta viewport tags are honoured and touch
+    /// breakpoints apply, so responsive charts use their mobile layout.
+    /// Default: false
+    pub mobile: Option<bool>,
+
+    /// Screen orientation the page sees, for libraries that lay out by
+    /// `screen.orientation` rather than by size. Default: none
+    pub screen_orientation: Option<ScreenOrientation>,
+
+    /// `User-Agent` the page sees, in its requests and `navigator.userAgent`,
+    /// for charts that pick their layout from the user agent.
+    /// Default: Chrome's own
+    #[oai(validator(max_length = 500, pattern = r"^[ -~]+$"))]
+    pub user_agent: Option<String>,
+
     /// Custom delay after render ready (milliseconds)
     /// Default: 500ms
     #[oai(validator(minimum(value = "0"), maximum(value = "5000")))]
@@ -56,8 +316,221 @@ pub struct RenderOptions {
     #[oai(validator(minimum(value = "1000"), maximum(value = "60000")))]
     pub timeout_ms: Option<u64>,
 
+    /// Preset for the wait options below (fast, quality). `fast` skips
+    /// fonts, network idle and images with a 50ms settle delay, for quick
+    /// thumbnails. `quality` waits for all three. Options set explicitly
+    /// override the preset
+    #[oai(validator(pattern = "^(fast|quality)$"))]
+    pub mode: Option<String>,
+
+    /// Hold `renderReady` until web fonts (`document.fonts.ready`) have
+    /// loaded, so labels aren't captured in a fallback font.
+    /// Default: true
+    pub wait_for_fonts: Option<bool>,
+
+    /// Wait until no new network requests have started for 500ms
+    pub wait_for_network_idle: Option<bool>,
+
+    /// Wait for every `<img>` on the page to finish loading
+    pub wait_for_images: Option<bool>,
+
+    /// CSS selector that must match an element before capture, checked
+    /// after `renderReady` and within `timeout_ms`. For libraries that
+    /// report ready before their output is in the page
+    #[oai(validator(max_length = 1024))]
+    pub wait_for_selector: Option<String>,
+
     /// Return base64 encoded string instead of binary
     pub return_base64: Option<bool>,
+
+    /// Capture each element matching these selectors and return them packed
+    /// into a single PNG sprite sheet plus CSS, instead of one image
+    #[oai(validator(max_items = 50))]
+    pub sprite_selectors: Option<Vec<String>>,
+
+    /// Rasterization path (default, swiftshader). `swiftshader` routes the
+    /// render to a separate browser pool with WebGL enabled
+    #[oai(validator(pattern = "^(default|swiftshader)$"))]
+    pub raster_engine: Option<String>,
+
+    /// Widen the capture to the page's full scrollable width (never narrower
+    /// than `width`, capped at 8192px)
+    pub auto_width: Option<bool>,
+
+    /// Grow the capture to the chart container's full content height, e.g.
+    /// for a Mermaid gantt of unknown length (never shorter than `height`,
+    /// capped at 8192px)
+    pub auto_height: Option<bool>,
+
+    /// Page overflow (hidden, visible). Use `visible` with `auto_width` to
+    /// capture labels drawn outside the chart container
+    /// Default: hidden
+    #[oai(validator(pattern = "^(hidden|visible)$"))]
+    pub overflow: Option<String>,
+
+    /// Named theme registered for the library, e.g. `light` or `dark` for
+    /// ECharts
+    pub theme: Option<String>,
+
+    /// `Accept-Language` header sent with the page's requests, for
+    /// locale-dependent CDN assets. Example: `de-DE,de;q=0.9`
+    /// Default: en-US
+    #[oai(validator(max_length = 200, pattern = r"^[A-Za-z0-9*,;=.\- ]+$"))]
+    pub accept_language: Option<String>,
+
+    /// Headers added to requests the page itself makes (fetch/XHR and other
+    /// subresources), e.g. auth for chart data fetched in-page. Only sent to
+    /// hosts in the server's `fetch_header_hosts`
+    pub inject_fetch_headers: Option<HashMap<String, String>>,
+
+    /// Seed for the page's `Math.random`, so layouts that place elements
+    /// randomly (e.g. ECharts `graph` series with `layout: "force"`) come out
+    /// the same for the same seed
+    pub seed: Option<u64>,
+
+    /// Slow the page's CPU down by this factor (1 = no throttling), so that
+    /// timing-dependent output such as animation frames matches across fast
+    /// and slow hosts. Renders take correspondingly longer.
+    /// Default: 1
+    #[oai(validator(minimum(value = "1"), maximum(value = "20")))]
+    pub cpu_throttling_rate: Option<f64>,
+
+    /// Emulated `prefers-reduced-motion` media feature (reduce, no-preference)
+    #[oai(validator(pattern = "^(reduce|no-preference)$"))]
+    pub prefers_reduced_motion: Option<String>,
+
+    /// Emulated `forced-colors` media feature (active, none)
+    #[oai(validator(pattern = "^(active|none)$"))]
+    pub forced_colors: Option<String>,
+
+    /// Emulated `prefers-color-scheme` media feature (light, dark)
+    #[oai(validator(pattern = "^(light|dark)$"))]
+    pub prefers_color_scheme: Option<String>,
+
+    /// Include the generated HTML in the base64 response for auditing.
+    /// Requires `allow_return_html` in the server config
+    pub return_html: Option<bool>,
+
+    /// Page options applied when `format` is pdf
+    pub pdf_options: Option<PdfOptions>,
+
+    /// On a failed render, respond 200 with a PNG card of the requested size
+    /// showing the error message (and an `X-Render-Error: true` header)
+    /// instead of an error status, e.g. for charts embedded in iframes
+    pub error_as_image: Option<bool>,
+
+    /// Include the page's console output (`console.log` and friends) in the
+    /// base64 response, up to 100 messages. Requires `return_base64`
+    pub capture_console: Option<bool>,
+
+    /// Attach the request `data` to the PDF as an embedded `data.json` file
+    pub embed_data: Option<bool>,
+
+    /// Upload the output to the configured object storage under this key and
+    /// return its URL instead of the image. Requires `storage_bucket` in the
+    /// server config
+    #[oai(validator(max_length = 1024))]
+    pub output_key: Option<String>,
+
+    /// Bits per channel of PNG output (8 or 16). Chrome captures at 8 bits,
+    /// so 16 widens each sample rather than adding precision.
+    /// Default: 8
+    #[oai(validator(multiple_of = "8", minimum(value = "8"), maximum(value = "16")))]
+    pub bit_depth: Option<u8>,
+
+    /// Physical resolution stamped into PNG/JPEG metadata (pixels per inch).
+    /// Pixels are not resampled; see `device_scale_factor` for that. Also
+    /// sets the pixel density used to convert `width_mm`/`height_mm`.
+    /// Default: `default_ppi` from the server config, if set
+    #[oai(validator(minimum(value = "1"), maximum(value = "2400")))]
+    pub ppi: Option<u32>,
+
+    /// Capture a "before" image at render ready and an "after" image once
+    /// `interactions` have been performed, returned together as JSON
+    pub capture_pair: Option<bool>,
+
+    /// Interactions performed between the "before" and "after" captures
+    pub interactions: Option<Vec<Interaction>>,
+
+    /// Delay after the last interaction before the "after" capture (milliseconds)
+    /// Default: 300ms
+    #[oai(validator(minimum(value = "0"), maximum(value = "5000")))]
+    pub interaction_delay_ms: Option<u64>,
+
+    /// Render fresh, skipping the server's response cache for both lookup
+    /// and storage
+    pub no_cache: Option<bool>,
+
+    /// Web fonts to load before the chart initializes, each from a font
+    /// file `url` or a `stylesheet_url`
+    #[oai(validator(max_items = 10))]
+    pub fonts: Option<Vec<FontSpec>>,
+}
+
+impl RenderOptions {
+    /// Capture width in CSS pixels, from `width` or `width_mm`
+    pub fn pixel_width(&self) -> u32 {
+        self.width
+            .or_else(|| self.width_mm.map(|mm| self.mm_to_pixels(mm)))
+            .unwrap_or_default()
+    }
+
+    /// Capture height in CSS pixels, from `height` or `height_mm`
+    pub fn pixel_height(&self) -> u32 {
+        self.height
+            .or_else(|| self.height_mm.map(|mm| self.mm_to_pixels(mm)))
+            .unwrap_or_default()
+    }
+
+    /// Size of the capture in device pixels: the clip region, or else the
+    /// requested viewport, times `device_scale_factor`. Content-sized
+    /// captures (`auto_width`/`auto_height`) can come out larger.
+    pub fn output_dimensions(&self) -> (u32, u32) {
+        let (width, height) = match &self.clip {
+            Some(clip) => (clip.width, clip.height),
+            None => (self.pixel_width(), self.pixel_height()),
+        };
+        let scale = self.device_scale_factor.unwrap_or(1.0);
+        (
+            (width as f64 * scale).round() as u32,
+            (height as f64 * scale).round() as u32,
+        )
+    }
+
+    /// PPI to stamp into the output. Physical sizes always stamp one so the
+    /// image prints at the requested dimensions.
+    pub fn output_ppi(&self) -> Option<u32> {
+        let physical = self.width_mm.is_some() || self.height_mm.is_some();
+        self.ppi.or(physical.then_some(DEFAULT_PPI))
+    }
+
+    /// Whether the library should draw with its SVG renderer.
+    pub fn svg_renderer(&self) -> bool {
+        match self.renderer.as_deref() {
+            Some(renderer) => renderer == "svg",
+            None => self.format == "svg",
+        }
+    }
+
+    /// Quality to pass Chrome for the output `format`: `quality` (default
+    /// 90) for JPEG and WebP, kept within 1-100 for requests that skipped
+    /// schema validation, and none for every other format.
+    pub fn capture_quality(&self) -> Option<u32> {
+        match self.format.as_str() {
+            "jpeg" | "jpg" | "webp" => {
+                Some(self.quality.unwrap_or(DEFAULT_QUALITY).clamp(1, 100).into())
+            }
+            _ => None,
+        }
+    }
+
+    /// The device scale factor multiplies the captured pixels, so the CSS
+    /// size is divided by it to keep the output at exactly `ppi`.
+    fn mm_to_pixels(&self, mm: f64) -> u32 {
+        let ppi = self.ppi.unwrap_or(DEFAULT_PPI) as f64;
+        let scale = self.device_scale_factor.unwrap_or(1.0);
+        (mm / MM_PER_INCH * ppi / scale).round() as u32
+    }
 }
 
 #[derive(Object, Deserialize, Clone)]
@@ -67,6 +540,27 @@ pub struct RenderRequest {
     pub options: RenderOptions,
 }
 
+#[derive(Object, Deserialize, Clone)]
+pub struct BatchRenderRequest {
+    /// Charts to render, at most `max_batch_size`
+    pub items: Vec<BatchItem>,
+
+    /// `json` (default) for base64 results, `zip` for an archive of the
+    /// rendered files
+    #[oai(validator(pattern = "^(json|zip)$"))]
+    pub output: Option<String>,
+}
+
+#[derive(Object, Deserialize, Clone)]
+pub struct BatchItem {
+    /// File name inside the ZIP archive, defaults to `<index>.<format>`
+    pub name: Option<String>,
+
+    #[oai(flatten)]
+    #[serde(flatten)]
+    pub request: RenderRequest,
+}
+
 #[derive(Object, Serialize)]
 pub struct Base64Response {
     /// Base64 encoded image data
@@ -74,27 +568,221 @@ pub struct Base64Response {
 
     /// MIME type of the image
     pub mime_type: String,
+
+    /// Width of the image in pixels. For SVG and PDF, the viewport width
+    /// times `device_scale_factor`
+    pub width: u32,
+
+    /// Height of the image in pixels, including any `auto_height` growth
+    /// for raster formats
+    pub height: u32,
+
+    /// Size of the image in bytes, before base64 encoding
+    pub size_bytes: u64,
+
+    /// HTML document that produced the image (when `return_html` is set)
+    #[oai(skip_serializing_if_is_none)]
+    pub html: Option<String>,
+
+    /// Console output of the page (when `capture_console` is set)
+    #[oai(skip_serializing_if_is_none)]
+    pub console: Option<Vec<ConsoleMessage>>,
+}
+
+#[derive(Object, Serialize, Clone, Debug)]
+pub struct ConsoleMessage {
+    /// Console method, e.g. log, warning, error
+    pub level: String,
+
+    pub text: String,
+}
+
+#[derive(Object, Serialize)]
+pub struct BatchItemResult {
+    /// Position of the chart in `items`
+    pub index: usize,
+
+    /// Rendered chart, when it succeeded
+    #[oai(skip_serializing_if_is_none)]
+    pub result: Option<Base64Response>,
+
+    /// Why the chart failed, when it did
+    #[oai(skip_serializing_if_is_none)]
+    pub error: Option<String>,
+}
+
+#[derive(Object, Serialize)]
+pub struct StoredResponse {
+    /// URL of the uploaded object
+    pub url: String,
+
+    /// Object key the output was stored under
+    pub key: String,
+}
+
+#[derive(Object, Serialize)]
+pub struct CapturePairResponse {
+    /// Capture taken right after render ready
+    pub before: Base64Response,
+
+    /// Capture taken after the configured interactions
+    pub after: Base64Response,
+}
+
+#[derive(Object, Serialize)]
+pub struct SpriteFrame {
+    /// Selector the frame was captured from
+    pub selector: String,
+
+    /// CSS class displaying this frame
+    pub class_name: String,
+
+    /// Frame position and size in sprite sheet pixels
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Object, Serialize)]
+pub struct SpriteResponse {
+    /// Base64 encoded PNG sprite sheet
+    pub sprite: Base64Response,
+
+    /// CSS rules showing each frame via `class="sprite sprite-N"`. Set the
+    /// sheet as `background-image` on `.sprite` yourself
+    pub css: String,
+
+    /// Frames in sheet order, matching `sprite_selectors`
+    pub frames: Vec<SpriteFrame>,
 }
 
 #[derive(ApiResponse)]
 pub enum RenderResponse {
     #[oai(status = 200, content_type = "application/octet-stream")]
-    Binary(Attachment<Vec<u8>>),
+    Binary(Attachment<Body>),
 
     #[oai(status = 200, content_type = "application/json")]
     Base64(Json<Base64Response>),
 
+    /// Raw RGBA pixels for `format: "rgba"`
+    #[oai(status = 200, content_type = "application/octet-stream")]
+    Raw(
+        Binary<Vec<u8>>,
+        #[oai(header = "X-Image-Width")] u32,
+        #[oai(header = "X-Image-Height")] u32,
+    ),
+
+    /// WebP image for `format: "webp"`
+    #[oai(status = 200, content_type = "image/webp")]
+    Webp(Binary<Vec<u8>>),
+
+    /// SVG document for `format: "svg"`
+    #[oai(status = 200, content_type = "image/svg+xml")]
+    Svg(Binary<Vec<u8>>),
+
+    /// Card showing the error of a failed render, when `error_as_image` is set
+    #[oai(status = 200, content_type = "image/png")]
+    ErrorImage(Binary<Vec<u8>>, #[oai(header = "X-Render-Error")] bool),
+
+    #[oai(status = 200, content_type = "application/json")]
+    Pair(Json<CapturePairResponse>),
+
+    #[oai(status = 200, content_type = "application/json")]
+    Sprite(Json<SpriteResponse>),
+
+    #[oai(status = 200, content_type = "application/json")]
+    Stored(Json<StoredResponse>),
+
+    #[oai(status = 400)]
+    BadRequest(Json<BadRequestResponse>),
+
     #[oai(status = 401)]
     Unauthorized(Json<UnauthorizedResponse>),
 
+    /// The page did not signal it was ready within `timeout_ms`
+    #[oai(status = 408)]
+    RequestTimeout(Json<RequestTimeoutResponse>),
+
+    /// `data` is over the server's size limit
+    #[oai(status = 413)]
+    PayloadTooLarge(Json<PayloadTooLargeResponse>),
+
+    #[oai(status = 422)]
+    UnprocessableEntity(Json<UnprocessableEntityResponse>),
+
     #[oai(status = 500)]
     InternalServerError(Json<InternalServerErrorResponse>),
+
+    /// The render succeeded but uploading it to object storage failed
+    #[oai(status = 502)]
+    BadGateway(Json<BadGatewayResponse>),
+
+    /// No browser could be launched; retry later
+    #[oai(status = 503)]
+    ServiceUnavailable(Json<ServiceUnavailableResponse>),
+
+    /// Capturing the rendered page did not finish within `timeout_ms`
+    #[oai(status = 504)]
+    GatewayTimeout(Json<GatewayTimeoutResponse>),
+}
+
+#[derive(ApiResponse)]
+pub enum RenderHtmlResponse {
+    /// The page a render would load, for opening in a browser
+    #[oai(status = 200, content_type = "text/html; charset=utf-8")]
+    Ok(Html<String>),
+
+    #[oai(status = 400)]
+    BadRequest(Json<BadRequestResponse>),
+
+    /// `data` is over the server's size limit
+    #[oai(status = 413)]
+    PayloadTooLarge(Json<PayloadTooLargeResponse>),
+
+    #[oai(status = 422)]
+    UnprocessableEntity(Json<UnprocessableEntityResponse>),
+}
+
+#[derive(ApiResponse)]
+pub enum BatchRenderResponse {
+    /// One result per item, in request order
+    #[oai(status = 200, content_type = "application/json")]
+    Ok(Json<Vec<BatchItemResult>>),
+
+    /// Rendered files, written as they complete (`output: "zip"`)
+    #[oai(status = 200, content_type = "application/zip")]
+    Zip(Attachment<Body>),
+
+    #[oai(status = 422)]
+    UnprocessableEntity(Json<UnprocessableEntityResponse>),
+}
+
+#[derive(ApiResponse)]
+pub enum HealthResponse {
+    #[oai(status = 200, content_type = "application/json")]
+    Ok(Json<JsonValue>),
+
+    /// Engine should not receive traffic: it is warming up, has no browser
+    /// or render slot free (`/health/ready`), or the `deep` canary failed
+    #[oai(status = 503, content_type = "application/json")]
+    ServiceUnavailable(Json<JsonValue>),
+}
+
+#[derive(ApiResponse)]
+pub enum MetricsResponse {
+    /// Prometheus text exposition format
+    #[oai(
+        status = 200,
+        content_type = "text/plain; version=0.0.4; charset=utf-8"
+    )]
+    Ok(PlainText<String>),
 }
 
 #[derive(ApiResponse)]
 pub enum ListLibrariesResponse {
     #[oai(status = 200, content_type = "application/json")]
-    Ok(Json<Vec<LibraryConfig>>),
+    Ok(Json<Vec<LibraryInfo>>),
 
     #[oai(status = 401)]
     Unauthorized(Json<UnauthorizedResponse>),
@@ -102,3 +790,16 @@ pub enum ListLibrariesResponse {
     #[oai(status = 500)]
     InternalServerError(Json<InternalServerErrorResponse>),
 }
+
+#[derive(ApiResponse)]
+pub enum LibraryDetailsResponse {
+    #[oai(status = 200, content_type = "application/json")]
+    Ok(Json<LibraryDetails>),
+
+    #[oai(status = 401)]
+    Unauthorized(Json<UnauthorizedResponse>),
+
+    /// No library is registered under the name
+    #[oai(status = 404)]
+    NotFound(Json<NotFoundResponse>),
+}
@@ -1,14 +1,97 @@
 use std::env;
+use std::fmt;
 
 use serde::Deserialize;
 use tracing::info;
 
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize)]
 pub struct Config {
     pub env: String, // file / server
     pub host: String,
     pub port: u16,
-    pub prefix: Option<String>
+    pub prefix: Option<String>,
+    pub default_ppi: Option<u32>,
+    #[serde(default)]
+    pub allow_return_html: bool,
+    #[serde(default)]
+    pub allow_request_code: bool,
+    #[serde(default)]
+    pub warmup: bool,
+    pub min_pool_size: Option<usize>,
+    pub max_pool_size: Option<usize>,
+    pub max_concurrent: Option<usize>,
+    pub scale_cooldown_secs: Option<u64>,
+    pub pool_idle_timeout_secs: Option<u64>,
+    pub library_registry_path: Option<String>,
+    pub admin_api_key: Option<String>,
+    pub api_key: Option<String>,
+    pub chrome_path: Option<String>,
+    pub extra_chrome_args: Option<String>, // space-separated
+    pub slow_render_threshold_ms: Option<u64>,
+    pub fetch_header_hosts: Option<String>, // comma-separated
+    pub allowed_cdn_hosts: Option<String>,  // comma-separated
+    pub cache_max_entries: Option<usize>,
+    pub cache_ttl_secs: Option<u64>,
+    pub crash_retries: Option<usize>,
+    pub max_connections: Option<usize>,
+    pub accept_backlog: Option<u32>,
+    pub max_batch_size: Option<usize>,
+    pub max_request_body_bytes: Option<usize>,
+    pub storage_bucket: Option<String>,
+    pub storage_region: Option<String>,
+    pub storage_endpoint: Option<String>,
+    pub storage_access_key_id: Option<String>,
+    pub storage_secret_access_key: Option<String>,
+    pub storage_public_url: Option<String>,
+}
+
+/// Shown in place of secret settings so the config can be logged.
+const REDACTED: &str = "<redacted>";
+
+fn redact(secret: &Option<String>) -> Option<&'static str> {
+    secret.as_ref().map(|_| REDACTED)
+}
+
+// Hand-written so that API keys and storage credentials never end up in logs
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("env", &self.env)
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("prefix", &self.prefix)
+            .field("default_ppi", &self.default_ppi)
+            .field("allow_return_html", &self.allow_return_html)
+            .field("allow_request_code", &self.allow_request_code)
+            .field("warmup", &self.warmup)
+            .field("min_pool_size", &self.min_pool_size)
+            .field("max_pool_size", &self.max_pool_size)
+            .field("max_concurrent", &self.max_concurrent)
+            .field("scale_cooldown_secs", &self.scale_cooldown_secs)
+            .field("pool_idle_timeout_secs", &self.pool_idle_timeout_secs)
+            .field("library_registry_path", &self.library_registry_path)
+            .field("admin_api_key", &redact(&self.admin_api_key))
+            .field("api_key", &redact(&self.api_key))
+            .field("chrome_path", &self.chrome_path)
+            .field("extra_chrome_args", &self.extra_chrome_args)
+            .field("slow_render_threshold_ms", &self.slow_render_threshold_ms)
+            .field("fetch_header_hosts", &self.fetch_header_hosts)
+            .field("allowed_cdn_hosts", &self.allowed_cdn_hosts)
+            .field("cache_max_entries", &self.cache_max_entries)
+            .field("cache_ttl_secs", &self.cache_ttl_secs)
+            .field("crash_retries", &self.crash_retries)
+            .field("max_connections", &self.max_connections)
+            .field("accept_backlog", &self.accept_backlog)
+            .field("max_batch_size", &self.max_batch_size)
+            .field("max_request_body_bytes", &self.max_request_body_bytes)
+            .field("storage_bucket", &self.storage_bucket)
+            .field("storage_region", &self.storage_region)
+            .field("storage_endpoint", &self.storage_endpoint)
+            .field("storage_access_key_id", &redact(&self.storage_access_key_id))
+            .field("storage_secret_access_key", &redact(&self.storage_secret_access_key))
+            .field("storage_public_url", &self.storage_public_url)
+            .finish()
+    }
 }
 
 pub fn get_config() -> Config {
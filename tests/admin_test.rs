@@ -0,0 +1,114 @@
+mod common;
+
+use poem::http::StatusCode;
+use rendering_engine::core::registry;
+use serde_json::{Value, json};
+
+fn template(wait_selector: &str) -> Value {
+    json!({
+        "cdn_url": "https://cdn.jsdelivr.net/npm/apexcharts@{version}/dist/apexcharts.min.js",
+        "wait_selector": wait_selector,
+        "init_script": "window.renderReady = true;"
+    })
+}
+
+#[tokio::test]
+async fn test_reload_registry_requires_admin_key() {
+    let path = std::env::temp_dir().join(format!("{}-admin-auth.json", std::process::id()));
+    std::fs::write(&path, "{}").unwrap();
+
+    // Without admin_api_key the endpoint is off, whatever key is sent
+    let cli = common::client(json!({"library_registry_path": path}));
+    let resp = cli
+        .post("/admin/registry/reload")
+        .header("X-Admin-Key", "anything")
+        .send()
+        .await;
+    resp.assert_status(StatusCode::FORBIDDEN);
+
+    let cli =
+        common::client(json!({"admin_api_key": "admin-secret", "library_registry_path": path}));
+    for key in [Some("wrong"), Some("admin-secret2"), Some(""), None] {
+        let mut request = cli.post("/admin/registry/reload");
+        if let Some(key) = key {
+            request = request.header("X-Admin-Key", key);
+        }
+        request.send().await.assert_status(StatusCode::UNAUTHORIZED);
+    }
+    std::fs::remove_file(path).ok();
+}
+
+#[tokio::test]
+async fn test_reload_registry_reports_changes() {
+    let path = std::env::temp_dir().join(format!("{}-admin-registry.json", std::process::id()));
+    let cli =
+        common::client(json!({"admin_api_key": "admin-secret", "library_registry_path": path}));
+    let reload = || {
+        cli.post("/admin/registry/reload")
+            .header("X-Admin-Key", "admin-secret")
+            .send()
+    };
+
+    std::fs::write(
+        &path,
+        json!({
+            "apexcharts-next": template("#render-container"),
+            "apexcharts-legacy": template("#render-container")
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let resp = reload().await;
+    resp.assert_status_is_ok();
+    resp.assert_json(json!({
+        "added": ["apexcharts-legacy", "apexcharts-next"],
+        "updated": [],
+        "removed": []
+    }))
+    .await;
+
+    std::fs::write(
+        &path,
+        json!({"apexcharts-next": template("#chart")}).to_string(),
+    )
+    .unwrap();
+    let resp = reload().await;
+    resp.assert_status_is_ok();
+    resp.assert_json(json!({
+        "added": [],
+        "updated": ["apexcharts-next"],
+        "removed": ["apexcharts-legacy"]
+    }))
+    .await;
+
+    // An invalid file is rejected and the registry is left as it was
+    std::fs::write(
+        &path,
+        json!({"evil": {
+            "cdn_url": "https://example.com/x.js",
+            "wait_selector": "#render-container",
+            "init_script": "window.renderReady = true;"
+        }})
+        .to_string(),
+    )
+    .unwrap();
+    let resp = reload().await;
+    resp.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = resp.json().await.value().deserialize();
+    assert_eq!(
+        body["detail"][0]["loc"],
+        json!(["config", "library_registry_path"])
+    );
+    assert!(
+        body["detail"][0]["msg"]
+            .as_str()
+            .unwrap()
+            .contains("'evil'"),
+        "{}",
+        body
+    );
+    assert!(registry::get_template("apexcharts-next").is_some());
+    assert!(registry::get_template("evil").is_none());
+
+    std::fs::remove_file(path).ok();
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use poem::http::StatusCode;
+use serde_json::{Value, json};
+
+#[tokio::test]
+async fn test_valid_api_key_is_accepted() {
+    let cli = common::client(json!({"api_key": "secret"}));
+
+    let resp = cli
+        .get("/libraries")
+        .header("X-API-Key", "secret")
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+}
+
+#[tokio::test]
+async fn test_missing_or_wrong_api_key_is_rejected() {
+    let cli = common::client(json!({"api_key": "secret"}));
+
+    let resp = cli.get("/libraries").send().await;
+    resp.assert_status(StatusCode::UNAUTHORIZED);
+    let body: Value = resp.json().await.value().deserialize();
+    assert_eq!(body["message"], "unauthorized");
+
+    for key in ["wrong", "secret2", ""] {
+        let resp = cli.get("/libraries").header("X-API-Key", key).send().await;
+        resp.assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    let resp = cli
+        .post("/render")
+        .body_json(&json!({
+            "library": {"name": "apache-echarts", "version": "5.4.0"},
+            "data": {},
+            "options": {"width": 400, "height": 300, "format": "png"}
+        }))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::UNAUTHORIZED);
+
+    // Probes don't need the key
+    cli.get("/health").send().await.assert_status_is_ok();
+}
+
+#[tokio::test]
+async fn test_no_api_key_configured_disables_auth() {
+    let cli = common::client(json!({}));
+
+    cli.get("/libraries").send().await.assert_status_is_ok();
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use poem::http::StatusCode;
+use serde_json::{Value, json};
+
+fn item(theme: Option<&str>) -> Value {
+    json!({
+        "library": {"name": "echarts", "version": "5.4.0"},
+        "data": {"series": [{"type": "bar", "data": [1, 2, 3]}]},
+        "options": {"width": 400, "height": 300, "format": "png", "theme": theme}
+    })
+}
+
+#[tokio::test]
+async fn test_batch_over_max_size_rejected() {
+    let cli = common::client(json!({"max_batch_size": 2}));
+
+    let resp = cli
+        .post("/render/batch")
+        .body_json(&json!({"items": [item(None), item(None), item(None)]}))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = resp.json().await.value().deserialize();
+    assert_eq!(body["detail"][0]["loc"], json!(["body", "items"]));
+}
+
+#[tokio::test]
+async fn test_batch_reports_errors_per_item() {
+    let cli = common::client(json!({"max_batch_size": 2}));
+
+    let resp = cli
+        .post("/render/batch")
+        .body_json(&json!({"items": [item(Some("no-such-theme")), item(None)]}))
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    let body: Value = resp.json().await.value().deserialize();
+    let results = body.as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["index"], 0);
+    assert!(
+        results[0]["error"]
+            .as_str()
+            .unwrap()
+            .contains("body.options.theme")
+    );
+    assert_eq!(results[1]["index"], 1);
+    assert!(results[1]["error"].is_string());
+    assert!(results[1].get("result").is_none());
+}
+
+fn named_item(name: &str) -> Value {
+    let mut item = item(None);
+    item["name"] = json!(name);
+    item
+}
+
+#[tokio::test]
+async fn test_batch_zip_rejects_duplicate_names() {
+    let cli = common::client(json!({"max_batch_size": 2}));
+
+    let resp = cli
+        .post("/render/batch")
+        .body_json(&json!({
+            "output": "zip",
+            "items": [named_item("chart.png"), named_item("chart.png")]
+        }))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = resp.json().await.value().deserialize();
+    assert_eq!(
+        body["detail"][0]["loc"],
+        json!(["body", "items", "1", "name"])
+    );
+}
+
+#[tokio::test]
+async fn test_batch_zip_archives_failed_items() {
+    let cli = common::client(json!({"max_batch_size": 2}));
+
+    let resp = cli
+        .post("/render/batch")
+        .body_json(&json!({
+            "output": "zip",
+            "items": [named_item("sales.png"), item(Some("no-such-theme"))]
+        }))
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    resp.assert_content_type("application/zip");
+    let archive = resp.0.into_body().into_vec().await.unwrap();
+
+    assert_eq!(&archive[..4], b"PK\x03\x04");
+    // End of central directory record: signature, then the entry count at +10
+    let end = archive.len() - 22;
+    assert_eq!(&archive[end..end + 4], b"PK\x05\x06");
+    assert_eq!(
+        u16::from_le_bytes([archive[end + 10], archive[end + 11]]),
+        2
+    );
+
+    let contains = |name: &[u8]| archive.windows(name.len()).any(|window| window == name);
+    assert!(contains(b"sales.png.error.txt"));
+    assert!(contains(b"1.png.error.txt"));
+    assert!(contains(b"no-such-theme"));
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use poem::{Body, http::StatusCode};
+use rendering_engine::core::template::{self, DataTooLarge, MAX_DATA_BYTES};
+use rendering_engine::schemas::render::RenderRequest;
+use serde_json::json;
+
+fn payload(points: usize) -> serde_json::Value {
+    json!({
+        "library": {"name": "apache-echarts", "version": "5.4.0"},
+        "data": {"series": [{"type": "bar", "data": vec![1; points]}]},
+        "options": {"width": 400, "height": 300, "format": "png"}
+    })
+}
+
+#[tokio::test]
+async fn test_oversized_body_is_rejected_with_413() {
+    let cli = common::client(json!({"allow_return_html": true, "max_request_body_bytes": 1024}));
+
+    let resp = cli
+        .post("/render")
+        .header("Content-Length", "4096")
+        .body(vec![b' '; 4096])
+        .send()
+        .await;
+    resp.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    resp.assert_json(json!({"message": "request body exceeds 1024 bytes"}))
+        .await;
+
+    // Without a Content-Length the body is read up to the limit
+    let resp = cli
+        .post("/render/html")
+        .content_type("application/json")
+        .body(Body::from_json(payload(1000)).unwrap())
+        .send()
+        .await;
+    resp.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn test_body_under_limit_reaches_route() {
+    let cli = common::client(json!({"allow_return_html": true, "max_request_body_bytes": 1024}));
+
+    let resp = cli.post("/render/html").body_json(&payload(3)).send().await;
+    resp.assert_status_is_ok();
+
+    let resp = cli.get("/health/live").send().await;
+    resp.assert_status_is_ok();
+}
+
+#[test]
+fn test_oversized_data_fails_before_page_is_built() {
+    // A string just over the limit once its quotes are added
+    let request: RenderRequest = serde_json::from_value(json!({
+        "library": {"name": "apache-echarts", "version": "5.4.0"},
+        "data": {"title": "x".repeat(MAX_DATA_BYTES)},
+        "options": {"width": 400, "height": 300, "format": "png"}
+    }))
+    .unwrap();
+
+    let error = template::generate_html(&request, &[]).unwrap_err();
+    assert!(error.is::<DataTooLarge>());
+    assert_eq!(
+        error.to_string(),
+        format!("data exceeds {} bytes once serialized", MAX_DATA_BYTES)
+    );
+}
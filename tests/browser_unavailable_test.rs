@@ -0,0 +1,81 @@
+mod common;
+
+use poem::http::StatusCode;
+use rendering_engine::core::renderer::{EngineSettings, RenderingEngine};
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn test_render_returns_503_when_browser_cannot_launch() {
+    // Lazy pool pointed at a missing binary, so every launch fails
+    let cli = common::client(json!({"max_concurrent": 1}));
+
+    let payload = json!({
+        "library": {"name": "apache-echarts", "version": "5.4.0"},
+        "data": {"series": [{"type": "bar", "data": [1, 2, 3]}]},
+        "options": {"width": 400, "height": 300, "format": "png"}
+    });
+
+    // With a single render permit, the second request only completes if the
+    // first released its permit after failing
+    for _ in 0..2 {
+        let start = Instant::now();
+        let resp = cli.post("/render").body_json(&payload).send().await;
+        resp.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+        assert!(
+            start.elapsed() < Duration::from_secs(5),
+            "Launch failure should be reported quickly, took {:?}",
+            start.elapsed()
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_streamed_pdf_failure_keeps_error_status() {
+    let cli = common::client(json!({}));
+
+    // Fails before any output, so the status is still the render's own
+    let resp = cli
+        .post("/render")
+        .body_json(&json!({
+            "library": {"name": "apache-echarts", "version": "5.4.0"},
+            "data": {"series": [{"type": "bar", "data": [1, 2, 3]}]},
+            "options": {"width": 400, "height": 300, "format": "pdf", "no_cache": true}
+        }))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn test_failed_renders_are_counted() {
+    let engine = RenderingEngine::with_settings(EngineSettings {
+        min_pool_size: 0,
+        chrome_path: Some("/nonexistent/chrome".into()),
+        ..Default::default()
+    })
+    .unwrap();
+
+    let request = serde_json::from_value(json!({
+        "library": {"name": "apache-echarts", "version": "5.4.0"},
+        "data": {"series": [{"type": "bar", "data": [1, 2, 3]}]},
+        "options": {"width": 400, "height": 300, "format": "png"}
+    }))
+    .unwrap();
+    assert!(engine.render(request).await.is_err());
+
+    let status = engine.health_check();
+    assert_eq!((status.renders_served, status.renders_failed), (0, 1));
+}
+
+#[test]
+fn test_chrome_major_version_parses_product() {
+    use rendering_engine::core::renderer::chrome_major_version;
+
+    assert_eq!(
+        chrome_major_version("HeadlessChrome/120.0.6099.109"),
+        Some(120)
+    );
+    assert_eq!(chrome_major_version("Chrome/88.0.4324.96"), Some(88));
+    assert_eq!(chrome_major_version("Chromium"), None);
+}
@@ -0,0 +1,105 @@
+use rendering_engine::core::cache::RenderCache;
+use rendering_engine::core::renderer::{EngineSettings, RenderingEngine};
+use rendering_engine::schemas::render::RenderRequest;
+use serde_json::{Value, json};
+use std::time::Duration;
+
+fn chart_request(options: Value) -> RenderRequest {
+    serde_json::from_value(json!({
+        "library": {"name": "apache-echarts", "version": "5.4.0"},
+        "data": {"series": [{"type": "bar", "data": [1, 2, 3]}]},
+        "options": options
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_cache_key_ignores_field_order() {
+    let a = chart_request(json!({"width": 400, "height": 300, "format": "png"}));
+    let b = chart_request(json!({"format": "png", "height": 300, "width": 400}));
+    let c = chart_request(json!({"width": 400, "height": 300, "format": "jpeg"}));
+    let base64 =
+        chart_request(json!({"width": 400, "height": 300, "format": "png", "return_base64": true}));
+
+    assert_eq!(RenderCache::key(&a), RenderCache::key(&b));
+    assert_eq!(RenderCache::key(&a), RenderCache::key(&base64));
+    assert_ne!(RenderCache::key(&a), RenderCache::key(&c));
+}
+
+#[test]
+fn test_cache_evicts_least_recently_used() {
+    let cache = RenderCache::new(2, Duration::from_secs(60));
+    let key = |format: &str| RenderCache::key(&chart_request(json!({"format": format})));
+
+    cache.insert(key("png"), b"png".to_vec());
+    cache.insert(key("jpeg"), b"jpeg".to_vec());
+    assert!(cache.get(&key("png")).is_some());
+    cache.insert(key("webp"), b"webp".to_vec());
+
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(&key("png")).as_deref(), Some(&b"png"[..]));
+    assert!(cache.get(&key("jpeg")).is_none(), "jpeg was least recent");
+    assert_eq!((cache.hits(), cache.misses()), (2, 1));
+}
+
+#[test]
+fn test_cache_entries_expire_after_ttl() {
+    let cache = RenderCache::new(10, Duration::from_millis(50));
+    let key = RenderCache::key(&chart_request(json!({"format": "png"})));
+
+    cache.insert(key, b"png".to_vec());
+    assert!(cache.get(&key).is_some());
+    std::thread::sleep(Duration::from_millis(100));
+    assert!(cache.get(&key).is_none());
+    assert!(cache.is_empty());
+}
+
+#[tokio::test]
+async fn test_identical_renders_served_from_cache() {
+    let engine = RenderingEngine::with_settings(EngineSettings {
+        cache_max_entries: 10,
+        ..Default::default()
+    })
+    .expect("Failed to initialize rendering engine");
+    let request = chart_request(json!({"width": 400, "height": 300, "format": "png"}));
+
+    let first = engine.render(request.clone()).await.unwrap();
+    let base64 = engine.render_base64(request).await.unwrap();
+
+    let status = engine.health_check();
+    assert_eq!(
+        status.renders_served, 1,
+        "Second render should not hit Chrome"
+    );
+    assert_eq!((status.cache_hits, status.cache_misses), (1, 1));
+    assert_eq!(
+        base64.data,
+        base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &first)
+    );
+}
+
+#[tokio::test]
+async fn test_no_cache_always_renders() {
+    let engine = RenderingEngine::with_settings(EngineSettings {
+        cache_max_entries: 10,
+        ..Default::default()
+    })
+    .expect("Failed to initialize rendering engine");
+    let request = chart_request(json!({
+        "width": 400,
+        "height": 300,
+        "format": "png",
+        "no_cache": true
+    }));
+
+    engine.render(request.clone()).await.unwrap();
+    engine.render(request).await.unwrap();
+
+    let status = engine.health_check();
+    assert_eq!(status.renders_served, 2, "Both renders should hit Chrome");
+    assert_eq!((status.cache_hits, status.cache_misses), (0, 0));
+    assert_eq!(
+        status.cache_entries, 0,
+        "no_cache output should not be stored"
+    );
+}
@@ -0,0 +1,46 @@
+use rendering_engine::core::registry;
+use rendering_engine::core::renderer::{CaptureTimeout, RenderingEngine};
+use rendering_engine::schemas::render::RenderRequest;
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+// Reports ready, then blocks the page's main thread forever right after the
+// engine has seen the ready flag, so the following capture hangs
+const WEDGED_TEMPLATE: &str = r##"{
+    "wedged": {
+        "cdn_url": "https://cdn.jsdelivr.net/npm/echarts@{version}/dist/echarts.min.js",
+        "wait_selector": "#render-container",
+        "init_script": "let wedged = false; Object.defineProperty(window, 'renderReady', { configurable: true, set() {}, get() { if (!wedged) { wedged = true; setTimeout(() => { while (true) {} }, 0); } return true; } });"
+    }
+}"##;
+
+#[tokio::test]
+async fn test_hung_capture_times_out() {
+    let path = std::env::temp_dir().join(format!("{}-wedged.json", std::process::id()));
+    std::fs::write(&path, WEDGED_TEMPLATE).unwrap();
+    registry::reload(&path).expect("Wedged template should load");
+
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+    let request: RenderRequest = serde_json::from_value(json!({
+        "library": {"name": "wedged", "version": "5.4.0"},
+        "data": {},
+        "options": {"width": 400, "height": 300, "format": "pdf", "timeout_ms": 3000}
+    }))
+    .unwrap();
+
+    let start = Instant::now();
+    let error = engine
+        .render(request)
+        .await
+        .expect_err("Capture should time out");
+
+    assert!(error.is::<CaptureTimeout>(), "Unexpected error: {}", error);
+    assert!(
+        start.elapsed() < Duration::from_secs(10),
+        "Capture should give up near timeout_ms, took {:?}",
+        start.elapsed()
+    );
+
+    std::fs::remove_file(path).ok();
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use poem::http::StatusCode;
+use serde_json::{Value, json};
+
+fn request(cdn_url: &str) -> Value {
+    json!({
+        "library": {"name": "apache-echarts", "version": "5.4.0", "cdn_url": cdn_url},
+        "data": {"series": [{"type": "bar", "data": [1, 2, 3]}]},
+        "options": {"width": 400, "height": 300, "format": "png"}
+    })
+}
+
+#[tokio::test]
+async fn test_cdn_url_checked_against_allowed_cdn_hosts() {
+    let cli = common::client(json!({"allowed_cdn_hosts": "cdn.example.com, Assets.Example.org"}));
+
+    let resp = cli
+        .post("/render")
+        .body_json(&request(
+            "https://cdn.jsdelivr.net/npm/echarts@5.4.0/dist/echarts.min.js",
+        ))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+    let body: Value = resp.json().await.value().deserialize();
+    let message = body["message"].as_str().unwrap();
+    assert!(
+        message.contains("'cdn.jsdelivr.net' not allowed"),
+        "Unexpected message: {}",
+        message
+    );
+
+    for allowed in [
+        "https://cdn.example.com/echarts.min.js",
+        "https://assets.example.org/echarts.min.js",
+    ] {
+        let resp = cli
+            .post("/render")
+            .body_json(&request(allowed))
+            .send()
+            .await;
+        resp.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
+
+#[tokio::test]
+async fn test_cdn_url_defaults_to_public_cdns() {
+    let cli = common::client(json!({}));
+
+    let resp = cli
+        .post("/render")
+        .body_json(&request("https://example.com/echarts.min.js"))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+
+    let resp = cli
+        .post("/render")
+        .body_json(&request(
+            "https://unpkg.com/echarts@5.4.0/dist/echarts.min.js",
+        ))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn test_font_urls_checked_against_allowed_hosts() {
+    let cli = common::client(json!({"allowed_cdn_hosts": "cdn.example.com"}));
+    let fonts_request = |url: &str| {
+        let mut payload = request("https://cdn.example.com/echarts.min.js");
+        payload["options"]["fonts"] = json!([{"family": "Brand", "url": url}]);
+        payload
+    };
+
+    let resp = cli
+        .post("/render")
+        .body_json(&fonts_request("https://tracker.example.net/brand.woff2"))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+    let body: Value = resp.json().await.value().deserialize();
+    let message = body["message"].as_str().unwrap();
+    assert!(
+        message
+            .starts_with("options.fonts.0 rejected: font host 'tracker.example.net' not allowed"),
+        "Unexpected message: {}",
+        message
+    );
+
+    for allowed in [
+        "https://cdn.example.com/brand.woff2",
+        "https://fonts.gstatic.com/s/inter/v13/inter.woff2",
+    ] {
+        let resp = cli
+            .post("/render")
+            .body_json(&fonts_request(allowed))
+            .send()
+            .await;
+        resp.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
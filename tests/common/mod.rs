@@ -0,0 +1,47 @@
+// Each test crate uses its own subset of these helpers
+#![allow(dead_code)]
+
+use poem::test::TestClient;
+use rendering_engine::core::renderer::{EngineSettings, RenderingEngine};
+use rendering_engine::settings::Config;
+use rendering_engine::{AppState, init_openapi_route};
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+/// Settings in `config` laid over a minimal file config.
+pub fn config(config: Value) -> Config {
+    let mut settings = json!({"env": "file", "host": "localhost", "port": 8080});
+    if let (Some(settings), Some(overrides)) = (settings.as_object_mut(), config.as_object()) {
+        settings.extend(overrides.clone());
+    }
+    serde_json::from_value(settings).unwrap()
+}
+
+/// The API over an engine whose lazy pool points at a missing binary, so
+/// requests are rejected before rendering or fail fast with 503.
+pub fn client(config: Value) -> TestClient<impl poem::Endpoint> {
+    let config = self::config(config);
+    let engine = RenderingEngine::with_settings(EngineSettings {
+        min_pool_size: 0,
+        chrome_path: Some("/nonexistent/chrome".into()),
+        ..EngineSettings::from(&config)
+    })
+    .unwrap();
+    with_engine(engine, config)
+}
+
+/// The API over `engine`, for tests that need a working browser.
+pub fn client_with_engine(
+    engine: RenderingEngine,
+    config: Value,
+) -> TestClient<impl poem::Endpoint> {
+    with_engine(engine, self::config(config))
+}
+
+fn with_engine(engine: RenderingEngine, config: Config) -> TestClient<impl poem::Endpoint> {
+    let app_state = Arc::new(AppState {
+        engine: Arc::new(engine),
+        storage: None,
+    });
+    TestClient::new(init_openapi_route(app_state, &config))
+}
@@ -0,0 +1,110 @@
+use rendering_engine::settings::Config;
+use serde_json::json;
+
+#[test]
+fn test_config_debug_redacts_secrets() {
+    let config: Config = serde_json::from_value(json!({
+        "env": "file",
+        "host": "localhost",
+        "port": 8080,
+        "admin_api_key": "admin-secret",
+        "api_key": "api-secret",
+        "storage_bucket": "renders",
+        "storage_access_key_id": "AKIAEXAMPLE",
+        "storage_secret_access_key": "storage-secret"
+    }))
+    .unwrap();
+
+    let logged = format!("{:?}", config);
+    for secret in [
+        "admin-secret",
+        "api-secret",
+        "AKIAEXAMPLE",
+        "storage-secret",
+    ] {
+        assert!(!logged.contains(secret), "{} leaked: {}", secret, logged);
+    }
+    assert!(
+        logged.contains("renders"),
+        "non-secret settings stay visible"
+    );
+    assert!(logged.contains("<redacted>"));
+}
+
+#[test]
+fn test_pool_sizes_come_from_config() {
+    use rendering_engine::core::renderer::EngineSettings;
+
+    let config: Config = serde_json::from_value(json!({
+        "env": "file",
+        "host": "localhost",
+        "port": 8080,
+        "min_pool_size": 2,
+        "max_pool_size": 4,
+        "max_concurrent": 6
+    }))
+    .unwrap();
+
+    let settings = EngineSettings::from(&config);
+    assert_eq!(
+        (
+            settings.min_pool_size,
+            settings.max_pool_size,
+            settings.max_concurrent
+        ),
+        (2, 4, 6)
+    );
+}
+
+#[test]
+fn test_min_pool_size_above_max_fails_fast() {
+    use rendering_engine::core::renderer::{EngineSettings, RenderingEngine};
+
+    let error = RenderingEngine::with_settings(EngineSettings {
+        min_pool_size: 5,
+        max_pool_size: 2,
+        ..Default::default()
+    })
+    .err()
+    .expect("min_pool_size > max_pool_size should be rejected");
+
+    assert_eq!(
+        error.to_string(),
+        "min_pool_size (5) must not exceed max_pool_size (2)"
+    );
+}
+
+#[test]
+fn test_chrome_launch_settings_come_from_config() {
+    use rendering_engine::core::renderer::EngineSettings;
+
+    let settings = |chrome_path: &str| {
+        EngineSettings::from(
+            &serde_json::from_value::<Config>(json!({
+                "env": "file",
+                "host": "localhost",
+                "port": 8080,
+                "chrome_path": chrome_path,
+                "extra_chrome_args": " --single-process  --window-size=800,600 "
+            }))
+            .unwrap(),
+        )
+    };
+
+    let missing = settings("/nonexistent/chromium");
+    assert_eq!(
+        missing.extra_chrome_args,
+        vec!["--single-process", "--window-size=800,600"]
+    );
+    assert_eq!(
+        missing.check_chrome_path().unwrap_err().to_string(),
+        "chrome_path '/nonexistent/chromium' does not exist"
+    );
+
+    let existing = std::env::current_exe().unwrap();
+    assert!(
+        settings(existing.to_str().unwrap())
+            .check_chrome_path()
+            .is_ok()
+    );
+}
@@ -0,0 +1,100 @@
+use rendering_engine::core::registry;
+use rendering_engine::core::renderer::{ConsoleLog, RenderError, RenderingEngine};
+use rendering_engine::schemas::render::RenderRequest;
+use serde_json::json;
+
+const LOGGING_TEMPLATE: &str = r##"
+[console-logger]
+cdn_url = "https://cdn.jsdelivr.net/npm/echarts@{version}/dist/echarts.min.js"
+wait_selector = "#render-container"
+init_script = """
+    console.log('rendering', {data}.points.length);
+    console.warn('deprecated option');
+    window.renderReady = true;
+"""
+
+[console-failure]
+cdn_url = "https://cdn.jsdelivr.net/npm/echarts@{version}/dist/echarts.min.js"
+wait_selector = "#render-container"
+init_script = """
+    console.log('loading', {data}.points.length, 'points');
+    setTimeout(() => { throw new Error('series.data is undefined'); }, 0);
+    setTimeout(() => { window.renderError = 'chart failed'; }, 50);
+"""
+"##;
+
+fn load_templates() {
+    let path = std::env::temp_dir().join(format!("{}-console.toml", std::process::id()));
+    std::fs::write(&path, LOGGING_TEMPLATE).unwrap();
+    registry::reload(&path).expect("Template should load");
+}
+
+#[tokio::test]
+async fn test_capture_console_returns_page_output() {
+    load_templates();
+
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+    let request: RenderRequest = serde_json::from_value(json!({
+        "library": {"name": "console-logger", "version": "5.4.0"},
+        "data": {"points": [1, 2, 3]},
+        "options": {
+            "width": 200,
+            "height": 200,
+            "format": "png",
+            "return_base64": true,
+            "capture_console": true
+        }
+    }))
+    .unwrap();
+
+    let response = engine.render_base64(request).await.unwrap();
+    let console: Vec<(String, String)> = response
+        .console
+        .expect("console requested")
+        .into_iter()
+        .map(|message| (message.level, message.text))
+        .collect();
+
+    assert_eq!(
+        console,
+        vec![
+            ("log".to_string(), "rendering 3".to_string()),
+            ("warning".to_string(), "deprecated option".to_string()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_failed_render_carries_console_output() {
+    load_templates();
+
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+    let request: RenderRequest = serde_json::from_value(json!({
+        "library": {"name": "console-failure", "version": "5.4.0"},
+        "data": {"points": [1, 2, 3]},
+        "options": {"width": 200, "height": 200, "format": "png"}
+    }))
+    .unwrap();
+
+    let error = engine.render(request).await.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<RenderError>(),
+        Some(RenderError::Initialization(_))
+    ));
+    let transcript = error
+        .downcast_ref::<ConsoleLog>()
+        .expect("console output should be attached")
+        .transcript();
+    assert!(
+        transcript.contains("[log] loading 3 points"),
+        "{}",
+        transcript
+    );
+    assert!(
+        transcript.contains("[error]") && transcript.contains("series.data is undefined"),
+        "{}",
+        transcript
+    );
+}
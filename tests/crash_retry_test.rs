@@ -0,0 +1,88 @@
+use rendering_engine::core::registry;
+use rendering_engine::core::renderer::{EngineSettings, RenderError, RenderingEngine};
+use rendering_engine::schemas::render::RenderRequest;
+use serde_json::json;
+use std::time::Duration;
+
+// Ready well after the test has killed the browser under it
+const SLOW_TEMPLATE: &str = r##"
+[slow-to-render]
+cdn_url = "https://cdn.jsdelivr.net/npm/echarts@{version}/dist/echarts.min.js"
+wait_selector = "#render-container"
+init_script = """
+    setTimeout(() => { window.renderReady = {data}.ready; }, 1500);
+"""
+"##;
+
+fn request() -> RenderRequest {
+    serde_json::from_value(json!({
+        "library": {"name": "slow-to-render", "version": "5.4.0"},
+        "data": {"ready": true},
+        "options": {"width": 200, "height": 200, "format": "png", "timeout_ms": 10000}
+    }))
+    .unwrap()
+}
+
+/// SIGKILL every process this test launched, which are the pool's browsers
+fn kill_browsers() {
+    let parent = std::process::id().to_string();
+    for entry in std::fs::read_dir("/proc").unwrap().flatten() {
+        let Ok(stat) = std::fs::read_to_string(entry.path().join("stat")) else {
+            continue;
+        };
+        // pid (comm) state ppid ...
+        let Some((_, rest)) = stat.rsplit_once(')') else {
+            continue;
+        };
+        if rest.split_whitespace().nth(1) == Some(parent.as_str()) {
+            std::process::Command::new("kill")
+                .arg("-9")
+                .arg(entry.file_name())
+                .status()
+                .ok();
+        }
+    }
+}
+
+async fn render_with_crash(crash_retries: usize) -> anyhow::Result<Vec<u8>> {
+    let engine = RenderingEngine::with_settings(EngineSettings {
+        min_pool_size: 1,
+        max_pool_size: 1,
+        max_concurrent: 1,
+        crash_retries,
+        ..Default::default()
+    })
+    .expect("Failed to initialize engine");
+
+    let render = {
+        let engine = engine.clone();
+        tokio::spawn(async move { engine.render(request()).await })
+    };
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    kill_browsers();
+
+    render.await.unwrap()
+}
+
+#[tokio::test]
+async fn test_render_retried_after_browser_crash() {
+    let path = std::env::temp_dir().join(format!("{}-slow-to-render.toml", std::process::id()));
+    std::fs::write(&path, SLOW_TEMPLATE).unwrap();
+    registry::reload(&path).expect("Template should load");
+
+    // One test, so the two engines' browsers aren't killed by each other
+    let error = render_with_crash(0).await.unwrap_err();
+    assert!(
+        matches!(
+            error.downcast_ref::<RenderError>(),
+            Some(RenderError::BrowserCrashed(_))
+        ),
+        "Unexpected error: {}",
+        error
+    );
+
+    render_with_crash(1)
+        .await
+        .expect("Render should succeed on a fresh browser");
+    std::fs::remove_file(path).ok();
+}
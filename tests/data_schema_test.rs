@@ -0,0 +1,160 @@
+mod common;
+
+use poem::http::StatusCode;
+use rendering_engine::core::data_schema;
+use rendering_engine::core::registry;
+use rendering_engine::core::validation;
+use rendering_engine::schemas::render::RenderRequest;
+use serde_json::{Value, json};
+
+fn request(library: Value, data: Value) -> RenderRequest {
+    serde_json::from_value(json!({
+        "library": library,
+        "data": data,
+        "options": {"width": 400, "height": 300, "format": "png"}
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_builtin_schemas_reject_malformed_data() {
+    let echarts = json!({"name": "apache-echarts", "version": "5.4.0"});
+    let summary = |library: &Value, data: Value| {
+        validation::validate_request(&request(library.clone(), data)).summary()
+    };
+
+    assert_eq!(
+        summary(&echarts, json!({"xAxis": {}})),
+        "body.data.series: series is required"
+    );
+    assert_eq!(
+        summary(&echarts, json!({"series": "bar"})),
+        "body.data.series: must be an array or an object, got a string"
+    );
+    assert_eq!(summary(&echarts, json!({"series": []})), "");
+    assert_eq!(
+        summary(
+            &json!({"name": "chartjs", "version": "4.4.0"}),
+            json!({"data": []})
+        ),
+        "body.data.type: type is required; body.data.data: must be an object, got an array"
+    );
+
+    // A non-object is reported once, by the expects_object check
+    assert_eq!(
+        summary(&echarts, json!([1, 2])),
+        "body.data: library 'apache-echarts' expects data to be a JSON object, got an array"
+    );
+
+    // A custom build may take different data
+    let custom = json!({
+        "name": "apache-echarts",
+        "version": "5.4.0",
+        "cdn_url": "https://cdn.jsdelivr.net/npm/echarts@5.4.0/dist/echarts.min.js"
+    });
+    assert_eq!(summary(&custom, json!({"xAxis": {}})), "");
+}
+
+#[test]
+fn test_schema_checks_nested_items() {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "points": {
+                "type": "array",
+                "minItems": 2,
+                "items": {"type": "object", "required": ["x"], "properties": {"x": {"type": "integer"}}}
+            }
+        }
+    });
+    data_schema::check(&schema).unwrap();
+
+    let errors = data_schema::validate(&json!({"points": [{"x": 1.5}]}), &schema);
+    assert_eq!(
+        errors,
+        vec![
+            (
+                vec!["points".to_string()],
+                "must have at least 2 items, got 1".to_string()
+            ),
+            (
+                vec!["points".to_string(), "0".to_string(), "x".to_string()],
+                "must be an integer, got a number".to_string()
+            ),
+        ]
+    );
+    assert!(data_schema::validate(&json!({"points": [{"x": 1}, {"x": 2}]}), &schema).is_empty());
+}
+
+#[test]
+fn test_unsupported_schema_keywords_fail_to_load() {
+    for (schema, expected) in [
+        (
+            json!({"type": "object", "pattern": "^a"}),
+            "data_schema keyword 'pattern' is not supported",
+        ),
+        (
+            json!({"type": "map"}),
+            "data_schema has unknown type \"map\"",
+        ),
+        (
+            json!({"properties": {"series": {"minItems": -1}}}),
+            "data_schema minItems must be a whole number",
+        ),
+    ] {
+        let error = data_schema::check(&schema).unwrap_err().to_string();
+        assert!(error.starts_with(expected), "{}", error);
+    }
+
+    let path = std::env::temp_dir().join(format!("{}-bad-schema.json", std::process::id()));
+    let templates = json!({
+        "uplot": {
+            "cdn_url": "https://cdn.jsdelivr.net/npm/uplot@{version}/dist/uPlot.iife.min.js",
+            "wait_selector": "#render-container",
+            "init_script": "window.renderReady = true;",
+            "data_schema": {"type": "object", "oneOf": []}
+        }
+    });
+    std::fs::write(&path, templates.to_string()).unwrap();
+    let error = match registry::reload(&path) {
+        Ok(_) => panic!("Template with an unsupported keyword should not load"),
+        Err(e) => e.to_string(),
+    };
+    std::fs::remove_file(&path).ok();
+    assert!(
+        error.contains("'uplot': data_schema keyword 'oneOf'"),
+        "{}",
+        error
+    );
+}
+
+#[tokio::test]
+async fn test_render_rejects_data_failing_schema_with_422() {
+    // Validation runs before any browser work, so no Chrome is needed
+    let cli = common::client(json!({}));
+
+    let resp = cli
+        .post("/render")
+        .body_json(&json!({
+            "library": {"name": "echarts", "version": "5.4.0"},
+            "data": {"title": {"text": "No series"}},
+            "options": {"width": 400, "height": 300, "format": "png"}
+        }))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    resp.assert_json(json!({
+        "detail": [{"loc": ["body", "data", "series"], "msg": "series is required"}]
+    }))
+    .await;
+
+    let resp = cli.get("/libraries/apache-echarts").send().await;
+    resp.assert_status_is_ok();
+    let body = resp.json().await;
+    body.value()
+        .object()
+        .get("data_schema")
+        .object()
+        .get("required")
+        .assert_string_array(&["series"]);
+}
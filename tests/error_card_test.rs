@@ -0,0 +1,26 @@
+mod common;
+
+use serde_json::json;
+
+#[tokio::test]
+async fn test_error_as_image_returns_card() {
+    // Chrome can't launch, so every render fails
+    let cli = common::client(json!({}));
+
+    let resp = cli
+        .post("/render")
+        .body_json(&json!({
+            "library": {"name": "apache-echarts", "version": "5.4.0"},
+            "data": {"series": [{"type": "bar", "data": [1, 2, 3]}]},
+            "options": {"width": 400, "height": 300, "format": "png", "error_as_image": true}
+        }))
+        .send()
+        .await;
+
+    resp.assert_status_is_ok();
+    resp.assert_header("X-Render-Error", "true");
+    resp.assert_content_type("image/png");
+    let png = resp.0.into_body().into_vec().await.unwrap();
+    let card = image::load_from_memory(&png).unwrap();
+    assert_eq!((card.width(), card.height()), (400, 300));
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use poem::http::StatusCode;
+use serde_json::{Value, json};
+
+fn payload(headers: Value) -> Value {
+    json!({
+        "library": {"name": "apache-echarts", "version": "5.4.0"},
+        "data": {"series": [{"type": "bar", "data": [1, 2, 3]}]},
+        "options": {
+            "width": 400,
+            "height": 300,
+            "format": "png",
+            "inject_fetch_headers": headers
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_fetch_headers_require_host_allowlist() {
+    let cli = common::client(json!({}));
+
+    let resp = cli
+        .post("/render")
+        .body_json(&payload(json!({"Authorization": "Bearer secret"})))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_fetch_headers_reject_invalid_header() {
+    let cli = common::client(json!({"fetch_header_hosts": "api.example.com"}));
+
+    let resp = cli
+        .post("/render")
+        .body_json(&payload(json!({"X-Token": "a\r\nX-Injected: 1"})))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = resp.json().await.value().deserialize();
+    assert_eq!(
+        body["detail"][0]["loc"],
+        json!(["body", "options", "inject_fetch_headers", "X-Token"])
+    );
+}
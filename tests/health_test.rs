@@ -0,0 +1,98 @@
+mod common;
+
+use poem::http::StatusCode;
+use rendering_engine::core::renderer::RenderingEngine;
+use serde_json::{Value, json};
+
+#[tokio::test]
+async fn test_health_reports_configured_max_concurrent() {
+    // Lazy pool, so no browser is needed to report capacity
+    let cli = common::client(json!({"max_pool_size": 10, "max_concurrent": 5}));
+
+    let resp = cli.get("/health").send().await;
+    resp.assert_status_is_ok();
+    let body: Value = resp.json().await.value().deserialize();
+
+    assert_eq!(body["render_slots"]["capacity"], 5);
+    assert_eq!(body["render_slots"]["available"], 5);
+    assert_eq!(body["render_slots"]["utilization_pct"], 0.0);
+}
+
+#[tokio::test]
+async fn test_deep_health_reports_failed_canary() {
+    let cli = common::client(json!({}));
+
+    // The default check never renders, so it stays healthy without a browser
+    let resp = cli.get("/health").send().await;
+    resp.assert_status_is_ok();
+    let body: Value = resp.json().await.value().deserialize();
+    assert!(body.get("canary").is_none());
+
+    let resp = cli.get("/health").query("deep", &true).send().await;
+    resp.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+    let body: Value = resp.json().await.value().deserialize();
+    assert_eq!(body["status"], "unhealthy");
+    assert_eq!(body["canary"]["passed"], false);
+    assert!(
+        body["canary"]["error"]
+            .as_str()
+            .is_some_and(|error| error.contains("No browser available")),
+        "Unexpected canary: {}",
+        body["canary"]
+    );
+}
+
+#[tokio::test]
+async fn test_deep_health_check_renders_canary() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let canary = engine.deep_health_check().await;
+    assert!(canary.passed(), "Canary failed: {:?}", canary.error);
+    assert!(canary.latency > std::time::Duration::ZERO);
+}
+
+#[tokio::test]
+async fn test_liveness_and_readiness_probes() {
+    // Lazy pool at a missing binary: nothing launched yet, so alive, and ready
+    // until a launch fails
+    let cli = common::client(json!({"api_key": "secret"}));
+
+    // Probes don't need the key
+    let resp = cli.get("/health/live").send().await;
+    resp.assert_status_is_ok();
+    resp.assert_json(json!({"status": "alive"})).await;
+
+    let resp = cli.get("/health/ready").send().await;
+    resp.assert_status_is_ok();
+    resp.assert_json(json!({"status": "ready"})).await;
+
+    let resp = cli
+        .post("/render")
+        .header("X-API-Key", "secret")
+        .body_json(&json!({
+            "library": {"name": "apache-echarts", "version": "5.4.0"},
+            "data": {"series": [{"type": "bar", "data": [1, 2, 3]}]},
+            "options": {"width": 400, "height": 300, "format": "png"}
+        }))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+
+    // Launches now back off, so the empty pool no longer counts as ready
+    let resp = cli.get("/health/ready").send().await;
+    resp.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+    resp.assert_json(json!({"status": "not_ready", "reason": "no browser in the pool"}))
+        .await;
+}
+
+#[tokio::test]
+async fn test_ready_with_a_warm_pool() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+    let cli = common::client_with_engine(engine, json!({}));
+
+    let resp = cli.get("/health/ready").send().await;
+    resp.assert_status_is_ok();
+    resp.assert_json(json!({"status": "ready"})).await;
+}
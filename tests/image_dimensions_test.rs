@@ -0,0 +1,111 @@
+use base64::Engine as _;
+use base64::engine::general_purpose;
+use rendering_engine::core::metadata::image_dimensions;
+use rendering_engine::core::renderer::RenderingEngine;
+use rendering_engine::schemas::render::{RenderOptions, RenderRequest};
+use serde_json::json;
+
+#[test]
+fn test_image_dimensions_read_from_header() {
+    let mut png = Vec::new();
+    image::RgbaImage::new(7, 5)
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .unwrap();
+    assert_eq!(image_dimensions(&png, "png"), Some((7, 5)));
+
+    // SOI, a JFIF APP0 segment, then a baseline frame header: 5 rows of 7
+    let mut jpeg = vec![0xff, 0xd8, 0xff, 0xe0, 0x00, 0x10];
+    jpeg.extend_from_slice(b"JFIF\0\x01\x01\x00\x00\x01\x00\x01\x00\x00");
+    jpeg.extend_from_slice(&[0xff, 0xc0, 0x00, 0x11, 0x08, 0x00, 0x05, 0x00, 0x07, 0x03]);
+    assert_eq!(image_dimensions(&jpeg, "jpeg"), Some((7, 5)));
+    assert_eq!(image_dimensions(&jpeg, "jpg"), Some((7, 5)));
+
+    let webp = |chunk: &[u8; 4], data: &[u8]| {
+        let mut bytes = b"RIFF\0\0\0\0WEBP".to_vec();
+        bytes.extend_from_slice(chunk);
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    };
+    let lossy = webp(b"VP8 ", &[0, 0, 0, 0x9d, 0x01, 0x2a, 7, 0, 5, 0]);
+    assert_eq!(image_dimensions(&lossy, "webp"), Some((7, 5)));
+    let lossless_bits: u32 = 6 | (4 << 14);
+    let mut lossless = vec![0x2f];
+    lossless.extend_from_slice(&lossless_bits.to_le_bytes());
+    assert_eq!(
+        image_dimensions(&webp(b"VP8L", &lossless), "webp"),
+        Some((7, 5))
+    );
+    let extended = webp(b"VP8X", &[0, 0, 0, 0, 6, 0, 0, 4, 0, 0]);
+    assert_eq!(image_dimensions(&extended, "webp"), Some((7, 5)));
+
+    assert_eq!(image_dimensions(&png, "jpeg"), None);
+    assert_eq!(image_dimensions(b"<svg/>", "svg"), None);
+}
+
+#[test]
+fn test_output_dimensions_scale_viewport_or_clip() {
+    let options = |value| -> RenderOptions { serde_json::from_value(value).unwrap() };
+
+    assert_eq!(
+        options(json!({"width": 400, "height": 300, "format": "svg"})).output_dimensions(),
+        (400, 300)
+    );
+    assert_eq!(
+        options(json!({
+            "width": 400,
+            "height": 300,
+            "format": "pdf",
+            "device_scale_factor": 1.5
+        }))
+        .output_dimensions(),
+        (600, 450)
+    );
+    assert_eq!(
+        options(json!({
+            "width": 400,
+            "height": 300,
+            "format": "png",
+            "device_scale_factor": 2.0,
+            "clip": {"x": 10, "y": 10, "width": 100, "height": 50}
+        }))
+        .output_dimensions(),
+        (200, 100)
+    );
+}
+
+#[tokio::test]
+async fn test_base64_response_reports_dimensions_and_size() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    // Content taller than the requested viewport, grown by auto_height
+    let request: RenderRequest = serde_json::from_value(json!({
+        "library": {"name": "raw-html"},
+        "data": {"html": "<div style=\"width: 100px; height: 500px; background: red\"></div>"},
+        "options": {
+            "width": 400,
+            "height": 300,
+            "format": "png",
+            "device_scale_factor": 2.0,
+            "auto_height": true,
+            "return_base64": true
+        }
+    }))
+    .unwrap();
+
+    let response = engine.render_base64(request).await.unwrap();
+    let png = general_purpose::STANDARD.decode(&response.data).unwrap();
+    let image = image::load_from_memory(&png).unwrap();
+    assert_eq!(response.size_bytes, png.len() as u64);
+    assert_eq!(
+        (response.width, response.height),
+        (image.width(), image.height())
+    );
+    assert_eq!(response.width, 800);
+    assert!(
+        response.height >= 1000,
+        "auto_height should grow past the viewport, got {}",
+        response.height
+    );
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use poem::http::StatusCode;
+use rendering_engine::core::validation;
+use rendering_engine::schemas::render::RenderRequest;
+use serde_json::{Value, json};
+
+fn request(options: Value) -> Value {
+    let mut payload = json!({
+        "library": {"name": "apache-echarts", "version": "5.4.0"},
+        "data": {"series": [{"type": "bar", "data": [1, 2, 3]}]},
+        "options": {"width": 400, "height": 300, "format": "png"}
+    });
+    payload["options"]
+        .as_object_mut()
+        .unwrap()
+        .extend(options.as_object().unwrap().clone());
+    payload
+}
+
+#[tokio::test]
+async fn test_interactions_checked_before_rendering() {
+    // Valid requests reach the missing browser and get 503
+    let cli = common::client(json!({}));
+
+    let resp = cli
+        .post("/render")
+        .body_json(&request(json!({
+            "capture_pair": true,
+            "interactions": [
+                {"action": "hover", "selector": ".bar"},
+                {"action": "hover"},
+                {"action": "click", "x": 10.0}
+            ]
+        })))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = resp.json().await.value().deserialize();
+    let locations: Vec<&Value> = body["detail"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|error| &error["loc"])
+        .collect();
+    assert_eq!(
+        locations,
+        [
+            &json!(["body", "options", "interactions", "1"]),
+            &json!(["body", "options", "interactions", "2"])
+        ]
+    );
+    assert_eq!(
+        body["detail"][0]["msg"],
+        "either selector or both x and y are required"
+    );
+
+    // Without capture_pair the interactions would never run
+    let resp = cli
+        .post("/render")
+        .body_json(&request(json!({
+            "interactions": [{"action": "click", "selector": ".bar"}]
+        })))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = resp.json().await.value().deserialize();
+    assert_eq!(
+        body["detail"][0]["loc"],
+        json!(["body", "options", "interactions"])
+    );
+
+    // Unknown actions fail the schema's pattern
+    let resp = cli
+        .post("/render")
+        .body_json(&request(json!({
+            "capture_pair": true,
+            "interactions": [{"action": "drag", "selector": ".bar"}]
+        })))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+
+    let resp = cli
+        .post("/render")
+        .body_json(&request(json!({
+            "capture_pair": true,
+            "interactions": [
+                {"action": "hover", "selector": ".bar"},
+                {"action": "click", "x": 10.0, "y": 20.0}
+            ]
+        })))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[test]
+fn test_interaction_delay_is_bounded() {
+    let request: RenderRequest = serde_json::from_value(request(json!({
+        "capture_pair": true,
+        "interactions": [{"action": "hover", "selector": ".bar"}],
+        "interaction_delay_ms": 3_600_000
+    })))
+    .unwrap();
+    assert_eq!(
+        validation::validate_request(&request).summary(),
+        "body.options.interaction_delay_ms: interaction_delay_ms must be at most 5000, got 3600000"
+    );
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use poem::http::StatusCode;
+use serde_json::{Value, json};
+
+#[tokio::test]
+async fn test_library_details() {
+    let cli = common::client(json!({}));
+
+    // Aliases resolve to the canonical entry
+    let resp = cli.get("/libraries/echarts").send().await;
+    resp.assert_status_is_ok();
+    let body: Value = resp.json().await.value().deserialize();
+    assert_eq!(body["name"], "apache-echarts");
+    assert_eq!(
+        body["cdn_url"],
+        "https://cdn.jsdelivr.net/npm/echarts@{version}/dist/echarts.min.js"
+    );
+    assert_eq!(body["default_version"], "5.4.0");
+    assert_eq!(body["wait_selector"], "#render-container");
+    assert_eq!(body["themes"], json!(["dark", "light"]));
+
+    let resp = cli.get("/libraries/chartjs").send().await;
+    let body: Value = resp.json().await.value().deserialize();
+    assert_eq!(body["wait_selector"], "#chart-canvas");
+    assert_eq!(body["plugins"], json!(["annotation", "datalabels"]));
+
+    let resp = cli.get("/libraries/VegaLite").send().await;
+    let body: Value = resp.json().await.value().deserialize();
+    assert_eq!(body["name"], "vega-lite");
+    assert_eq!(body["default_version"], "5.21.0");
+}
+
+#[tokio::test]
+async fn test_libraries_list_default_versions() {
+    let resp = common::client(json!({})).get("/libraries").send().await;
+    resp.assert_status_is_ok();
+    let body: Value = resp.json().await.value().deserialize();
+    let version = |name: &str| {
+        body.as_array()
+            .unwrap()
+            .iter()
+            .find(|library| library["name"] == name)
+            .map(|library| library["version"].clone())
+            .unwrap()
+    };
+
+    assert_eq!(version("chartjs"), "4.4.0");
+    assert_eq!(version("mermaid"), "11.4.1");
+    // No library script, so nothing to version
+    assert_eq!(version("raw-html"), Value::Null);
+}
+
+#[tokio::test]
+async fn test_unknown_library_details_returns_404() {
+    let resp = common::client(json!({}))
+        .get("/libraries/no-such-library")
+        .send()
+        .await;
+
+    resp.assert_status(StatusCode::NOT_FOUND);
+    let body: Value = resp.json().await.value().deserialize();
+    assert_eq!(
+        body["message"],
+        "Library 'no-such-library' is not registered"
+    );
+}
@@ -0,0 +1,304 @@
+use rendering_engine::core::renderer::{RenderError, RenderingEngine};
+use rendering_engine::schemas::render::RenderRequest;
+use serde_json::{Value, json};
+
+const PNG_SIGNATURE: &[u8] = &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+fn request(library: &str, version: &str, data: Value) -> RenderRequest {
+    serde_json::from_value(json!({
+        "library": {
+            "name": library,
+            "version": version
+        },
+        "data": data,
+        "options": {
+            "width": 600,
+            "height": 400,
+            "format": "png"
+        }
+    }))
+    .unwrap()
+}
+
+fn region(name: &str, x: f64) -> Value {
+    json!({
+        "type": "Feature",
+        "properties": {"name": name},
+        "geometry": {
+            "type": "Polygon",
+            "coordinates": [[[x, 0.0], [x + 10.0, 0.0], [x + 10.0, 10.0], [x, 10.0], [x, 0.0]]]
+        }
+    })
+}
+
+#[tokio::test]
+async fn test_echarts_map_renders_two_region_choropleth() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let data = json!({
+        "geojson": {
+            "type": "FeatureCollection",
+            "features": [region("West", 0.0), region("East", 10.0)]
+        },
+        "mapName": "regions",
+        "option": {
+            "visualMap": {"min": 0, "max": 100, "show": false},
+            "series": [{
+                "type": "map",
+                "map": "regions",
+                "data": [
+                    {"name": "West", "value": 10},
+                    {"name": "East", "value": 90}
+                ]
+            }]
+        }
+    });
+
+    let png = engine
+        .render(request("echarts-map", "5.4.0", data))
+        .await
+        .expect("Choropleth render failed");
+
+    assert!(png.starts_with(PNG_SIGNATURE));
+}
+
+/// 1x1 solid red PNG, served as every tile so the map is deterministic
+const RED_TILE: &str = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAIAAACQd1PeAAAADElEQVR4nGP4z8AAAAMBAQDJ/pLvAAAAAElFTkSuQmCC";
+
+#[tokio::test]
+async fn test_leaflet_waits_for_tiles() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let data = json!({
+        "center": [51.505, -0.09],
+        "zoom": 13,
+        "tileUrl": RED_TILE,
+        "attribution": "",
+        "markers": [{"lat": 51.51, "lng": -0.1, "label": "Marker"}]
+    });
+
+    let png = engine
+        .render(request("leaflet", "1.9.4", data))
+        .await
+        .expect("Leaflet render failed");
+
+    let image = image::load_from_memory(&png).unwrap().to_rgba8();
+    assert_eq!(
+        image.get_pixel(300, 200).0,
+        [255, 0, 0, 255],
+        "Map center should show a loaded tile"
+    );
+}
+
+#[tokio::test]
+async fn test_vega_lite_renders_and_reports_invalid_specs() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let spec = json!({
+        "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+        "data": {"values": [{"x": "A", "y": 10}, {"x": "B", "y": 30}]},
+        "mark": "bar",
+        "encoding": {
+            "x": {"field": "x", "type": "nominal"},
+            "y": {"field": "y", "type": "quantitative"}
+        }
+    });
+    let png = engine
+        .render(request("vega-lite", "5.21.0", spec))
+        .await
+        .expect("Vega-Lite render failed");
+    assert!(png.starts_with(PNG_SIGNATURE));
+
+    // vega-embed rejects, which must fail the render rather than time out
+    let error = engine
+        .render(request("vega-lite", "5.21.0", json!({"mark": 42})))
+        .await
+        .unwrap_err();
+    assert!(
+        error
+            .downcast_ref::<RenderError>()
+            .is_some_and(|e| matches!(e, RenderError::Initialization(_))),
+        "Unexpected error: {}",
+        error
+    );
+}
+
+#[tokio::test]
+async fn test_apexcharts_round_trip() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let data = json!({
+        "chart": {"type": "bar"},
+        "series": [{"name": "sales", "data": [30, 200, 100, 400]}],
+        "xaxis": {"categories": ["Q1", "Q2", "Q3", "Q4"]}
+    });
+    let png = engine
+        .render(request("apexcharts", "3.54.1", data.clone()))
+        .await
+        .expect("ApexCharts render failed");
+    let image = image::load_from_memory(&png).expect("Output should be a valid image");
+    assert_eq!((image.width(), image.height()), (600, 400));
+
+    let mut svg_request = request("apexcharts", "3.54.1", data);
+    svg_request.options.format = "svg".to_string();
+    let svg = engine
+        .render(svg_request)
+        .await
+        .expect("ApexCharts svg failed");
+    let svg = String::from_utf8(svg).unwrap();
+    assert!(svg.starts_with("<svg") && svg.contains("apexcharts-svg"));
+}
+
+#[tokio::test]
+async fn test_billboardjs_renders() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let data = json!({
+        "data": {"columns": [["sales", 30, 200, 100, 400]], "type": "bar"}
+    });
+    let png = engine
+        .render(request("billboardjs", "3.14.0", data))
+        .await
+        .expect("billboard.js render failed");
+
+    assert!(png.starts_with(PNG_SIGNATURE));
+}
+
+#[tokio::test]
+async fn test_highcharts_renders() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let data = json!({
+        "chart": {"type": "column"},
+        "title": {"text": "Sales"},
+        "xAxis": {"categories": ["Q1", "Q2", "Q3", "Q4"]},
+        "series": [{"name": "sales", "data": [30, 200, 100, 400]}]
+    });
+    let png = engine
+        .render(request("highcharts", "11.4.8", data))
+        .await
+        .expect("Highcharts render failed");
+
+    assert!(png.starts_with(PNG_SIGNATURE));
+}
+
+#[tokio::test]
+async fn test_chartjs_renders_with_plugins() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let mut request = request(
+        "chartjs",
+        "4.4.0",
+        json!({
+            "type": "bar",
+            "data": {"labels": ["Q1", "Q2"], "datasets": [{"data": [30, 200]}]},
+            "options": {
+                "plugins": {
+                    "datalabels": {"color": "#000"},
+                    "annotation": {"annotations": {"goal": {"type": "line", "yMin": 150, "yMax": 150}}}
+                }
+            }
+        }),
+    );
+    request.library.plugins = Some(vec!["datalabels".to_string(), "annotation".to_string()]);
+    let png = engine
+        .render(request)
+        .await
+        .expect("Chart.js render with plugins failed");
+
+    assert!(png.starts_with(PNG_SIGNATURE));
+}
+
+#[tokio::test]
+async fn test_plotly_renders() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let data = json!({
+        "traces": [{"type": "scatter", "x": [1, 2, 3, 4], "y": [10, 15, 13, 17]}],
+        "layout": {"title": {"text": "Sales"}}
+    });
+    let png = engine
+        .render(request("plotly", "2.35.2", data))
+        .await
+        .expect("Plotly render failed");
+
+    assert!(png.starts_with(PNG_SIGNATURE));
+}
+
+#[tokio::test]
+async fn test_highcharts_line_series_renders() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let data = json!({"series": [{"type": "line", "data": [1, 3, 2, 4]}]});
+    let png = engine
+        .render(request("highcharts", "11.4.8", data))
+        .await
+        .expect("Highcharts line render failed");
+
+    assert!(!png.is_empty());
+    assert!(png.starts_with(PNG_SIGNATURE));
+}
+
+#[tokio::test]
+async fn test_mermaid_renders_and_reports_syntax_errors() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let png = engine
+        .render(request(
+            "mermaid",
+            "11.4.1",
+            json!({"definition": "graph TD; Start --> Stop"}),
+        ))
+        .await
+        .expect("Mermaid render failed");
+    assert!(png.starts_with(PNG_SIGNATURE));
+
+    let error = engine
+        .render(request(
+            "mermaid",
+            "11.4.1",
+            json!({"definition": "graph TD; Start -->"}),
+        ))
+        .await
+        .expect_err("Invalid definition should fail");
+    assert!(error.to_string().contains("Render initialization failed"));
+}
+
+#[tokio::test]
+async fn test_raw_html_renders_markup_and_waits_for_ready_expression() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    // The script recolors the box late; readyExpression holds the capture
+    let data = json!({
+        "html": "<div id=\"box\" style=\"width:600px;height:400px;background:red\"></div><script>setTimeout(() => { box.style.background = 'rgb(0, 0, 255)'; box.dataset.done = '1'; }, 200);</script>",
+        "readyExpression": "document.getElementById('box').dataset.done === '1'"
+    });
+    let png = engine
+        .render(request("raw-html", "1.0.0", data))
+        .await
+        .expect("raw-html render failed");
+
+    let image = image::load_from_memory(&png).unwrap().to_rgba8();
+    assert_eq!(image.get_pixel(300, 200).0, [0, 0, 255, 255]);
+
+    let error = engine
+        .render(request("raw-html", "1.0.0", json!({"markup": "<p>hi</p>"})))
+        .await
+        .unwrap_err();
+    assert!(
+        error.to_string().contains("raw-html requires data.html"),
+        "Unexpected error: {}",
+        error
+    );
+}
@@ -0,0 +1,46 @@
+use poem::listener::Acceptor;
+use rendering_engine::listener::LimitedAcceptor;
+use rendering_engine::settings::Config;
+use serde_json::json;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpStream;
+
+#[tokio::test]
+async fn test_connections_over_limit_are_rejected() {
+    let config: Config = serde_json::from_value(json!({
+        "env": "file",
+        "host": "127.0.0.1",
+        "port": 0,
+        "max_connections": 1,
+        "accept_backlog": 16
+    }))
+    .unwrap();
+    let mut acceptor = LimitedAcceptor::bind(&config).unwrap();
+    let addr = acceptor.local_addr()[0].as_socket_addr().cloned().unwrap();
+
+    let _first = TcpStream::connect(addr).await.unwrap();
+    let (held, ..) = acceptor.accept().await.unwrap();
+
+    // Rejected while the first connection is open
+    let mut second = TcpStream::connect(addr).await.unwrap();
+    let accepting = tokio::spawn(async move {
+        let result = acceptor.accept().await.map(|(io, ..)| io);
+        (acceptor, result)
+    });
+    let mut response = Vec::new();
+    tokio::time::timeout(Duration::from_secs(5), second.read_to_end(&mut response))
+        .await
+        .expect("Rejected connection should be closed")
+        .unwrap();
+    assert!(response.starts_with(b"HTTP/1.1 503"));
+
+    // Accepted again once the slot is free
+    drop(held);
+    let _third = TcpStream::connect(addr).await.unwrap();
+    let (_, result) = tokio::time::timeout(Duration::from_secs(5), accepting)
+        .await
+        .expect("Third connection should be accepted")
+        .unwrap();
+    assert!(result.is_ok());
+}
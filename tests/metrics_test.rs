@@ -0,0 +1,42 @@
+mod common;
+
+use serde_json::json;
+
+#[tokio::test]
+async fn test_metrics_count_failed_render() {
+    // Every launch fails, so the render is counted as browser_unavailable
+    let cli = common::client(json!({}));
+
+    cli.post("/render")
+        .body_json(&json!({
+            "library": {"name": "echarts", "version": "5.4.0"},
+            "data": {"series": [{"type": "bar", "data": [1, 2, 3]}]},
+            "options": {"width": 400, "height": 300, "format": "jpeg"}
+        }))
+        .send()
+        .await;
+
+    let resp = cli.get("/metrics").send().await;
+    resp.assert_status_is_ok();
+    resp.assert_content_type("text/plain; version=0.0.4; charset=utf-8");
+    let body = resp.0.into_body().into_string().await.unwrap();
+
+    for line in [
+        "rendering_engine_renders_total 1",
+        // Aliases are counted under the registered name
+        "rendering_engine_renders_by_library_total{library=\"apache-echarts\"} 1",
+        "rendering_engine_renders_by_format_total{format=\"jpeg\"} 1",
+        "rendering_engine_render_failures_total{type=\"browser_unavailable\"} 1",
+        "rendering_engine_render_duration_seconds_count 0",
+        "rendering_engine_render_duration_seconds_bucket{le=\"+Inf\"} 0",
+        "rendering_engine_browser_pool_size{raster_engine=\"default\"} 0",
+        "# TYPE rendering_engine_render_duration_seconds histogram",
+    ] {
+        assert!(
+            body.lines().any(|l| l == line),
+            "Missing '{}' in:\n{}",
+            line,
+            body
+        );
+    }
+}
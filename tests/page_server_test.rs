@@ -0,0 +1,78 @@
+use rendering_engine::core::page_server;
+use rendering_engine::core::renderer::RenderingEngine;
+use rendering_engine::schemas::render::RenderRequest;
+use serde_json::json;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+
+/// Status line and body of a GET for `url`
+fn get(url: &str) -> (String, String) {
+    let url = url::Url::parse(url).unwrap();
+    let mut stream = TcpStream::connect((url.host_str().unwrap(), url.port().unwrap())).unwrap();
+    write!(
+        stream,
+        "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n",
+        url.path()
+    )
+    .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    (head.lines().next().unwrap().to_string(), body.to_string())
+}
+
+#[test]
+fn test_served_page_is_removed_on_drop() {
+    let page = page_server::serve("<p>large page</p>".to_string()).unwrap();
+    let other = page_server::serve("<p>other page</p>".to_string()).unwrap();
+    assert!(page.url.starts_with("http://127.0.0.1:"));
+    assert_ne!(page.url, other.url);
+
+    assert_eq!(
+        get(&page.url),
+        (
+            "HTTP/1.1 200 OK".to_string(),
+            "<p>large page</p>".to_string()
+        )
+    );
+
+    let url = page.url.clone();
+    drop(page);
+    assert_eq!(get(&url).0, "HTTP/1.1 404 Not Found");
+    assert_eq!(get(&other.url).1, "<p>other page</p>");
+}
+
+#[tokio::test]
+async fn test_page_above_data_url_limit_renders() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    // ~2 MiB of scene, over the data: URL limit once base64 encoded
+    let mut shapes: Vec<_> = (0..20000)
+        .map(|i| {
+            json!({
+                "type": "Rect",
+                "config": {"x": i % 400, "y": 250, "width": 1, "height": 1, "fill": "#000001"}
+            })
+        })
+        .collect();
+    shapes.push(json!({
+        "type": "Rect",
+        "config": {"x": 0, "y": 0, "width": 400, "height": 200, "fill": "red"}
+    }));
+    let request: RenderRequest = serde_json::from_value(json!({
+        "library": {"name": "konvajs", "version": "9.2.0"},
+        "data": {"shapes": shapes},
+        "options": {"width": 400, "height": 300, "format": "png"}
+    }))
+    .unwrap();
+    assert!(engine.page_html(&request).unwrap().len() > 1024 * 1024);
+
+    let png = engine
+        .render(request)
+        .await
+        .expect("Large scene render failed");
+    let image = image::load_from_memory(&png).unwrap().to_rgba8();
+    assert_eq!(image.get_pixel(200, 100).0, [255, 0, 0, 255]);
+}
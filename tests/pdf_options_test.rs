@@ -0,0 +1,178 @@
+use rendering_engine::core::renderer::RenderingEngine;
+use rendering_engine::schemas::render::RenderRequest;
+use serde_json::{Value, json};
+
+fn tall_pdf_request(pdf_options: Value) -> RenderRequest {
+    serde_json::from_value(json!({
+        "library": {
+            "name": "konvajs",
+            "version": "9.2.0"
+        },
+        "data": {
+            "shapes": [{
+                "type": "Rect",
+                "config": {"x": 0, "y": 0, "width": 800, "height": 4000, "fill": "steelblue"}
+            }]
+        },
+        "options": {
+            "width": 800,
+            "height": 4000,
+            "format": "pdf",
+            "pdf_options": pdf_options
+        }
+    }))
+    .unwrap()
+}
+
+fn count_pages(pdf: &[u8]) -> usize {
+    let count = |needle: &[u8]| pdf.windows(needle.len()).filter(|w| *w == needle).count();
+    count(b"/Type /Page") - count(b"/Type /Pages")
+}
+
+#[tokio::test]
+async fn test_pdf_page_ranges_select_fewer_pages() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let all_pages = engine.render(tall_pdf_request(json!({}))).await.unwrap();
+    let first_page = engine
+        .render(tall_pdf_request(json!({"page_ranges": "1"})))
+        .await
+        .unwrap();
+
+    let all_count = count_pages(&all_pages);
+    let selected_count = count_pages(&first_page);
+    assert!(
+        all_count > 1,
+        "Tall content should span multiple pages, got {}",
+        all_count
+    );
+    assert_eq!(
+        selected_count, 1,
+        "page_ranges \"1\" should print a single page"
+    );
+}
+
+#[tokio::test]
+async fn test_embed_data_attaches_request_data() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let mut request = tall_pdf_request(json!({}));
+    request.options.embed_data = Some(true);
+    let pdf = engine.render(request).await.unwrap();
+
+    let document = lopdf::Document::load_mem(&pdf).expect("Output should be a valid PDF");
+    let names = document
+        .catalog()
+        .unwrap()
+        .get(b"Names")
+        .and_then(|names| document.dereference(names))
+        .and_then(|(_, names)| names.as_dict())
+        .expect("Catalog should have a Names dictionary");
+    assert!(
+        names.has(b"EmbeddedFiles"),
+        "embed_data should add an EmbeddedFiles name tree"
+    );
+}
+
+#[tokio::test]
+async fn test_render_to_writer_streams_pdf() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let mut streamed = Vec::new();
+    let written = engine
+        .render_to_writer(tall_pdf_request(json!({})), &mut streamed)
+        .await
+        .unwrap();
+    assert_eq!(written, streamed.len() as u64);
+
+    let rendered = engine.render(tall_pdf_request(json!({}))).await.unwrap();
+    lopdf::Document::load_mem(&streamed).expect("Streamed output should be a valid PDF");
+    assert_eq!(count_pages(&streamed), count_pages(&rendered));
+}
+
+#[tokio::test]
+async fn test_echarts_svg_renderer_keeps_pdf_vector() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let request = |renderer: &str| -> RenderRequest {
+        serde_json::from_value(json!({
+            "library": {"name": "apache-echarts", "version": "5.4.0"},
+            "data": {
+                "xAxis": {"data": ["A", "B", "C"]},
+                "yAxis": {},
+                "series": [{"type": "bar", "data": [10, 20, 30]}]
+            },
+            "options": {"width": 400, "height": 300, "format": "pdf", "renderer": renderer}
+        }))
+        .unwrap()
+    };
+    let has_image = |pdf: &[u8]| pdf.windows(14).any(|w| w == b"/Subtype /Image");
+
+    let canvas = engine.render(request("canvas")).await.unwrap();
+    let svg = engine.render(request("svg")).await.unwrap();
+    assert!(has_image(&canvas), "canvas renderer embeds a bitmap");
+    assert!(!has_image(&svg), "svg renderer should print as vectors");
+}
+
+/// Width and height of the first page's `MediaBox`, in points
+fn media_box(pdf: &[u8]) -> (f32, f32) {
+    let document = lopdf::Document::load_mem(pdf).expect("Output should be a valid PDF");
+    let page_id = *document.get_pages().values().next().unwrap();
+    let media_box = document
+        .get_object(page_id)
+        .and_then(|page| page.as_dict())
+        .and_then(|page| page.get(b"MediaBox"))
+        .and_then(|media_box| media_box.as_array())
+        .expect("Page should have a MediaBox");
+    let values: Vec<f32> = media_box
+        .iter()
+        .map(|value| value.as_float().unwrap())
+        .collect();
+    (values[2] - values[0], values[3] - values[1])
+}
+
+#[tokio::test]
+async fn test_pdf_paper_size_and_landscape() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let a4_landscape = engine
+        .render(tall_pdf_request(json!({
+            "paper_width": 8.27,
+            "paper_height": 11.69,
+            "landscape": true,
+            "margin_top": 0.5,
+            "margin_bottom": 0.5,
+            "margin_left": 0.75,
+            "margin_right": 0.75,
+            "print_background": true
+        })))
+        .await
+        .unwrap();
+
+    let (width, height) = media_box(&a4_landscape);
+    assert!(
+        (width - 841.7).abs() < 2.0 && (height - 595.4).abs() < 2.0,
+        "Expected A4 landscape (842x595pt), got {}x{}",
+        width,
+        height
+    );
+}
+
+#[test]
+fn test_pdf_margins_must_leave_printable_area() {
+    use rendering_engine::core::validation;
+
+    let errors = validation::validate_request(&tall_pdf_request(json!({
+        "paper_width": 4,
+        "margin_left": 2,
+        "margin_right": 2
+    })));
+    assert!(errors.is_has_error());
+    let landscape = validation::validate_request(&tall_pdf_request(json!({"landscape": true})));
+    assert!(!landscape.is_has_error());
+}
@@ -0,0 +1,45 @@
+use rendering_engine::core::renderer::{EngineSettings, RenderingEngine, ScaleDirection};
+use rendering_engine::schemas::render::RenderRequest;
+use serde_json::json;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_idle_browsers_are_reaped_to_min_pool_size() {
+    let engine = RenderingEngine::with_settings(EngineSettings {
+        min_pool_size: 1,
+        max_pool_size: 4,
+        max_concurrent: 4,
+        scale_cooldown: Duration::ZERO,
+        pool_idle_timeout: Duration::from_secs(1),
+        ..Default::default()
+    })
+    .expect("Failed to initialize rendering engine");
+
+    let renders: Vec<_> = (0..4)
+        .map(|i| {
+            let engine = engine.clone();
+            let request: RenderRequest = serde_json::from_value(json!({
+                "library": {"name": "apache-echarts", "version": "5.4.0"},
+                "data": {"series": [{"type": "bar", "data": [i, 2, 3]}]},
+                "options": {"width": 400, "height": 300, "format": "png"}
+            }))
+            .unwrap();
+            tokio::spawn(async move { engine.render(request).await })
+        })
+        .collect();
+    for render in renders {
+        render.await.unwrap().expect("Render failed");
+    }
+
+    let scaled = engine.health_check().pool_size;
+    assert!(scaled > 1, "Concurrent renders should grow the pool");
+
+    // Idle for longer than pool_idle_timeout plus a reaper tick
+    tokio::time::sleep(Duration::from_secs(3)).await;
+
+    let status = engine.health_check();
+    assert_eq!(status.pool_size, 1, "Idle browsers should be reaped");
+    let last_event = &status.pools[0].recent_scale_events[0];
+    assert_eq!(last_event.direction, ScaleDirection::Down);
+    assert_eq!((last_event.from, last_event.to), (scaled, 1));
+}
@@ -14,7 +14,8 @@ async fn test_browser_pool_auto_scaling() {
     );
 
     let app_state = Arc::new(AppState {
-        engine: engine.clone()
+        engine: engine.clone(),
+        storage: None,
     });
 
     let config = get_config();
@@ -130,7 +131,10 @@ async fn test_health_endpoint_shows_pool_metrics() {
             .expect("Failed to initialize rendering engine")
     );
 
-    let app_state = Arc::new(AppState { engine });
+    let app_state = Arc::new(AppState {
+        engine,
+        storage: None,
+    });
     let config = get_config();
     let app = init_openapi_route(app_state, &config);
     let cli = TestClient::new(app);
@@ -0,0 +1,51 @@
+use rendering_engine::core::registry;
+use rendering_engine::core::renderer::RenderingEngine;
+use rendering_engine::schemas::render::RenderRequest;
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+// Mentions renderReady so the registry accepts it, but never sets it
+const NEVER_READY_TEMPLATE: &str = r##"
+[never-ready]
+cdn_url = "https://cdn.jsdelivr.net/npm/echarts@{version}/dist/echarts.min.js"
+wait_selector = "#render-container"
+init_script = """
+    if ({data}.ready) { window.renderReady = true; }
+"""
+"##;
+
+#[tokio::test]
+async fn test_ready_wait_honors_timeout_ms() {
+    let path = std::env::temp_dir().join(format!("{}-never-ready.toml", std::process::id()));
+    std::fs::write(&path, NEVER_READY_TEMPLATE).unwrap();
+    registry::reload(&path).expect("Template should load");
+
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+    let request: RenderRequest = serde_json::from_value(json!({
+        "library": {"name": "never-ready", "version": "5.4.0"},
+        "data": {"ready": false},
+        "options": {"width": 200, "height": 200, "format": "png", "timeout_ms": 2000}
+    }))
+    .unwrap();
+
+    let start = Instant::now();
+    let error = engine
+        .render(request)
+        .await
+        .expect_err("Render should time out");
+
+    assert!(
+        error.to_string().contains("timeout_ms (2000ms)"),
+        "Unexpected error: {}",
+        error
+    );
+    // Launch and setup count towards timeout_ms too
+    assert!(
+        start.elapsed() < Duration::from_secs(4),
+        "Render should give up near timeout_ms, took {:?}",
+        start.elapsed()
+    );
+
+    std::fs::remove_file(path).ok();
+}
@@ -0,0 +1,95 @@
+use rendering_engine::core::registry;
+
+const APEXCHARTS_TEMPLATE: &str = r##"
+[apexcharts-next]
+cdn_url = "https://cdn.jsdelivr.net/npm/apexcharts@{version}/dist/apexcharts.min.js"
+wait_selector = "#render-container"
+expects_object = true
+init_script = """
+    new ApexCharts(document.getElementById('render-container'), {data})
+        .render()
+        .then(() => { window.renderReady = true; });
+"""
+"##;
+
+fn write_registry_file(name: &str, contents: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn test_reload_registry_from_file() {
+    let path = write_registry_file("libraries.toml", APEXCHARTS_TEMPLATE);
+    let changes = registry::reload(&path).expect("Valid registry file should load");
+    assert_eq!(changes.added, vec!["apexcharts-next".to_string()]);
+    assert!(changes.updated.is_empty() && changes.removed.is_empty());
+    assert!(registry::get_template("apexcharts-next").is_some());
+    assert!(registry::get_template("apache-echarts").is_some());
+
+    // Reloading the same file changes nothing
+    let changes = registry::reload(&path).unwrap();
+    assert!(changes.added.is_empty() && changes.updated.is_empty());
+
+    // An invalid entry rejects the whole file and keeps the current registry
+    let invalid = write_registry_file(
+        "libraries.json",
+        r##"{"evil": {"cdn_url": "https://example.com/x.js", "wait_selector": "#render-container", "init_script": "window.renderReady = true"}}"##,
+    );
+    let error = registry::reload(&invalid)
+        .err()
+        .expect("Disallowed CDN should fail");
+    assert!(error.to_string().contains("'evil'"), "{}", error);
+    assert!(registry::get_template("apexcharts-next").is_some());
+    assert!(registry::get_template("evil").is_none());
+
+    // A callback_event script that never calls renderDone would time out
+    let never_done = write_registry_file(
+        "never-done.toml",
+        r##"
+[never-done]
+cdn_url = "https://unpkg.com/konva@{version}/konva.min.js"
+wait_selector = "#render-container"
+ready_strategy = "callback_event"
+init_script = "window.renderReady = true;"
+"##,
+    );
+    let error = registry::reload(&never_done)
+        .err()
+        .expect("Missing renderDone should fail");
+    assert!(error.to_string().contains("renderDone"), "{}", error);
+
+    std::fs::remove_file(path).ok();
+    std::fs::remove_file(invalid).ok();
+    std::fs::remove_file(never_done).ok();
+}
+
+#[test]
+fn test_library_name_aliases() {
+    for name in [
+        "apache-echarts",
+        "echarts",
+        "ECharts",
+        "apacheecharts",
+        "Apache_ECharts",
+    ] {
+        assert_eq!(
+            registry::canonical_name(name).as_deref(),
+            Some("apache-echarts"),
+            "{} should resolve",
+            name
+        );
+    }
+    assert_eq!(
+        registry::canonical_name("Chart.js").as_deref(),
+        Some("chartjs")
+    );
+    assert_eq!(
+        registry::canonical_name("konva").as_deref(),
+        Some("konvajs")
+    );
+    assert!(registry::canonical_name("vega").is_none());
+
+    assert!(registry::get_template("echarts").is_some());
+    assert_eq!(registry::aliases("apache-echarts"), vec!["echarts"]);
+}
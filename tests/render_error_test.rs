@@ -0,0 +1,104 @@
+mod common;
+
+use poem::http::StatusCode;
+use rendering_engine::core::registry;
+use rendering_engine::core::renderer::{self, BrowserUnavailable, RenderError, RenderingEngine};
+use serde_json::{Value, json};
+use std::time::Duration;
+
+// Mentions renderReady so the registry accepts it, but never sets it
+const NEVER_READY_TEMPLATE: &str = r##"
+[never-ready-route]
+cdn_url = "https://cdn.jsdelivr.net/npm/echarts@{version}/dist/echarts.min.js"
+wait_selector = "#render-container"
+init_script = """
+    if ({data}.ready) { window.renderReady = true; }
+"""
+"##;
+
+#[tokio::test]
+async fn test_unsupported_library_returns_400() {
+    // Rejected before any browser work, so the missing Chrome never matters
+    let cli = common::client(json!({}));
+
+    let resp = cli
+        .post("/render")
+        .body_json(&json!({
+            "library": {"name": "no-such-library", "version": "1.0.0"},
+            "data": {},
+            "options": {"width": 400, "height": 300, "format": "png"}
+        }))
+        .send()
+        .await;
+
+    resp.assert_status(StatusCode::BAD_REQUEST);
+    let body: Value = resp.json().await.value().deserialize();
+    let message = body["message"].as_str().unwrap();
+    assert!(message.starts_with("Unsupported library: no-such-library"));
+    assert!(
+        message.contains("apache-echarts"),
+        "Message should list the registered libraries: {}",
+        message
+    );
+}
+
+#[tokio::test]
+async fn test_ready_timeout_returns_408() {
+    let path = std::env::temp_dir().join(format!("{}-never-ready-route.toml", std::process::id()));
+    std::fs::write(&path, NEVER_READY_TEMPLATE).unwrap();
+    registry::reload(&path).expect("Template should load");
+
+    let cli = common::client_with_engine(
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine"),
+        json!({}),
+    );
+
+    let resp = cli
+        .post("/render")
+        .body_json(&json!({
+            "library": {"name": "never-ready-route", "version": "5.4.0"},
+            "data": {"ready": false},
+            "options": {"width": 200, "height": 200, "format": "png", "timeout_ms": 1000}
+        }))
+        .send()
+        .await;
+
+    resp.assert_status(StatusCode::REQUEST_TIMEOUT);
+    std::fs::remove_file(path).ok();
+}
+
+#[test]
+fn test_error_codes_classify_render_errors() {
+    let code = |error: anyhow::Error| renderer::error_code(&error);
+
+    assert_eq!(
+        code(
+            RenderError::Timeout {
+                timeout: Duration::from_secs(1),
+                detail: "renderReady".to_string(),
+            }
+            .into()
+        ),
+        "TIMEOUT"
+    );
+    assert_eq!(
+        code(RenderError::UnsupportedLibrary("nope".to_string()).into()),
+        "UNSUPPORTED_LIBRARY"
+    );
+    assert_eq!(
+        code(RenderError::Initialization("no data".to_string()).into()),
+        "INIT_ERROR"
+    );
+    // Context added on the way up doesn't hide the cause
+    assert_eq!(
+        code(
+            anyhow::Error::from(RenderError::BrowserCrashed("gone".to_string())).context("render")
+        ),
+        "BROWSER_CRASH"
+    );
+    assert_eq!(
+        code(BrowserUnavailable("pool exhausted".to_string()).into()),
+        "BROWSER_UNAVAILABLE"
+    );
+    assert_eq!(code(anyhow::anyhow!("screenshot failed")), "INTERNAL_ERROR");
+}
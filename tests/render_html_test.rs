@@ -0,0 +1,69 @@
+use poem::{http::StatusCode, test::TestClient};
+use rendering_engine::core::renderer::{EngineSettings, RenderingEngine};
+use rendering_engine::schemas::render::RenderRequest;
+use rendering_engine::settings::Config;
+use rendering_engine::{AppState, init_openapi_route};
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+fn request(library: &str) -> Value {
+    json!({
+        "library": {"name": library, "version": "5.4.0"},
+        "data": {"series": [{"type": "bar", "data": [1, 2, 3]}]},
+        "options": {"width": 400, "height": 300, "format": "png"}
+    })
+}
+
+#[tokio::test]
+async fn test_render_html_returns_the_render_page() {
+    // Nonexistent Chrome: the endpoint must not need a browser
+    let engine = Arc::new(
+        RenderingEngine::with_settings(EngineSettings {
+            min_pool_size: 0,
+            chrome_path: Some("/nonexistent/chrome".into()),
+            ..Default::default()
+        })
+        .unwrap(),
+    );
+    let app_state = Arc::new(AppState {
+        engine: engine.clone(),
+        storage: None,
+    });
+    let config = |allow_return_html: bool| -> Config {
+        serde_json::from_value(json!({
+            "env": "file",
+            "host": "localhost",
+            "port": 8080,
+            "allow_return_html": allow_return_html
+        }))
+        .unwrap()
+    };
+
+    let cli = TestClient::new(init_openapi_route(app_state.clone(), &config(true)));
+    let resp = cli
+        .post("/render/html")
+        .body_json(&request("apache-echarts"))
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    resp.assert_content_type("text/html; charset=utf-8");
+    let expected = engine
+        .page_html(&serde_json::from_value::<RenderRequest>(request("apache-echarts")).unwrap())
+        .unwrap();
+    resp.assert_text(expected).await;
+
+    let resp = cli
+        .post("/render/html")
+        .body_json(&request("no-such-library"))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+
+    let cli = TestClient::new(init_openapi_route(app_state, &config(false)));
+    let resp = cli
+        .post("/render/html")
+        .body_json(&request("apache-echarts"))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+}
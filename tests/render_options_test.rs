@@ -0,0 +1,602 @@
+use rendering_engine::core::renderer::RenderingEngine;
+use rendering_engine::core::validation;
+use rendering_engine::schemas::render::RenderRequest;
+use serde_json::{Value, json};
+use std::time::{Duration, Instant};
+
+fn bar_chart_request(options: Value) -> RenderRequest {
+    let mut request = json!({
+        "library": {
+            "name": "apache-echarts",
+            "version": "5.4.0"
+        },
+        "data": {
+            "xAxis": {"data": ["A", "B", "C"]},
+            "yAxis": {},
+            "series": [{"type": "bar", "data": [10, 20, 30]}]
+        },
+        "options": {
+            "width": 400,
+            "height": 300,
+            "format": "png"
+        }
+    });
+
+    for (key, value) in options.as_object().unwrap() {
+        request["options"][key] = value.clone();
+    }
+
+    serde_json::from_value(request).unwrap()
+}
+
+#[tokio::test]
+async fn test_forced_colors_changes_capture() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let default = engine.render(bar_chart_request(json!({}))).await.unwrap();
+    let forced = engine
+        .render(bar_chart_request(json!({
+            "forced_colors": "active",
+            "prefers_color_scheme": "dark"
+        })))
+        .await
+        .unwrap();
+
+    assert!(!forced.is_empty());
+    assert_ne!(
+        default, forced,
+        "forced-colors capture should differ from default"
+    );
+}
+
+#[tokio::test]
+async fn test_physical_size_sets_pixels_and_ppi() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let png = engine
+        .render(bar_chart_request(json!({
+            "width": null,
+            "height": null,
+            "width_mm": 85,
+            "height_mm": 55,
+            "ppi": 300
+        })))
+        .await
+        .unwrap();
+
+    let ihdr_u32 = |offset: usize| u32::from_be_bytes(png[offset..offset + 4].try_into().unwrap());
+    assert_eq!((ihdr_u32(16), ihdr_u32(20)), (1004, 650));
+
+    let phys = png
+        .windows(4)
+        .position(|w| w == b"pHYs")
+        .expect("PNG should carry a pHYs chunk");
+    let pixels_per_meter = u32::from_be_bytes(png[phys + 4..phys + 8].try_into().unwrap());
+    assert_eq!(pixels_per_meter, 11811, "300 PPI in pixels per meter");
+}
+
+#[tokio::test]
+async fn test_asset_waits_complete() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let png = engine
+        .render(bar_chart_request(json!({
+            "wait_for_fonts": true,
+            "wait_for_network_idle": true,
+            "wait_for_images": true
+        })))
+        .await
+        .expect("Render should finish once fonts, images and network settle");
+
+    assert!(!png.is_empty());
+}
+
+#[tokio::test]
+async fn test_rgba_format_returns_raw_pixels() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let raw = engine
+        .render_rgba(bar_chart_request(json!({"format": "rgba"})))
+        .await
+        .unwrap();
+
+    assert_eq!((raw.width, raw.height), (400, 300));
+    assert_eq!(raw.pixels.len(), 400 * 300 * 4);
+    assert_eq!(
+        &raw.pixels[..4],
+        &[255, 255, 255, 255],
+        "Top-left is the white page"
+    );
+}
+
+#[tokio::test]
+async fn test_themes_change_capture() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let light = engine
+        .render(bar_chart_request(json!({"theme": "light"})))
+        .await
+        .unwrap();
+    let dark = engine
+        .render(bar_chart_request(json!({"theme": "dark"})))
+        .await
+        .unwrap();
+
+    assert_ne!(light, dark, "dark theme capture should differ from light");
+}
+
+#[tokio::test]
+async fn test_bit_depth_16_reencodes_png() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let png = engine
+        .render(bar_chart_request(json!({"bit_depth": 16})))
+        .await
+        .unwrap();
+
+    assert_eq!(png[24], 16, "IHDR bit depth");
+    let decoded = image::load_from_memory(&png).unwrap();
+    assert_eq!((decoded.width(), decoded.height()), (400, 300));
+    assert!(matches!(
+        decoded.color(),
+        image::ColorType::Rgb16 | image::ColorType::Rgba16
+    ));
+}
+
+#[tokio::test]
+async fn test_cpu_throttling_renders() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let png = engine
+        .render(bar_chart_request(json!({
+            "cpu_throttling_rate": 4,
+            "timeout_ms": 60000
+        })))
+        .await
+        .expect("Throttled render should still complete");
+
+    assert!(!png.is_empty());
+}
+
+#[tokio::test]
+async fn test_seed_makes_force_layout_reproducible() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let force_graph = || -> RenderRequest {
+        serde_json::from_value(json!({
+            "library": {"name": "apache-echarts", "version": "5.4.0"},
+            "data": {
+                "animation": false,
+                "series": [{
+                    "type": "graph",
+                    "layout": "force",
+                    "force": {"layoutAnimation": false, "repulsion": 100},
+                    "data": (0..12).map(|i| json!({"name": format!("n{}", i)})).collect::<Vec<_>>(),
+                    "links": (1..12).map(|i| json!({"source": "n0", "target": format!("n{}", i)})).collect::<Vec<_>>()
+                }]
+            },
+            "options": {"width": 400, "height": 300, "format": "png", "seed": 42}
+        }))
+        .unwrap()
+    };
+
+    let first = engine.render(force_graph()).await.unwrap();
+    let second = engine.render(force_graph()).await.unwrap();
+
+    assert_eq!(first, second, "same seed should give the same layout");
+}
+
+#[tokio::test]
+async fn test_render_delay_sets_settle_time() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+    // Warm up so the timed renders don't include a browser launch or CDN fetch
+    engine.render(bar_chart_request(json!({}))).await.unwrap();
+
+    let timed_render = |render_delay_ms: u64| {
+        let request = bar_chart_request(json!({
+            "render_delay_ms": render_delay_ms,
+            "poll_interval_ms": 50
+        }));
+        let engine = &engine;
+        async move {
+            let start = Instant::now();
+            engine.render(request).await.unwrap();
+            start.elapsed()
+        }
+    };
+
+    let short = timed_render(0).await;
+    let long = timed_render(3000).await;
+
+    assert!(
+        long >= short + Duration::from_millis(2500),
+        "render_delay_ms should extend the render: {:?} vs {:?}",
+        long,
+        short
+    );
+}
+
+#[tokio::test]
+async fn test_svg_format_returns_markup() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let svg = engine
+        .render(bar_chart_request(json!({"format": "svg"})))
+        .await
+        .expect("SVG render failed");
+    let svg = String::from_utf8(svg).expect("SVG output should be UTF-8");
+
+    assert!(svg.starts_with("<svg"), "Unexpected output: {:.100}", svg);
+    assert!(svg.contains(r#"xmlns="http://www.w3.org/2000/svg""#));
+    assert!(
+        svg.contains("<path"),
+        "bars should be drawn as vector paths"
+    );
+}
+
+#[test]
+fn test_svg_format_rejected_for_canvas_libraries() {
+    let mut request = bar_chart_request(json!({"format": "svg"}));
+    request.library.name = "chartjs".to_string();
+
+    let errors = validation::validate_request(&request);
+    assert!(errors.is_has_error());
+    assert!(errors.summary().contains("can't produce svg"));
+}
+
+#[test]
+fn test_renderer_option_selects_echarts_renderer() {
+    use rendering_engine::core::template;
+
+    let renderer = |options: Value| {
+        let html = template::generate_html(&bar_chart_request(options), &[]).unwrap();
+        if html.contains("{ renderer: 'svg' }") {
+            "svg"
+        } else {
+            assert!(html.contains("{ renderer: 'canvas' }"));
+            "canvas"
+        }
+    };
+
+    assert_eq!(renderer(json!({})), "canvas");
+    assert_eq!(renderer(json!({"format": "svg"})), "svg");
+    assert_eq!(renderer(json!({"format": "pdf", "renderer": "svg"})), "svg");
+    assert_eq!(renderer(json!({"format": "png", "renderer": "svg"})), "svg");
+
+    let errors = validation::validate_request(&bar_chart_request(
+        json!({"format": "svg", "renderer": "canvas"}),
+    ));
+    assert_eq!(
+        errors.summary(),
+        "body.options.renderer: svg output needs the svg renderer"
+    );
+}
+
+#[tokio::test]
+async fn test_clip_captures_region() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let png = engine
+        .render(bar_chart_request(json!({
+            "clip": {"x": 50, "y": 20, "width": 120, "height": 80}
+        })))
+        .await
+        .unwrap();
+
+    let ihdr_u32 = |offset: usize| u32::from_be_bytes(png[offset..offset + 4].try_into().unwrap());
+    assert_eq!((ihdr_u32(16), ihdr_u32(20)), (120, 80));
+}
+
+#[test]
+fn test_clip_outside_page_rejected() {
+    let request = bar_chart_request(json!({
+        "clip": {"x": 300, "y": 0, "width": 200, "height": 100}
+    }));
+
+    let errors = validation::validate_request(&request);
+    assert!(errors.summary().contains("exceeds the 400x300 page"));
+}
+
+#[tokio::test]
+async fn test_transparent_background_has_alpha() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let png = engine
+        .render(bar_chart_request(json!({"transparent": true})))
+        .await
+        .unwrap();
+
+    let image = image::load_from_memory(&png).unwrap().to_rgba8();
+    assert_eq!(image.get_pixel(0, 0)[3], 0, "corner should be transparent");
+    assert!(
+        image.pixels().any(|pixel| pixel[3] == 255),
+        "bars stay opaque"
+    );
+}
+
+#[test]
+fn test_background_color_validation() {
+    let errors_for = |color: &str| {
+        validation::validate_request(&bar_chart_request(json!({"background_color": color})))
+            .is_has_error()
+    };
+
+    for valid in [
+        "#222",
+        "#1e1e2eff",
+        "rgb(34, 34, 34)",
+        "hsla(210 40% 20% / 0.5)",
+        "Navy",
+    ] {
+        assert!(!errors_for(valid), "{} should be accepted", valid);
+    }
+    for invalid in [
+        "#12",
+        "darkish",
+        "red; } body { display: none",
+        "url(x.png)",
+        "rgb()",
+    ] {
+        assert!(errors_for(invalid), "{} should be rejected", invalid);
+    }
+}
+
+#[tokio::test]
+async fn test_background_color_fills_page() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let png = engine
+        .render(bar_chart_request(json!({"background_color": "#222222"})))
+        .await
+        .unwrap();
+
+    let image = image::load_from_memory(&png).unwrap().to_rgb8();
+    assert_eq!(image.get_pixel(0, 0).0, [0x22, 0x22, 0x22]);
+}
+
+#[tokio::test]
+async fn test_animations_disabled_by_default_for_identical_output() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    // No settle delay, so an animated chart would be caught mid-animation
+    let options = json!({"render_delay_ms": 0});
+    let first = engine
+        .render(bar_chart_request(options.clone()))
+        .await
+        .unwrap();
+    let second = engine.render(bar_chart_request(options)).await.unwrap();
+
+    assert_eq!(first, second, "repeated renders should be byte-identical");
+}
+
+#[tokio::test]
+async fn test_png_without_quality_renders() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let request = bar_chart_request(json!({}));
+    assert_eq!(request.options.quality, None);
+    let png = engine.render(request).await.expect("PNG render failed");
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+}
+
+#[test]
+fn test_quality_out_of_range_rejected_by_schema() {
+    use poem_openapi::types::ParseFromJSON;
+
+    let request = |quality: u32| {
+        json!({
+            "library": {"name": "apache-echarts", "version": "5.4.0"},
+            "data": {},
+            "options": {"width": 400, "height": 300, "format": "jpeg", "quality": quality}
+        })
+    };
+
+    assert!(RenderRequest::parse_from_json(Some(request(80))).is_ok());
+    for quality in [0, 101] {
+        assert!(
+            RenderRequest::parse_from_json(Some(request(quality))).is_err(),
+            "quality {} should be rejected",
+            quality
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_mobile_render_produces_valid_image() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let png = engine
+        .render(bar_chart_request(json!({
+            "mobile": true,
+            "user_agent": "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) Mobile/15E148"
+        })))
+        .await
+        .expect("Mobile render failed");
+    let image = image::load_from_memory(&png).expect("Output should be a valid image");
+    assert_eq!((image.width(), image.height()), (400, 300));
+}
+
+#[test]
+fn test_user_agent_must_be_printable() {
+    use poem_openapi::types::ParseFromJSON;
+
+    let request = |user_agent: &str| {
+        json!({
+            "library": {"name": "apache-echarts", "version": "5.4.0"},
+            "data": {},
+            "options": {"width": 400, "height": 300, "user_agent": user_agent}
+        })
+    };
+
+    assert!(
+        RenderRequest::parse_from_json(Some(request("Mozilla/5.0 (Linux; Android 14)"))).is_ok()
+    );
+    assert!(RenderRequest::parse_from_json(Some(request("Mozilla/5.0\r\nX-Injected: 1"))).is_err());
+}
+
+#[tokio::test]
+async fn test_screen_orientation_is_emulated() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    let request: RenderRequest = serde_json::from_value(json!({
+        "library": {"name": "raw-html"},
+        "data": {
+            "html": "<script>console.log(screen.orientation.type, screen.orientation.angle)</script>"
+        },
+        "options": {
+            "width": 400,
+            "height": 300,
+            "format": "png",
+            "return_base64": true,
+            "capture_console": true,
+            "screen_orientation": {"type": "landscape-primary"}
+        }
+    }))
+    .unwrap();
+
+    let response = engine.render_base64(request).await.unwrap();
+    let console = response.console.expect("console requested");
+    assert_eq!(console[0].text, "landscape-primary 90");
+}
+
+#[test]
+fn test_screen_orientation_type_and_angle_validated() {
+    use poem_openapi::types::ParseFromJSON;
+
+    let request = |orientation: Value| {
+        json!({
+            "library": {"name": "apache-echarts", "version": "5.4.0"},
+            "data": {},
+            "options": {"width": 400, "height": 300, "screen_orientation": orientation}
+        })
+    };
+
+    for valid in [
+        json!({"type": "portrait-primary"}),
+        json!({"type": "landscape-secondary", "angle": 90}),
+    ] {
+        assert!(RenderRequest::parse_from_json(Some(request(valid))).is_ok());
+    }
+    for invalid in [
+        json!({"type": "sideways"}),
+        json!({"type": "landscape-primary", "angle": 360}),
+    ] {
+        assert!(
+            RenderRequest::parse_from_json(Some(request(invalid.clone()))).is_err(),
+            "{} should be rejected",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn test_capture_quality_per_format() {
+    let quality = |format: &str, quality: Option<u8>| {
+        bar_chart_request(json!({"format": format, "quality": quality}))
+            .options
+            .capture_quality()
+    };
+
+    assert_eq!(quality("jpeg", None), Some(90));
+    assert_eq!(quality("jpg", Some(1)), Some(1));
+    assert_eq!(quality("webp", Some(100)), Some(100));
+    // Deserialized without the schema's 1-100 check
+    assert_eq!(quality("jpeg", Some(0)), Some(1));
+    assert_eq!(quality("webp", Some(255)), Some(100));
+    for format in ["png", "pdf", "rgba", "svg"] {
+        assert_eq!(
+            quality(format, Some(80)),
+            None,
+            "{} takes no quality",
+            format
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_auto_height_grows_capture_to_content() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    // A 900px tall SVG in a container that asks for 300px
+    let request = |auto_height: bool| -> RenderRequest {
+        serde_json::from_value(json!({
+            "library": {"name": "d3", "version": "7.9.0"},
+            "data": {
+                "renderCode": "d3.select(container).append('svg').attr('width', width).attr('height', 900).append('rect').attr('width', width).attr('height', 900).attr('fill', 'steelblue');"
+            },
+            "options": {"width": 400, "height": 300, "format": "png", "auto_height": auto_height}
+        }))
+        .unwrap()
+    };
+
+    let fixed = engine.render(request(false)).await.unwrap();
+    let fitted = engine.render(request(true)).await.unwrap();
+
+    let fixed = image::load_from_memory(&fixed).unwrap();
+    let fitted = image::load_from_memory(&fitted).unwrap();
+    assert_eq!((fixed.width(), fixed.height()), (400, 300));
+    assert_eq!(
+        (fitted.width(), fitted.height()),
+        (400, 900),
+        "auto_height should capture the full content height"
+    );
+}
+
+#[tokio::test]
+async fn test_wait_for_selector_holds_capture_until_element_appears() {
+    use rendering_engine::core::renderer::RenderError;
+
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+
+    // Ready immediately, but the content only shows up 300ms later
+    let request = |selector: &str| -> RenderRequest {
+        serde_json::from_value(json!({
+            "library": {"name": "raw-html", "version": "1.0.0"},
+            "data": {
+                "html": "<script>setTimeout(() => { document.getElementById('render-container').innerHTML = '<div id=\"late\" style=\"width:400px;height:300px;background:rgb(0,0,255)\"></div>'; }, 300);</script>"
+            },
+            "options": {
+                "width": 400,
+                "height": 300,
+                "format": "png",
+                "render_delay_ms": 0,
+                "timeout_ms": 2000,
+                "wait_for_selector": selector
+            }
+        }))
+        .unwrap()
+    };
+
+    let png = engine.render(request("#late")).await.unwrap();
+    let image = image::load_from_memory(&png).unwrap().to_rgba8();
+    assert_eq!(image.get_pixel(200, 150).0, [0, 0, 255, 255]);
+
+    let error = engine.render(request("#never")).await.unwrap_err();
+    assert!(
+        matches!(
+            error.downcast_ref::<RenderError>(),
+            Some(RenderError::Timeout { .. })
+        ),
+        "Unexpected error: {}",
+        error
+    );
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use poem::http::StatusCode;
+use rendering_engine::core::renderer::RenderingEngine;
+use serde_json::{Value, json};
+
+const PNG_SIGNATURE: &[u8] = &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+fn d3_request() -> Value {
+    json!({
+        "library": {"name": "d3", "version": "7.9.0"},
+        "data": {
+            "values": [10, 20, 30],
+            "renderCode": "d3.select(container).append('svg').attr('width', width).attr('height', height).selectAll('rect').data(data.values).join('rect').attr('x', (d, i) => i * 60).attr('y', d => height - d * 5).attr('width', 40).attr('height', d => d * 5);"
+        },
+        "options": {"width": 400, "height": 300, "format": "png"}
+    })
+}
+
+#[tokio::test]
+async fn test_d3_requires_allow_request_code() {
+    // Rejected before rendering, so no browser is launched
+    let cli = common::client(json!({"allow_request_code": false}));
+
+    let resp = cli.post("/render").body_json(&d3_request()).send().await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_d3_runs_render_code() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+    let cli = common::client_with_engine(engine, json!({"allow_request_code": true}));
+
+    let resp = cli.post("/render").body_json(&d3_request()).send().await;
+    resp.assert_status_is_ok();
+    let png = resp.0.into_body().into_vec().await.unwrap();
+    assert!(png.starts_with(PNG_SIGNATURE));
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use poem::http::StatusCode;
+use rendering_engine::core::renderer::RenderingEngine;
+use rendering_engine::request_id::REQUEST_ID_HEADER;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_request_id_is_echoed_or_generated() {
+    let cli = common::client(json!({"api_key": "secret"}));
+
+    let resp = cli
+        .get("/health/live")
+        .header(REQUEST_ID_HEADER, "checkout-1234")
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    resp.assert_header(REQUEST_ID_HEADER, "checkout-1234");
+
+    // Rejected requests carry one too
+    let resp = cli
+        .get("/libraries")
+        .header(REQUEST_ID_HEADER, "checkout-1235")
+        .send()
+        .await;
+    resp.assert_status(StatusCode::UNAUTHORIZED);
+    resp.assert_header(REQUEST_ID_HEADER, "checkout-1235");
+
+    let mut generated = Vec::new();
+    for supplied in [None, Some("has spaces"), Some(&*"x".repeat(129))] {
+        let mut request = cli.get("/health/live");
+        if let Some(id) = supplied {
+            request = request.header(REQUEST_ID_HEADER, id);
+        }
+        let resp = request.send().await;
+        let id = resp.0.headers()[REQUEST_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert_eq!(id.len(), 32, "Expected a generated ID, got {:?}", id);
+        assert!(id.chars().all(|c| c.is_ascii_hexdigit()));
+        generated.push(id);
+    }
+    generated.dedup();
+    assert_eq!(generated.len(), 3, "Generated IDs should differ");
+}
+
+#[tokio::test]
+async fn test_internal_error_includes_request_id() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+    let cli = common::client_with_engine(engine, json!({"api_key": "secret"}));
+
+    // A sprite selector matching nothing fails the render
+    let resp = cli
+        .post("/render")
+        .header("X-API-Key", "secret")
+        .header(REQUEST_ID_HEADER, "checkout-1236")
+        .body_json(&json!({
+            "library": {"name": "apache-echarts", "version": "5.4.0"},
+            "data": {"series": [{"type": "bar", "data": [1, 2, 3]}]},
+            "options": {
+                "width": 400,
+                "height": 300,
+                "format": "png",
+                "sprite_selectors": ["#no-such-element"]
+            }
+        }))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    resp.assert_header(REQUEST_ID_HEADER, "checkout-1236");
+    let body = resp.json().await;
+    body.value()
+        .object()
+        .get("request_id")
+        .assert_string("checkout-1236");
+}
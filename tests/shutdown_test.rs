@@ -0,0 +1,55 @@
+use rendering_engine::core::renderer::{BrowserUnavailable, EngineSettings, RenderingEngine};
+use rendering_engine::schemas::render::RenderRequest;
+use serde_json::json;
+use std::time::Duration;
+
+fn chart_request() -> RenderRequest {
+    serde_json::from_value(json!({
+        "library": {"name": "apache-echarts", "version": "5.4.0"},
+        "data": {"series": [{"type": "bar", "data": [1, 2, 3]}]},
+        "options": {"width": 400, "height": 300, "format": "png"}
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_drained_engine_refuses_renders() {
+    let engine = RenderingEngine::with_settings(EngineSettings {
+        min_pool_size: 0,
+        chrome_path: Some("/nonexistent/chrome".into()),
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert!(engine.drain(Duration::from_secs(1)).await);
+    engine.shutdown();
+
+    let error = engine.render(chart_request()).await.unwrap_err();
+    assert!(
+        error.downcast_ref::<BrowserUnavailable>().is_some(),
+        "Unexpected error: {}",
+        error
+    );
+    assert_eq!(engine.health_check().pool_size, 0);
+}
+
+#[tokio::test]
+async fn test_drain_waits_for_in_flight_renders() {
+    let engine = RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize engine");
+
+    let render = {
+        let engine = engine.clone();
+        tokio::spawn(async move { engine.render(chart_request()).await })
+    };
+    // Let the render take its permit before draining
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    assert!(engine.drain(Duration::from_secs(30)).await);
+    render
+        .await
+        .unwrap()
+        .expect("In-flight render should complete");
+
+    engine.shutdown();
+    assert_eq!(engine.health_check().pool_size, 0);
+}
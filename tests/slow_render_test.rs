@@ -0,0 +1,17 @@
+use rendering_engine::core::renderer::{EngineSettings, RenderingEngine};
+use rendering_engine::core::selftest;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_renders_over_threshold_are_counted() {
+    let engine = RenderingEngine::with_settings(EngineSettings {
+        slow_render_threshold: Some(Duration::from_millis(1)),
+        ..Default::default()
+    })
+    .expect("Failed to initialize rendering engine");
+
+    let request = selftest::sample_request("apache-echarts").unwrap();
+    engine.render(request).await.unwrap();
+
+    assert_eq!(engine.health_check().slow_renders, 1);
+}
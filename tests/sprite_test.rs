@@ -0,0 +1,61 @@
+use rendering_engine::core::sprite;
+
+fn png(width: u32, height: u32, color: [u8; 4]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    image::RgbaImage::from_pixel(width, height, image::Rgba(color))
+        .write_to(
+            &mut std::io::Cursor::new(&mut bytes),
+            image::ImageFormat::Png,
+        )
+        .unwrap();
+    bytes
+}
+
+#[test]
+fn test_sprite_stacks_frames_and_scales_css() {
+    // Captured at a device scale factor of 2
+    let sheet = sprite::build(
+        vec![
+            ("#legend".to_string(), png(40, 20, [255, 0, 0, 255])),
+            ("#axis".to_string(), png(60, 30, [0, 0, 255, 255])),
+        ],
+        2.0,
+    )
+    .unwrap();
+
+    let image = image::load_from_memory(&sheet.png).unwrap().to_rgba8();
+    assert_eq!(image.dimensions(), (60, 50));
+    assert_eq!(image.get_pixel(0, 19).0, [255, 0, 0, 255]);
+    assert_eq!(image.get_pixel(0, 20).0, [0, 0, 255, 255]);
+    // The narrower frame leaves the rest of its rows transparent
+    assert_eq!(image.get_pixel(50, 10).0, [0, 0, 0, 0]);
+
+    let frames: Vec<_> = sheet
+        .frames
+        .iter()
+        .map(|frame| {
+            (
+                frame.selector.as_str(),
+                frame.class_name.as_str(),
+                frame.y,
+                frame.width,
+                frame.height,
+            )
+        })
+        .collect();
+    assert_eq!(
+        frames,
+        [
+            ("#legend", "sprite-0", 0, 40, 20),
+            ("#axis", "sprite-1", 20, 60, 30)
+        ]
+    );
+
+    // Sheet pixels halved into CSS pixels
+    assert_eq!(
+        sheet.css,
+        ".sprite { background-repeat: no-repeat; background-size: 30px 25px; }\n\
+         .sprite-0 { width: 20px; height: 10px; background-position: 0 -0px; }\n\
+         .sprite-1 { width: 30px; height: 15px; background-position: 0 -10px; }\n"
+    );
+}
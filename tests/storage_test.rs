@@ -0,0 +1,57 @@
+mod common;
+
+use poem::http::StatusCode;
+use serde_json::{Value, json};
+
+fn request_with_key(key: &str) -> Value {
+    json!({
+        "library": {"name": "apache-echarts", "version": "5.4.0"},
+        "data": {"series": [{"type": "bar", "data": [1, 2, 3]}]},
+        "options": {"width": 400, "height": 300, "format": "png", "output_key": key}
+    })
+}
+
+#[tokio::test]
+async fn test_output_key_requires_configured_storage() {
+    let resp = common::client(json!({}))
+        .post("/render")
+        .body_json(&request_with_key("charts/sales.png"))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_output_key_rejects_path_traversal() {
+    let resp = common::client(json!({}))
+        .post("/render")
+        .body_json(&request_with_key("charts/../secrets.png"))
+        .send()
+        .await;
+    resp.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn test_format_inferred_from_output_key_extension() {
+    let cli = common::client(json!({}));
+    let mut request = request_with_key("charts/sales.pdf");
+
+    // Inferred as pdf, so it gets past validation to the storage check
+    request["options"].as_object_mut().unwrap().remove("format");
+    let resp = cli.post("/render").body_json(&request).send().await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+
+    // Explicit format disagreeing with the extension
+    request["options"]["format"] = json!("png");
+    let resp = cli.post("/render").body_json(&request).send().await;
+    resp.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Nothing to infer from
+    request["options"].as_object_mut().unwrap().remove("format");
+    request["options"]
+        .as_object_mut()
+        .unwrap()
+        .remove("output_key");
+    let resp = cli.post("/render").body_json(&request).send().await;
+    resp.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+}
@@ -0,0 +1,37 @@
+use rendering_engine::core::renderer::RenderingEngine;
+use rendering_engine::schemas::render::RenderRequest;
+use serde_json::json;
+
+fn request() -> RenderRequest {
+    serde_json::from_value(json!({
+        "library": {"name": "konvajs", "version": "9.2.0"},
+        "data": {
+            "shapes": [{
+                "type": "Rect",
+                "config": {"x": 10, "y": 10, "width": 50, "height": 50, "fill": "steelblue"}
+            }]
+        },
+        "options": {"width": 200, "height": 200, "format": "png", "render_delay_ms": 0}
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_rapid_sequential_renders_on_one_instance() {
+    // A single browser instance, so every render reuses it right after the
+    // previous render's tab was closed
+    let engine = RenderingEngine::with_config(1, 1, 1).expect("Failed to initialize engine");
+
+    for i in 0..25 {
+        engine
+            .render(request())
+            .await
+            .unwrap_or_else(|e| panic!("Render {} failed: {}", i, e));
+    }
+
+    assert_eq!(
+        engine.health_check().pool_size,
+        1,
+        "instance was not replaced"
+    );
+}
@@ -0,0 +1,283 @@
+use rendering_engine::core::{registry, template};
+use rendering_engine::schemas::render::RenderRequest;
+use serde_json::{Value, json};
+
+// Chart.js with the datalabels plugin and a stylesheet
+const PLUGIN_TEMPLATE: &str = r##"
+[chartjs-datalabels]
+cdn_url = "https://cdn.jsdelivr.net/npm/chart.js@{version}/dist/chart.umd.js"
+extra_scripts = ["https://cdn.jsdelivr.net/npm/chartjs-plugin-datalabels@2.2.0"]
+head_html = '<link rel="stylesheet" href="https://cdn.jsdelivr.net/npm/example@1.0.0/theme.css">'
+wait_selector = "#render-container"
+ready_strategy = "sync"
+init_script = "Chart.register(ChartDataLabels);"
+"##;
+
+const PAYLOAD: &str = "</script><script>window.injected = true</script><!--";
+
+fn data_block(html: &str) -> &str {
+    let start = html
+        .find(r#"<script type="application/json" id="render-data">"#)
+        .unwrap();
+    let rest = &html[start..];
+    let body = &rest[rest.find('>').unwrap() + 1..];
+    &body[..body.find("</script>").unwrap()]
+}
+
+#[test]
+fn test_data_cannot_close_its_script_element() {
+    let request: RenderRequest = serde_json::from_value(json!({
+        "library": {"name": "apache-echarts", "version": "5.4.0"},
+        "data": {
+            "title": {"text": PAYLOAD},
+            "series": [{"type": "bar", "data": [1, 2, 3], "name": "a & b > c\n'quoted'"}]
+        },
+        "options": {"width": 400, "height": 300, "format": "png"}
+    }))
+    .unwrap();
+
+    let html = template::generate_html(&request, &[]).unwrap();
+
+    assert!(!html.contains("window.injected = true</script>"));
+    assert_eq!(html.matches("<!--").count(), 0);
+
+    // The block holds valid JSON that decodes back to the request data
+    let data: Value = serde_json::from_str(data_block(&html)).unwrap();
+    assert_eq!(data, request.data);
+
+    // Init scripts read the block instead of embedding the data
+    assert!(html.contains("JSON.parse(document.getElementById('render-data').textContent)"));
+}
+
+#[test]
+fn test_render_ready_waits_for_fonts_by_default() {
+    let request = |options: Value| -> RenderRequest {
+        serde_json::from_value(json!({
+            "library": {"name": "apache-echarts", "version": "5.4.0"},
+            "data": {"series": [{"type": "bar", "data": [1, 2, 3]}]},
+            "options": options
+        }))
+        .unwrap()
+    };
+
+    let html = template::generate_html(&request(json!({"format": "png"})), &[]).unwrap();
+    assert!(html.contains("document.fonts.ready.then"));
+
+    let html = template::generate_html(
+        &request(json!({"format": "png", "wait_for_fonts": false})),
+        &[],
+    )
+    .unwrap();
+    assert!(!html.contains("document.fonts.ready"));
+    assert!(html.contains("window.renderReady = false;"));
+}
+
+#[test]
+fn test_fonts_are_declared_and_loaded_before_init() {
+    let request: RenderRequest = serde_json::from_value(json!({
+        "library": {"name": "apache-echarts", "version": "5.4.0"},
+        "data": {"textStyle": {"fontFamily": "Brand Sans"}, "series": [{"type": "bar", "data": [1]}]},
+        "options": {
+            "width": 400,
+            "height": 300,
+            "format": "png",
+            "fonts": [
+                {"family": "Brand Sans", "url": "https://assets.example.com/brand.woff2"},
+                {"family": "Inter", "stylesheet_url": "https://fonts.googleapis.com/css2?family=Inter&display=block"}
+            ]
+        }
+    }))
+    .unwrap();
+
+    let html = template::generate_html(&request, &["assets.example.com".to_string()]).unwrap();
+    let head = &html[..html.find("</head>").unwrap()];
+    assert!(head.contains(
+        r#"@font-face { font-family: "Brand Sans"; src: url("https://assets.example.com/brand.woff2"); }"#
+    ));
+    assert!(head.contains(
+        r#"<link rel="stylesheet" href="https://fonts.googleapis.com/css2?family=Inter&display=block">"#
+    ));
+    assert!(html.contains(r#"Promise.all(["Brand Sans","Inter"].map("#));
+
+    // Font hosts are limited to the allowed CDN hosts and Google Fonts
+    let error = template::generate_html(&request, &[]).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("font host 'assets.example.com' not allowed"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn test_font_specs_are_validated() {
+    use rendering_engine::core::validation;
+
+    let request = |fonts: Value| -> RenderRequest {
+        serde_json::from_value(json!({
+            "library": {"name": "apache-echarts", "version": "5.4.0"},
+            "data": {"series": [{"type": "bar", "data": [1]}]},
+            "options": {"width": 400, "height": 300, "format": "png", "fonts": fonts}
+        }))
+        .unwrap()
+    };
+
+    for fonts in [
+        json!([{"family": "Brand", "url": "http://assets.example.com/brand.woff2"}]),
+        json!([{"family": "Brand\"; }", "url": "https://assets.example.com/brand.woff2"}]),
+        json!([{"family": "Brand"}]),
+    ] {
+        let errors = validation::validate_request(&request(fonts.clone()));
+        assert!(errors.is_has_error(), "{} should be rejected", fonts);
+        assert!(template::generate_html(&request(fonts), &[]).is_err());
+    }
+}
+
+#[test]
+fn test_raw_html_loads_no_library_script() {
+    let request: RenderRequest = serde_json::from_value(json!({
+        "library": {"name": "raw-html", "version": "1.0.0"},
+        "data": {"html": "<svg width=\"10\" height=\"10\"></svg>"},
+        "options": {"width": 400, "height": 300, "format": "png"}
+    }))
+    .unwrap();
+
+    let html = template::generate_html(&request, &[]).unwrap();
+    assert!(!html.contains("<script src="));
+    assert!(html.contains("raw-html requires data.html"));
+}
+
+#[test]
+fn test_ready_strategy_wraps_init_script() {
+    let html = |library: &str, data: Value| -> String {
+        let request: RenderRequest = serde_json::from_value(json!({
+            "library": {"name": library, "version": "1.0.0"},
+            "data": data,
+            "options": {"width": 400, "height": 300, "format": "png"}
+        }))
+        .unwrap();
+        template::generate_html(&request, &[]).unwrap()
+    };
+
+    // Ready once the returned promise resolves, not when the script returns
+    let plotly = html("plotly", json!({"traces": []}));
+    assert!(plotly.contains("Promise.resolve((() => {"));
+    assert!(plotly.contains("return Plotly.newPlot("));
+
+    let highcharts = html("highcharts", json!({}));
+    assert!(highcharts.contains("const renderDone = (error) => {"));
+
+    let chartjs = html("chartjs", json!({}));
+    let drawn = chartjs.find("new Chart(ctx, config);").unwrap();
+    assert!(chartjs[drawn..].contains("window.renderReady = true;"));
+}
+
+#[test]
+fn test_extra_scripts_load_after_library_script() {
+    let path = std::env::temp_dir().join(format!("{}-plugin-template.toml", std::process::id()));
+    std::fs::write(&path, PLUGIN_TEMPLATE).unwrap();
+    registry::reload(&path).expect("Template should load");
+
+    let request: RenderRequest = serde_json::from_value(json!({
+        "library": {"name": "chartjs-datalabels", "version": "4.4.0"},
+        "data": {},
+        "options": {"width": 400, "height": 300, "format": "png"}
+    }))
+    .unwrap();
+    let html = template::generate_html(&request, &[]).unwrap();
+
+    let head = &html[..html.find("</head>").unwrap()];
+    assert!(head.contains("example@1.0.0/theme.css"));
+    let library = html.find("chart.js@4.4.0/dist/chart.umd.js").unwrap();
+    let plugin = html.find("chartjs-plugin-datalabels@2.2.0").unwrap();
+    let init = html.find("Chart.register(ChartDataLabels);").unwrap();
+    assert!(library < plugin && plugin < init);
+    std::fs::remove_file(path).ok();
+}
+
+#[test]
+fn test_chartjs_plugins_load_and_register() {
+    use rendering_engine::core::validation;
+
+    let request = |plugins: Value| -> RenderRequest {
+        serde_json::from_value(json!({
+            "library": {"name": "chartjs", "version": "4.4.0", "plugins": plugins},
+            "data": {"type": "bar", "data": {"labels": ["a"], "datasets": [{"data": [1]}]}},
+            "options": {"width": 400, "height": 300, "format": "png"}
+        }))
+        .unwrap()
+    };
+
+    let html = template::generate_html(&request(json!(["datalabels", "annotation"])), &[]).unwrap();
+    let library = html.find("chart.js@4.4.0/dist/chart.umd.js").unwrap();
+    let plugin = html.find("chartjs-plugin-datalabels@").unwrap();
+    assert!(library < plugin && html.contains("chartjs-plugin-annotation@"));
+    assert!(html.contains(
+        r#"Chart.register(...[window["ChartDataLabels"], window["chartjs-plugin-annotation"]]);"#
+    ));
+
+    let html = template::generate_html(&request(json!(null)), &[]).unwrap();
+    assert!(!html.contains("chartjs-plugin"));
+    assert!(html.contains("Chart.register(...[]);"));
+
+    let unknown = request(json!(["zoom"]));
+    let errors = validation::validate_request(&unknown);
+    assert!(errors.summary().contains("library.plugins: library 'chartjs' has no plugin 'zoom' (available: annotation, datalabels)"));
+    assert!(template::generate_html(&unknown, &[]).is_err());
+}
+
+#[test]
+fn test_missing_version_falls_back_to_default() {
+    let request: RenderRequest = serde_json::from_value(json!({
+        "library": {"name": "apache-echarts"},
+        "data": {"series": [{"type": "bar", "data": [1, 2, 3]}]},
+        "options": {"width": 400, "height": 300, "format": "png"}
+    }))
+    .unwrap();
+
+    let html = template::generate_html(&request, &[]).unwrap();
+    assert!(html.contains(
+        r#"<script src="https://cdn.jsdelivr.net/npm/echarts@5.4.0/dist/echarts.min.js"></script>"#
+    ));
+}
+
+#[test]
+fn test_unsubstituted_version_is_rejected() {
+    use rendering_engine::core::validation;
+
+    // Same registry as the other test in this binary; the plugin template
+    // has no default_version
+    let path = std::env::temp_dir().join(format!("{}-versionless.toml", std::process::id()));
+    std::fs::write(&path, PLUGIN_TEMPLATE).unwrap();
+    registry::reload(&path).expect("Template should load");
+
+    let request = |library: Value| -> RenderRequest {
+        serde_json::from_value(json!({
+            "library": library,
+            "data": {},
+            "options": {"width": 400, "height": 300, "format": "png"}
+        }))
+        .unwrap()
+    };
+
+    for (library, expected) in [
+        (
+            json!({"name": "chartjs-datalabels"}),
+            "body.library.version: library 'chartjs-datalabels' has no default version, set library.version",
+        ),
+        (
+            json!({
+                "name": "apache-echarts",
+                "cdn_url": "https://cdn.jsdelivr.net/npm/echarts@{version}/dist/echarts.min.js"
+            }),
+            "body.library.cdn_url: cdn_url must name a version, {version} is not substituted in it",
+        ),
+    ] {
+        let request = request(library);
+        assert_eq!(validation::validate_request(&request).summary(), expected);
+        assert!(template::generate_html(&request, &[]).is_err());
+    }
+
+    std::fs::remove_file(path).ok();
+}
@@ -0,0 +1,39 @@
+use poem::test::TestClient;
+use rendering_engine::core::renderer::RenderingEngine;
+use rendering_engine::settings::Config;
+use rendering_engine::{AppState, init_openapi_route};
+use serde_json::json;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_webp_render_round_trip() {
+    let engine =
+        RenderingEngine::with_config(1, 2, 2).expect("Failed to initialize rendering engine");
+    let app_state = Arc::new(AppState {
+        engine: Arc::new(engine),
+        storage: None,
+    });
+    let config: Config =
+        serde_json::from_value(json!({"env": "file", "host": "localhost", "port": 8080})).unwrap();
+    let cli = TestClient::new(init_openapi_route(app_state, &config));
+
+    let resp = cli
+        .post("/render")
+        .body_json(&json!({
+            "library": {"name": "apache-echarts", "version": "5.4.0"},
+            "data": {
+                "xAxis": {"data": ["A", "B", "C"]},
+                "yAxis": {},
+                "series": [{"type": "bar", "data": [10, 20, 30]}]
+            },
+            "options": {"width": 400, "height": 300, "format": "webp", "quality": 80}
+        }))
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    resp.assert_content_type("image/webp");
+
+    let webp = resp.0.into_body().into_vec().await.unwrap();
+    assert_eq!(&webp[..4], b"RIFF");
+    assert_eq!(&webp[8..12], b"WEBP");
+}